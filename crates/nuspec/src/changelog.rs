@@ -0,0 +1,142 @@
+/// The maximum length of the release notes accepted by nuget.org, in characters.
+pub const RELEASE_NOTES_LIMIT: usize = 35_000;
+
+/// The heading of a version section in a [Keep a Changelog](https://keepachangelog.com) file,
+/// such as `## [1.0.0] - 2017-06-20`.
+pub const DEFAULT_CHANGELOG_HEADING: &str = "## [{version}]";
+
+/// Extracts the section of a version from a changelog, without the heading.
+///
+/// The `heading` is the beginning of the heading line with a `{version}` placeholder, such as
+/// [`DEFAULT_CHANGELOG_HEADING`] or `# v{version}`. The section ends at the next heading of
+/// the same or a higher level. Returns `None` if there is no section for the version.
+pub fn changelog_section(content: &str, version: &str, heading: &str) -> Option<String> {
+    let heading = heading.replace("{version}", version);
+    let level = heading.chars().take_while(|c| *c == '#').count();
+    let mut lines = content.lines();
+    lines.find(|line| {
+        line.strip_prefix(heading.as_str()).is_some_and(|rest| {
+            !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+        })
+    })?;
+
+    let mut section = Vec::new();
+    let mut in_code = false;
+    for line in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+        }
+        if !in_code && level > 0 {
+            let line_level = line.chars().take_while(|c| *c == '#').count();
+            if line_level > 0 && line_level <= level {
+                break;
+            }
+        }
+        section.push(line);
+    }
+
+    // The link reference definitions at the end of the file belong to all sections
+    while let Some(last) = section.last() {
+        let last = last.trim();
+        let is_definition = last.starts_with('[') && last.contains("]:");
+        if last.is_empty() || is_definition {
+            section.pop();
+        } else {
+            break;
+        }
+    }
+    let start = section.iter().position(|l| !l.trim().is_empty())?;
+    Some(section[start..].join("\n"))
+}
+
+/// Trims the release notes to `limit` characters at a line boundary, marking the cut with `...`.
+pub fn trim_release_notes(notes: &str, limit: usize) -> String {
+    if notes.chars().count() <= limit {
+        return notes.to_string();
+    }
+    const MARKER: &str = "\n...";
+    let end = notes
+        .char_indices()
+        .nth(limit.saturating_sub(MARKER.len()))
+        .map_or(notes.len(), |(i, _)| i);
+    let end = if notes[end..].starts_with('\n') {
+        end
+    } else {
+        notes[..end].rfind('\n').unwrap_or(end)
+    };
+    format!("{}{MARKER}", notes[..end].trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = r#"# Changelog
+
+All notable changes to this project will be documented in this file.
+
+## [Unreleased]
+
+- Something new
+
+## [1.1.0] - 2024-03-01
+
+### Added
+
+- The `foo` option.
+
+```md
+## Not a heading
+```
+
+### Fixed
+
+- The crash on start.
+
+## [1.1.0-beta.1] - 2024-02-01
+
+- A preview.
+
+## [1.0.0] - 2024-01-01
+
+- The first release.
+
+[unreleased]: https://github.com/example/example/compare/v1.1.0...HEAD
+[1.1.0]: https://github.com/example/example/compare/v1.0.0...v1.1.0
+"#;
+
+    #[test]
+    fn test_changelog_section() {
+        assert_eq!(
+            changelog_section(CHANGELOG, "1.1.0", DEFAULT_CHANGELOG_HEADING).unwrap(),
+            "### Added\n\n- The `foo` option.\n\n```md\n## Not a heading\n```\n\n### Fixed\n\n- The crash on start."
+        );
+        assert_eq!(
+            changelog_section(CHANGELOG, "1.1.0-beta.1", DEFAULT_CHANGELOG_HEADING).unwrap(),
+            "- A preview."
+        );
+        assert_eq!(
+            changelog_section(CHANGELOG, "1.0.0", DEFAULT_CHANGELOG_HEADING).unwrap(),
+            "- The first release."
+        );
+        assert_eq!(
+            changelog_section(CHANGELOG, "1.0", DEFAULT_CHANGELOG_HEADING),
+            None
+        );
+        assert_eq!(
+            changelog_section("# v2.0.0\n\nBreaking.\n# v1.0.0\n", "2.0.0", "# v{version}"),
+            Some("Breaking.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_trim_release_notes() {
+        assert_eq!(trim_release_notes("short", 10), "short");
+        assert_eq!(
+            trim_release_notes("- first line\n- second line\n- third line", 30),
+            "- first line\n- second line\n..."
+        );
+        assert!(trim_release_notes(&"- é\n".repeat(100), 50).chars().count() <= 50);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{Files, Metadata, Package, from_str};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{error, fs, io, path};
+
+/// The xml namespace used by the Chocolatey package templates.
+pub const CHOCOLATEY_NAMESPACE: &str = "http://schemas.microsoft.com/packaging/2015/06/nuspec.xsd";
+
+/// The name of the verification file required for the packages with embedded binaries.
+pub const CHOCOLATEY_VERIFICATION_FILE: &str = "VERIFICATION.txt";
+
+/// A Chocolatey package specification, a nuspec with the Chocolatey metadata extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default)]
+#[serde(rename = "package")]
+pub struct ChocolateyPackage {
+    #[serde(rename = "@xmlns", skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub metadata: ChocolateyPackageMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<Files>,
+}
+
+/// The NuGet metadata followed by the Chocolatey-only elements.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default)]
+pub struct ChocolateyPackageMetadata {
+    #[serde(flatten)]
+    pub nuget: Metadata,
+    #[serde(flatten)]
+    pub chocolatey: ChocolateyMetadata,
+}
+
+/// The metadata elements that only Chocolatey knows, which are not valid in a NuGet package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ChocolateyMetadata {
+    /// A URL for the license of the package, which NuGet deprecates in favor of `license`.
+    #[serde(rename = "licenseUrl", skip_serializing_if = "Option::is_none")]
+    pub license_url: Option<String>,
+    /// A URL for the source code of the packaged software.
+    #[serde(rename = "projectSourceUrl", skip_serializing_if = "Option::is_none")]
+    pub project_source_url: Option<String>,
+    /// A URL for the source of the package definition itself.
+    #[serde(rename = "packageSourceUrl", skip_serializing_if = "Option::is_none")]
+    pub package_source_url: Option<String>,
+    /// A URL for the documentation of the packaged software.
+    #[serde(rename = "docsUrl", skip_serializing_if = "Option::is_none")]
+    pub docs_url: Option<String>,
+    /// A URL for the issue tracker of the packaged software.
+    #[serde(rename = "bugTrackerUrl", skip_serializing_if = "Option::is_none")]
+    pub bug_tracker_url: Option<String>,
+}
+
+impl ChocolateyPackage {
+    /// Extends a NuGet package with the Chocolatey metadata, in the namespace of the Chocolatey
+    /// templates.
+    ///
+    /// The `license` element is dropped if `licenseUrl` is set, because `choco pack` does not
+    /// accept both.
+    pub fn new(pkg: Package, chocolatey: ChocolateyMetadata) -> Self {
+        let mut nuget = pkg.metadata;
+        if chocolatey.license_url.is_some() {
+            nuget.license = None;
+        }
+        ChocolateyPackage {
+            namespace: Some(CHOCOLATEY_NAMESPACE.to_string()),
+            metadata: ChocolateyPackageMetadata { nuget, chocolatey },
+            files: pkg.files,
+        }
+    }
+}
+
+// The parts of a Chocolatey nuspec file read back after the generation.
+#[derive(Deserialize)]
+struct ChocolateyNuspec {
+    metadata: ChocolateyMetadata,
+    files: Option<Files>,
+}
+
+/// A command shim created by Chocolatey for a binary of the package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChocolateyShim {
+    /// The name of the command.
+    pub name: String,
+    /// The path of the binary relative to the `tools` directory of the package.
+    pub path: String,
+}
+
+/// Returns the `chocolateyInstall.ps1` script, which creates a shim for each binary.
+pub fn chocolatey_install_script(shims: &[ChocolateyShim]) -> String {
+    let mut script = String::from(
+        "$ErrorActionPreference = 'Stop'\n\
+         $toolsDir = Split-Path -Parent $MyInvocation.MyCommand.Definition\n\n",
+    );
+    for shim in shims {
+        script.push_str(&format!(
+            "Install-BinFile -Name '{}' -Path (Join-Path $toolsDir '{}')\n",
+            shim.name,
+            shim.path.replace('/', "\\")
+        ));
+    }
+    script
+}
+
+/// Returns the `chocolateyUninstall.ps1` script, which removes the shims of the binaries.
+pub fn chocolatey_uninstall_script(shims: &[ChocolateyShim]) -> String {
+    let mut script = String::from("$ErrorActionPreference = 'Stop'\n\n");
+    for shim in shims {
+        script.push_str(&format!("Uninstall-BinFile -Name '{}'\n", shim.name));
+    }
+    script
+}
+
+/// Returns the content of the `VERIFICATION.txt` file with the SHA256 checksum of each binary.
+/// The checksum of a binary that is not built yet is marked as pending.
+pub fn chocolatey_verification(
+    source_url: Option<&str>,
+    checksums: &[(String, Option<String>)],
+) -> String {
+    let mut text = String::from(
+        "VERIFICATION\n\
+         Verification is intended to assist the Chocolatey moderators and community\n\
+         in verifying that this package's contents are trustworthy.\n\n",
+    );
+    if let Some(url) = source_url {
+        text.push_str(&format!(
+            "The binaries are built from the sources at {url}.\n"
+        ));
+    }
+    text.push_str("The checksums can be verified with `Get-FileHash -Algorithm SHA256 <file>`.\n");
+    for (file, checksum) in checksums {
+        text.push_str(&format!(
+            "\n  File: {file}\n  Checksum Type: sha256\n  Checksum: {}\n",
+            checksum.as_deref().unwrap_or("pending")
+        ));
+    }
+    text
+}
+
+/// Returns the uppercase hex SHA256 checksum of a file, as printed by `Get-FileHash`.
+pub fn sha256_checksum<P: AsRef<path::Path>>(file: P) -> io::Result<String> {
+    Ok(Sha256::digest(fs::read(file)?)
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect())
+}
+
+/// Updates the checksums in the `VERIFICATION.txt` file of a generated Chocolatey package.
+///
+/// The build script generates the package before the binaries of the crate are compiled,
+/// so the checksums must be refreshed once the final binaries are built, right before packing.
+pub fn update_chocolatey_verification<P: AsRef<path::Path>>(
+    nuspec_file: P,
+) -> Result<(), Box<dyn error::Error>> {
+    let nuspec_file = nuspec_file.as_ref();
+    let nuspec_dir = nuspec_file
+        .parent()
+        .ok_or(format!("Failed to get the directory of {nuspec_file:?}"))?;
+    let pkg: ChocolateyNuspec = from_str(&fs::read_to_string(nuspec_file)?)?;
+    let files = pkg.files.map(|f| f.file).unwrap_or_default();
+    let target = |file: &crate::File| file.target_path().to_string();
+    let verification = files
+        .iter()
+        .find(|f| target(f).ends_with(CHOCOLATEY_VERIFICATION_FILE))
+        .ok_or(format!(
+            "No {CHOCOLATEY_VERIFICATION_FILE} file in {nuspec_file:?}"
+        ))?;
+
+    // The generator hides the binaries from the automatic shims with an `.ignore` file
+    let mut checksums = Vec::new();
+    for file in &files {
+        let ignore = format!("{}.ignore", target(file));
+        if files.iter().any(|f| target(f) == ignore) {
+            let name = target(file).trim_start_matches("tools/").replace('/', "\\");
+            checksums.push((
+                name,
+                Some(sha256_checksum(nuspec_dir.join(file.source_path()))?),
+            ));
+        }
+    }
+    let text = chocolatey_verification(pkg.metadata.project_source_url.as_deref(), &checksums);
+    fs::write(nuspec_dir.join(verification.source_path()), text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+    use crate::to_string_indent;
+
+    fn shims() -> Vec<ChocolateyShim> {
+        vec![
+            ChocolateyShim {
+                name: "first".to_string(),
+                path: "first.exe".to_string(),
+            },
+            ChocolateyShim {
+                name: "second".to_string(),
+                path: "net10.0/win-x64/second.exe".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_scripts() {
+        let install = chocolatey_install_script(&shims());
+        assert!(
+            install
+                .contains("Install-BinFile -Name 'first' -Path (Join-Path $toolsDir 'first.exe')")
+        );
+        assert!(install.contains("(Join-Path $toolsDir 'net10.0\\win-x64\\second.exe')"));
+        let uninstall = chocolatey_uninstall_script(&shims());
+        assert!(
+            uninstall
+                .ends_with("Uninstall-BinFile -Name 'first'\nUninstall-BinFile -Name 'second'\n")
+        );
+    }
+
+    #[test]
+    fn test_package() {
+        let pkg: Package = from_str(
+            r#"<package>
+  <metadata>
+    <id>example</id>
+    <license type="expression">MIT</license>
+  </metadata>
+</package>"#,
+        )
+        .unwrap();
+        let chocolatey = ChocolateyPackage::new(
+            pkg.clone(),
+            ChocolateyMetadata {
+                docs_url: Some("https://docs.rs/example".to_string()),
+                ..Default::default()
+            },
+        );
+        let xml = to_string_indent(&chocolatey, ' ', 2).unwrap();
+        assert!(xml.contains(CHOCOLATEY_NAMESPACE), "{xml}");
+        assert!(
+            xml.contains(r#"<license type="expression">MIT</license>"#),
+            "{xml}"
+        );
+        assert!(
+            xml.contains("<docsUrl>https://docs.rs/example</docsUrl>"),
+            "{xml}"
+        );
+
+        let chocolatey = ChocolateyPackage::new(
+            pkg,
+            ChocolateyMetadata {
+                license_url: Some("https://example.com/LICENSE".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(chocolatey.metadata.nuget.license, None);
+        let xml = to_string_indent(&chocolatey, ' ', 2).unwrap();
+        assert!(
+            xml.contains("<licenseUrl>https://example.com/LICENSE</licenseUrl>"),
+            "{xml}"
+        );
+    }
+
+    #[test]
+    fn test_update_verification() {
+        let dir = TempDir::new("chocolatey");
+        fs::write(dir.join("tool.exe"), b"abc").unwrap();
+        fs::write(dir.join("tool.exe.ignore"), b"").unwrap();
+        fs::write(
+            dir.join("example.nuspec"),
+            r#"<package>
+  <metadata>
+    <id>example</id>
+    <projectSourceUrl>https://example.com/src</projectSourceUrl>
+  </metadata>
+  <files>
+    <file src="tool.exe" target="tools\tool.exe"/>
+    <file src="tool.exe.ignore" target="tools/tool.exe.ignore"/>
+    <file src="VERIFICATION.txt" target="tools/VERIFICATION.txt"/>
+  </files>
+</package>"#,
+        )
+        .unwrap();
+
+        update_chocolatey_verification(dir.join("example.nuspec")).unwrap();
+        let text = fs::read_to_string(dir.join("VERIFICATION.txt")).unwrap();
+        assert!(text.contains("built from the sources at https://example.com/src."));
+        assert!(text.contains(
+            "  File: tool.exe\n  Checksum Type: sha256\n  Checksum: BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD\n"
+        ));
+        assert!(!text.contains("ignore"));
+    }
+}
@@ -0,0 +1,330 @@
+//! The `package.metadata.nuspec` table of the crate manifest.
+//!
+//! The package definition is written with the natural snake_case or kebab-case keys of the
+//! [`neutral`] model:
+//!
+//! ```toml
+//! [package.metadata.nuspec.metadata]
+//! require-license-acceptance = false
+//! license = { expression = "MIT" }
+//!
+//! [[package.metadata.nuspec.metadata.dependency-groups]]
+//! target-framework = "net8.0"
+//! dependencies = [{ id = "Example.Dependency", version = "[1.0.0, 2.0.0)" }]
+//!
+//! [[package.metadata.nuspec.files]]
+//! src = "../../LICENSE-MIT"
+//! ```
+//!
+//! The XML-shaped `package` table with camelCase keys is still accepted for compatibility.
+use crate::parse::{closest_name, expected, quoted};
+use crate::{MergePolicy, Package, PathSeparator, neutral};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::error;
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NuspecConfig {
+    /// The package metadata with snake_case or kebab-case keys.
+    #[serde(default)]
+    pub metadata: Option<neutral::Metadata>,
+    /// The files to include in the package.
+    #[serde(default)]
+    pub files: Option<Vec<neutral::File>>,
+    /// The legacy package definition with the same camelCase structure as the xml file.
+    #[serde(default)]
+    pub package: Option<Package>,
+    #[serde(default, alias = "out-dir")]
+    pub out_dir: Option<String>,
+    /// A path to a shared defaults file, relative to the crate manifest.
+    /// It is either a nuspec file or a TOML file with the `metadata` and `files` keys.
+    #[serde(default)]
+    pub defaults: Option<String>,
+    /// The policy to layer the package definition on top of the defaults file.
+    #[serde(default, alias = "merge-policy")]
+    pub merge_policy: Option<MergePolicy>,
+    /// Packages a binary as a .NET tool.
+    #[serde(default, alias = "dotnet-tool")]
+    pub dotnet_tool: Option<DotnetToolConfig>,
+    /// Splits the debug files into a symbols package, see the `symbols` key.
+    #[serde(default)]
+    pub symbols: Option<bool>,
+    /// A path to the package icon, relative to the crate manifest.
+    /// The file is added to the package root and validated against the NuGet rules.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// The separator of the package paths in the generated nuspec files, `slash` by default.
+    #[serde(default, alias = "path-separator")]
+    pub path_separator: Option<PathSeparator>,
+    /// Writes the files in a canonical form, so the builds of the same commit produce the same output.
+    #[serde(default)]
+    pub deterministic: Option<bool>,
+    /// Generates the copyright notice from a template.
+    #[serde(default)]
+    pub copyright: Option<CopyrightConfig>,
+    /// Takes the release notes from a changelog file.
+    #[serde(default, alias = "release-notes")]
+    pub release_notes: Option<ReleaseNotesConfig>,
+    /// The validation of the package readme.
+    #[serde(default)]
+    pub readme: Option<ReadmeConfig>,
+    /// Generates a Chocolatey package next to the NuGet one.
+    #[serde(default)]
+    pub chocolatey: Option<ChocolateyConfig>,
+}
+
+/// The `dotnet-tool` table, which packages a `[[bin]]` target as a native executable .NET tool,
+/// so it can be installed with `dotnet tool install`.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DotnetToolConfig {
+    /// The name of the command, the name of the binary by default.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// The binary to package, required if the crate has several `[[bin]]` targets.
+    #[serde(default)]
+    pub bin: Option<String>,
+    /// The target framework of the tool path, `net10.0` by default.
+    #[serde(default, alias = "target-framework")]
+    pub target_framework: Option<String>,
+    /// The runtime identifiers listed by the top-level package, each published as a separate
+    /// `<id>.<rid>` package. The runtime identifier of the build target is always listed.
+    #[serde(default, alias = "runtime-identifiers")]
+    pub runtime_identifiers: Option<Vec<String>>,
+}
+
+impl NuspecConfig {
+    /// Converts the configured package definition to the nuspec model.
+    pub fn to_package(&self) -> Result<Package, Box<dyn error::Error>> {
+        if self.package.is_some() && (self.metadata.is_some() || self.files.is_some()) {
+            return Err(
+                "The `package` table can not be combined with the `metadata` and `files` keys, \
+                please move its content to `metadata` and `files`"
+                    .into(),
+            );
+        }
+        if let Some(pkg) = &self.package {
+            return Ok(pkg.clone());
+        }
+        Ok(neutral::Package {
+            xmlns: None,
+            metadata: self.metadata.clone().unwrap_or_default(),
+            files: self.files.clone(),
+        }
+        .into())
+    }
+}
+
+/// The `readme` table, which controls how the package readme is prepared.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReadmeConfig {
+    /// Generates a `README.md` from the package metadata if the crate has no Markdown readme,
+    /// disabled by default.
+    #[serde(default)]
+    pub fallback: Option<bool>,
+    /// Rewrites the relative links to absolute URLs of the repository at the current commit.
+    #[serde(default, alias = "rewrite-links")]
+    pub rewrite_links: Option<bool>,
+    /// Fails the build on a readme that is not a Markdown file instead of warning,
+    /// disabled by default.
+    #[serde(default)]
+    pub strict: Option<bool>,
+}
+
+/// The `copyright` table, which generates the copyright notice unless it is set in the metadata.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CopyrightConfig {
+    /// The template with the `{year}` and `{authors}` placeholders, `Copyright © {year} {authors}` by default.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// The first year of the copyright, rendered as a range up to the current year.
+    #[serde(default, alias = "start-year")]
+    pub start_year: Option<i32>,
+}
+
+/// The `release-notes` table, which sets the release notes to the section of the current version
+/// in a Keep a Changelog style file.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReleaseNotesConfig {
+    /// A path to the changelog relative to the crate manifest, `CHANGELOG.md` by default.
+    #[serde(default)]
+    pub changelog: Option<String>,
+    /// The beginning of the version headings with a `{version}` placeholder, `## [{version}]` by default.
+    #[serde(default)]
+    pub heading: Option<String>,
+}
+
+/// The `chocolatey` table, which generates a Chocolatey package with the shims for the binaries.
+///
+/// The Chocolatey-specific metadata is set only in the Chocolatey package, see
+/// [`crate::ChocolateyMetadata`]. The `license` is dropped from it if `license-url` is set,
+/// because `choco pack` does not accept `licenseUrl` together with `license`.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ChocolateyConfig {
+    /// The package id, the NuGet package id in lowercase by default.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default, alias = "package-source-url")]
+    pub package_source_url: Option<String>,
+    /// The Cargo package repository by default.
+    #[serde(default, alias = "project-source-url")]
+    pub project_source_url: Option<String>,
+    #[serde(default, alias = "docs-url")]
+    pub docs_url: Option<String>,
+    #[serde(default, alias = "bug-tracker-url")]
+    pub bug_tracker_url: Option<String>,
+    #[serde(default, alias = "license-url")]
+    pub license_url: Option<String>,
+}
+
+/// Deserializes a TOML document, extending the errors about unknown keys with the closest known key.
+pub(crate) fn from_toml_str<T: DeserializeOwned>(
+    content: &str,
+) -> Result<T, Box<dyn error::Error>> {
+    toml::from_str(content).map_err(|err| match suggest(err.message()) {
+        Some(hint) => format!("{}\nhelp: {hint}", err.to_string().trim_end()).into(),
+        None => err.into(),
+    })
+}
+
+fn suggest(message: &str) -> Option<String> {
+    let name = quoted(message, "unknown field `").or(quoted(message, "unknown variant `"))?;
+    let candidates = expected(message);
+    closest_name(name, &candidates).map(|candidate| format!("did you mean `{candidate}`?"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BuildAction, License};
+
+    #[derive(Debug, Deserialize)]
+    struct Manifest {
+        nuspec: NuspecConfig,
+    }
+
+    fn load(content: &str) -> Result<Package, Box<dyn error::Error>> {
+        from_toml_str::<Manifest>(content)?.nuspec.to_package()
+    }
+
+    #[test]
+    fn test_kebab_and_snake_case_keys() {
+        let pkg = load(
+            r#"
+            [nuspec.metadata]
+            id = "example"
+            require-license-acceptance = true
+            development_dependency = true
+            license = { expression = "MIT" }
+
+            [[nuspec.metadata.dependency-groups]]
+            target-framework = "net8.0"
+            dependencies = [{ id = "X", version = "[1.0.0, 2.0.0)" }]
+
+            [[nuspec.metadata.content_files]]
+            include = "cs/**/*.cs"
+            build-action = "Compile"
+            copy-to-output = false
+
+            [[nuspec.files]]
+            src = "LICENSE"
+            target = "docs"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(pkg.metadata.id, "example");
+        assert_eq!(pkg.metadata.require_license_acceptance, Some(true));
+        assert_eq!(pkg.metadata.development_dependency, Some(true));
+        assert_eq!(
+            pkg.metadata.license,
+            Some(License::Expression("MIT".to_string()))
+        );
+        let groups = pkg.metadata.dependencies.unwrap().group.unwrap();
+        assert_eq!(groups[0].target_framework.as_deref(), Some("net8.0"));
+        assert_eq!(groups[0].dependency[0].id, "X");
+        let content_files = pkg.metadata.content_files.unwrap().files;
+        assert_eq!(content_files[0].build_action, Some(BuildAction::Compile));
+        assert_eq!(content_files[0].copy_to_output, Some(false));
+        let files = pkg.files.unwrap().file;
+        assert_eq!(files[0].src, "LICENSE");
+        assert_eq!(files[0].target.as_deref(), Some("docs"));
+    }
+
+    #[test]
+    fn test_legacy_package_table() {
+        let pkg = load(
+            r#"
+            [nuspec.package.metadata]
+            requireLicenseAcceptance = false
+
+            [[nuspec.package.files.file]]
+            src = "LICENSE"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(pkg.metadata.require_license_acceptance, Some(false));
+        assert_eq!(pkg.files.unwrap().file[0].src, "LICENSE");
+    }
+
+    #[test]
+    fn test_legacy_and_new_keys_conflict() {
+        let err = load(
+            r#"
+            [nuspec.package.metadata]
+            id = "a"
+            [nuspec.metadata]
+            id = "b"
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("can not be combined"), "{err}");
+    }
+
+    #[test]
+    fn test_unknown_key_suggestion() {
+        let err = load(
+            r#"
+            [nuspec.metadata]
+            requireLicenseAcceptance = true
+            "#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("line 3"), "{err}");
+        assert!(
+            err.ends_with("help: did you mean `require-license-acceptance`?"),
+            "{err}"
+        );
+
+        let err = load("[nuspec]\nout-dri = \"x\"").unwrap_err().to_string();
+        assert!(err.ends_with("help: did you mean `out-dir`?"), "{err}");
+    }
+
+    #[test]
+    fn test_dotnet_tool_table() {
+        let config = from_toml_str::<Manifest>(
+            r#"
+            [nuspec.dotnet-tool]
+            command = "example"
+            bin = "example-cli"
+            target-framework = "net9.0"
+            runtime-identifiers = ["linux-x64", "win-x64"]
+            "#,
+        )
+        .unwrap()
+        .nuspec;
+        let tool = config.dotnet_tool.unwrap();
+        assert_eq!(tool.command.as_deref(), Some("example"));
+        assert_eq!(tool.bin.as_deref(), Some("example-cli"));
+        assert_eq!(tool.target_framework.as_deref(), Some("net9.0"));
+        assert_eq!(
+            tool.runtime_identifiers,
+            Some(vec!["linux-x64".to_string(), "win-x64".to_string()])
+        );
+    }
+}
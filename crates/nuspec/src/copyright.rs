@@ -0,0 +1,92 @@
+/// The copyright template used when only a start year is configured.
+pub const DEFAULT_COPYRIGHT_TEMPLATE: &str = "Copyright © {year} {authors}";
+
+/// Renders a copyright notice from a template with the `{year}` and `{authors}` placeholders.
+///
+/// The `{year}` placeholder becomes a range, such as `2019-2024`, if the `start_year` is before
+/// the `year`. The e-mail addresses of the authors, as in `Name <name@example.com>`, are omitted.
+pub fn render_copyright(
+    template: &str,
+    authors: &[String],
+    year: i32,
+    start_year: Option<i32>,
+) -> String {
+    let years = match start_year {
+        Some(start) if start < year => format!("{start}-{year}"),
+        _ => year.to_string(),
+    };
+    let authors = authors
+        .iter()
+        .map(|author| match author.find('<') {
+            Some(i) => author[..i].trim(),
+            None => author.trim(),
+        })
+        .filter(|author| !author.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    template
+        .replace("{year}", &years)
+        .replace("{authors}", &authors)
+}
+
+/// Returns the UTC year of a Unix timestamp, such as `SOURCE_DATE_EPOCH`.
+pub fn year_from_timestamp(timestamp: i64) -> i32 {
+    civil_from_timestamp(timestamp).0
+}
+
+// Returns the UTC date and the seconds since midnight of a Unix timestamp, using the civil
+// calendar conversion from http://howardhinnant.github.io/date_algorithms.html
+pub(crate) fn civil_from_timestamp(timestamp: i64) -> (i32, u32, u32, u32) {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400) as u32;
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month, day, seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_copyright() {
+        let authors = vec![
+            "Jane Doe <jane@example.com>".to_string(),
+            "John Doe".to_string(),
+        ];
+        assert_eq!(
+            render_copyright(DEFAULT_COPYRIGHT_TEMPLATE, &authors, 2024, None),
+            "Copyright © 2024 Jane Doe, John Doe"
+        );
+        assert_eq!(
+            render_copyright("(c) {year} {authors}", &authors[1..], 2024, Some(2019)),
+            "(c) 2019-2024 John Doe"
+        );
+        assert_eq!(
+            render_copyright("(c) {year} Example", &[], 2024, Some(2024)),
+            "(c) 2024 Example"
+        );
+    }
+
+    #[test]
+    fn test_year_from_timestamp() {
+        assert_eq!(year_from_timestamp(0), 1970);
+        assert_eq!(year_from_timestamp(1_704_067_199), 2023);
+        assert_eq!(year_from_timestamp(1_704_067_200), 2024);
+        assert_eq!(year_from_timestamp(1_735_689_599), 2024);
+        assert_eq!(year_from_timestamp(-1), 1969);
+        assert_eq!(civil_from_timestamp(951_827_696), (2000, 2, 29, 45_296));
+    }
+}
@@ -0,0 +1,850 @@
+use crate::{
+    ContentFile, Dependencies, Dependency, Files, License, Metadata, Package, PackageType,
+    References, Repository, VersionRange,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// A structured difference between two package definitions.
+///
+/// Element order and formatting are ignored, so only the changes that matter for the consumers of
+/// the package are reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default)]
+pub struct PackageDiff {
+    pub changes: Vec<Change>,
+}
+
+/// A single change between two package definitions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    /// A metadata field, named as in the nuspec file, such as `projectUrl`, has been changed.
+    MetadataChanged {
+        field: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// The license expression or file has been changed.
+    LicenseChanged {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// A dependency has been added. The target framework is `None` for the dependencies
+    /// that apply to all frameworks.
+    DependencyAdded {
+        target_framework: Option<String>,
+        id: String,
+        version: String,
+    },
+    /// A dependency has been removed.
+    DependencyRemoved {
+        target_framework: Option<String>,
+        id: String,
+        version: String,
+    },
+    /// The included or excluded assets of a dependency have been changed.
+    DependencyAssetsChanged {
+        target_framework: Option<String>,
+        id: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// The version range of a dependency has been changed.
+    DependencyRangeChanged {
+        target_framework: Option<String>,
+        id: String,
+        old: String,
+        new: String,
+        range: RangeChange,
+    },
+    /// A file has been added to the package.
+    FileAdded { src: String, target: Option<String> },
+    /// A file has been removed from the package.
+    FileRemoved { src: String, target: Option<String> },
+    /// A file is placed to a different folder within the package.
+    FileRetargeted {
+        src: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// The exclusion patterns of a file have been changed.
+    FileExcludeChanged {
+        src: String,
+        target: Option<String>,
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+/// How a dependency version range has been changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeChange {
+    /// The new range accepts every version of the old one and more.
+    Widened,
+    /// The old range accepts every version of the new one and more.
+    Narrowed,
+    /// The ranges overlap partially, do not overlap or can not be parsed.
+    Changed,
+}
+
+impl PackageDiff {
+    /// Returns true if the packages are semantically equal.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Renders the changes as a pretty-printed JSON array.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.changes)
+    }
+}
+
+impl Display for PackageDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::MetadataChanged { field, old, new } => {
+                write!(f, "{field} {}", describe(old, new))
+            }
+            Change::LicenseChanged { old, new } => write!(f, "license {}", describe(old, new)),
+            Change::DependencyAdded {
+                target_framework,
+                id,
+                version,
+            } => write!(
+                f,
+                "dependency {id} {version} added{}",
+                framework(target_framework)
+            ),
+            Change::DependencyRemoved {
+                target_framework,
+                id,
+                version,
+            } => write!(
+                f,
+                "dependency {id} {version} removed{}",
+                framework(target_framework)
+            ),
+            Change::DependencyAssetsChanged {
+                target_framework,
+                id,
+                old,
+                new,
+            } => write!(
+                f,
+                "dependency {id} assets {}{}",
+                describe(old, new),
+                framework(target_framework)
+            ),
+            Change::DependencyRangeChanged {
+                target_framework,
+                id,
+                old,
+                new,
+                range,
+            } => {
+                let verb = match range {
+                    RangeChange::Widened => "widened",
+                    RangeChange::Narrowed => "narrowed",
+                    RangeChange::Changed => "changed",
+                };
+                write!(
+                    f,
+                    "dependency {id} {verb} from {old} to {new}{}",
+                    framework(target_framework)
+                )
+            }
+            Change::FileAdded { src, target } => {
+                write!(f, "file {src} added{}", file_target(target))
+            }
+            Change::FileRemoved { src, target } => {
+                write!(f, "file {src} removed{}", file_target(target))
+            }
+            Change::FileRetargeted { src, old, new } => write!(
+                f,
+                "file {src} retargeted from {} to {}",
+                package_root(old),
+                package_root(new)
+            ),
+            Change::FileExcludeChanged {
+                src,
+                target,
+                old,
+                new,
+            } => write!(
+                f,
+                "file {src} exclude {}{}",
+                describe(old, new),
+                file_target(target)
+            ),
+        }
+    }
+}
+
+fn describe(old: &Option<String>, new: &Option<String>) -> String {
+    match (old, new) {
+        (Some(old), Some(new)) => format!("changed from \"{old}\" to \"{new}\""),
+        (None, Some(new)) => format!("set to \"{new}\""),
+        (Some(old), None) => format!("removed (was \"{old}\")"),
+        (None, None) => "unchanged".to_string(),
+    }
+}
+
+fn framework(target_framework: &Option<String>) -> String {
+    match target_framework {
+        Some(tfm) => format!(" for {tfm}"),
+        None => String::new(),
+    }
+}
+
+fn file_target(target: &Option<String>) -> String {
+    match target {
+        Some(target) if !target.is_empty() => format!(" with target {target}"),
+        _ => String::new(),
+    }
+}
+
+fn package_root(target: &Option<String>) -> &str {
+    match target.as_deref() {
+        Some(target) if !target.is_empty() => target,
+        _ => "the package root",
+    }
+}
+
+fn repository(repository: &Repository) -> String {
+    [
+        ("type", &repository.repository_type),
+        ("url", &repository.url),
+        ("branch", &repository.branch),
+        ("commit", &repository.commit),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.as_ref().map(|v| format!("{name}={v}")))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+// Renders the items of a collection whose order does not matter.
+fn sorted(items: impl Iterator<Item = String>) -> String {
+    let mut items: Vec<_> = items.collect();
+    items.sort();
+    items.join(", ")
+}
+
+fn package_type(package_type: &PackageType) -> String {
+    match &package_type.version {
+        Some(version) => format!("{} {version}", package_type.name),
+        None => package_type.name.to_string(),
+    }
+}
+
+fn references(references: &References) -> String {
+    let ungrouped = references.reference.iter().flatten().map(|r| (&None, r));
+    let grouped = references
+        .group
+        .iter()
+        .flatten()
+        .flat_map(|g| g.reference.iter().map(move |r| (&g.target_framework, r)));
+    sorted(
+        ungrouped
+            .chain(grouped)
+            .map(|(tfm, r)| format!("{}{}", r.file, framework(tfm))),
+    )
+}
+
+// The content files are kept in order, because the later entries override the earlier ones.
+fn content_file(file: &ContentFile) -> String {
+    let attributes = [
+        ("exclude", file.exclude.as_ref().map(|e| e.join(";"))),
+        (
+            "buildAction",
+            file.build_action.as_ref().map(|b| b.to_string()),
+        ),
+        ("copyToOutput", file.copy_to_output.map(|v| v.to_string())),
+        ("flatten", file.flatten.map(|v| v.to_string())),
+    ];
+    attributes
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|v| format!(" {name}={v}")))
+        .fold(file.include.clone(), |text, attribute| text + &attribute)
+}
+
+fn assets(dependency: &Dependency) -> Option<String> {
+    let assets: Vec<_> = [
+        ("include", &dependency.include),
+        ("exclude", &dependency.exclude),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.as_ref().map(|v| format!("{name}={}", v.join(","))))
+    .collect();
+    (!assets.is_empty()).then(|| assets.join(" "))
+}
+
+impl Package {
+    /// Compares the package with another one and returns the semantic changes needed to
+    /// turn this package into the other.
+    pub fn diff(&self, other: &Package) -> PackageDiff {
+        let mut changes = Vec::new();
+        diff_metadata(&self.metadata, &other.metadata, &mut changes);
+        diff_dependencies(
+            &self.metadata.dependencies,
+            &other.metadata.dependencies,
+            &mut changes,
+        );
+        diff_files(&self.files, &other.files, &mut changes);
+        PackageDiff { changes }
+    }
+}
+
+fn diff_metadata(old: &Metadata, new: &Metadata, changes: &mut Vec<Change>) {
+    let non_empty = |s: &str| {
+        if s.is_empty() {
+            None
+        } else {
+            Some(s.to_string())
+        }
+    };
+    let fields: [(&str, Option<String>, Option<String>); 20] = [
+        ("id", non_empty(&old.id), non_empty(&new.id)),
+        ("version", non_empty(&old.version), non_empty(&new.version)),
+        (
+            "description",
+            non_empty(&old.description),
+            non_empty(&new.description),
+        ),
+        (
+            "authors",
+            non_empty(&old.authors.join(",")),
+            non_empty(&new.authors.join(",")),
+        ),
+        (
+            "projectUrl",
+            old.project_url.clone(),
+            new.project_url.clone(),
+        ),
+        ("icon", old.icon.clone(), new.icon.clone()),
+        ("readme", old.readme.clone(), new.readme.clone()),
+        (
+            "requireLicenseAcceptance",
+            old.require_license_acceptance.map(|v| v.to_string()),
+            new.require_license_acceptance.map(|v| v.to_string()),
+        ),
+        (
+            "developmentDependency",
+            old.development_dependency.map(|v| v.to_string()),
+            new.development_dependency.map(|v| v.to_string()),
+        ),
+        (
+            "releaseNotes",
+            old.release_notes.clone(),
+            new.release_notes.clone(),
+        ),
+        ("copyright", old.copyright.clone(), new.copyright.clone()),
+        ("language", old.language.clone(), new.language.clone()),
+        (
+            "tags",
+            old.tags.as_ref().map(|t| t.join(" ")),
+            new.tags.as_ref().map(|t| t.join(" ")),
+        ),
+        (
+            "repository",
+            old.repository.as_ref().map(repository),
+            new.repository.as_ref().map(repository),
+        ),
+        ("title", old.title.clone(), new.title.clone()),
+        (
+            "minClientVersion",
+            old.min_client_version.clone(),
+            new.min_client_version.clone(),
+        ),
+        (
+            "packageTypes",
+            old.package_types.as_ref().map(|p| {
+                p.package_type
+                    .iter()
+                    .map(package_type)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }),
+            new.package_types.as_ref().map(|p| {
+                p.package_type
+                    .iter()
+                    .map(package_type)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }),
+        ),
+        (
+            "frameworkAssemblies",
+            old.framework_assemblies.as_ref().map(|a| {
+                sorted(
+                    a.framework_assembly
+                        .iter()
+                        .map(|f| format!("{}{}", f.assembly_name, framework(&f.target_framework))),
+                )
+            }),
+            new.framework_assemblies.as_ref().map(|a| {
+                sorted(
+                    a.framework_assembly
+                        .iter()
+                        .map(|f| format!("{}{}", f.assembly_name, framework(&f.target_framework))),
+                )
+            }),
+        ),
+        (
+            "references",
+            old.references.as_ref().map(references),
+            new.references.as_ref().map(references),
+        ),
+        (
+            "contentFiles",
+            old.content_files.as_ref().map(|c| {
+                c.files
+                    .iter()
+                    .map(content_file)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+            new.content_files.as_ref().map(|c| {
+                c.files
+                    .iter()
+                    .map(content_file)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+        ),
+    ];
+    for (field, old, new) in fields {
+        if old != new {
+            changes.push(Change::MetadataChanged {
+                field: field.to_string(),
+                old,
+                new,
+            });
+        }
+    }
+    if old.license != new.license {
+        changes.push(Change::LicenseChanged {
+            old: old.license.as_ref().map(License::to_string),
+            new: new.license.as_ref().map(License::to_string),
+        });
+    }
+}
+
+/// A dependency with its original target framework, package id and version range.
+struct DependencyEntry {
+    target_framework: Option<String>,
+    id: String,
+    version: String,
+    assets: Option<String>,
+}
+
+/// The dependencies keyed by the lowercase target framework and package id, the ids and
+/// frameworks are case-insensitive in NuGet, and by the occurrence of the id in the framework,
+/// so the duplicate ids are compared in order rather than collapsed.
+type DependencyMap = BTreeMap<(Option<String>, String, usize), DependencyEntry>;
+
+fn collect_dependencies(dependencies: &Option<Dependencies>) -> DependencyMap {
+    let mut map = BTreeMap::new();
+    let Some(dependencies) = dependencies else {
+        return map;
+    };
+    let ungrouped = dependencies.dependency.iter().flatten().map(|d| (None, d));
+    let grouped = dependencies.group.iter().flatten().flat_map(|g| {
+        g.dependency
+            .iter()
+            .map(move |d| (g.target_framework.clone(), d))
+    });
+    let mut occurrences = BTreeMap::new();
+    for (tfm, dependency) in ungrouped.chain(grouped) {
+        let key = (
+            tfm.as_ref().map(|t: &String| t.to_lowercase()),
+            dependency.id.to_lowercase(),
+        );
+        let occurrence = occurrences.entry(key.clone()).or_insert(0);
+        *occurrence += 1;
+        map.insert(
+            (key.0, key.1, *occurrence),
+            DependencyEntry {
+                target_framework: tfm,
+                id: dependency.id.clone(),
+                version: dependency.version.clone(),
+                assets: assets(dependency),
+            },
+        );
+    }
+    map
+}
+
+fn diff_dependencies(
+    old: &Option<Dependencies>,
+    new: &Option<Dependencies>,
+    changes: &mut Vec<Change>,
+) {
+    let old = collect_dependencies(old);
+    let new = collect_dependencies(new);
+    for (key, entry) in &old {
+        let Some(new_entry) = new.get(key) else {
+            changes.push(Change::DependencyRemoved {
+                target_framework: entry.target_framework.clone(),
+                id: entry.id.clone(),
+                version: entry.version.clone(),
+            });
+            continue;
+        };
+        if let Some(range) = range_change(&entry.version, &new_entry.version) {
+            changes.push(Change::DependencyRangeChanged {
+                target_framework: entry.target_framework.clone(),
+                id: entry.id.clone(),
+                old: entry.version.clone(),
+                new: new_entry.version.clone(),
+                range,
+            });
+        }
+        if entry.assets != new_entry.assets {
+            changes.push(Change::DependencyAssetsChanged {
+                target_framework: entry.target_framework.clone(),
+                id: entry.id.clone(),
+                old: entry.assets.clone(),
+                new: new_entry.assets.clone(),
+            });
+        }
+    }
+    for (key, entry) in new {
+        if !old.contains_key(&key) {
+            changes.push(Change::DependencyAdded {
+                target_framework: entry.target_framework,
+                id: entry.id,
+                version: entry.version,
+            });
+        }
+    }
+}
+
+// Returns how a version range has been changed, or `None` if it is equal.
+fn range_change(old: &str, new: &str) -> Option<RangeChange> {
+    match (old.parse::<VersionRange>(), new.parse::<VersionRange>()) {
+        (Ok(old_range), Ok(new_range)) if old_range == new_range => None,
+        (Ok(old_range), Ok(new_range)) if new_range.is_superset_of(&old_range) => {
+            Some(RangeChange::Widened)
+        }
+        (Ok(old_range), Ok(new_range)) if old_range.is_superset_of(&new_range) => {
+            Some(RangeChange::Narrowed)
+        }
+        _ if old == new => None,
+        _ => Some(RangeChange::Changed),
+    }
+}
+
+/// The files keyed by their source and target with the normalized separators, so a source
+/// packaged to several targets is compared entry by entry. The values are the exclusion patterns.
+type FileMap = BTreeMap<(String, Option<String>), Option<String>>;
+
+fn diff_files(old: &Option<Files>, new: &Option<Files>, changes: &mut Vec<Change>) {
+    let collect = |files: &Option<Files>| -> FileMap {
+        files
+            .iter()
+            .flat_map(|f| f.file.iter())
+            .map(|f| {
+                (
+                    (
+                        f.src.replace('\\', "/"),
+                        f.target.as_ref().map(|t| t.replace('\\', "/")),
+                    ),
+                    f.exclude.as_ref().map(|e| e.join(";")),
+                )
+            })
+            .collect()
+    };
+    let old = collect(old);
+    let new = collect(new);
+    // A source packaged once on both sides with different targets has been retargeted
+    let single = |files: &FileMap, src: &str| {
+        let mut targets = files.keys().filter(|(s, _)| s == src);
+        match (targets.next(), targets.next()) {
+            (Some((_, target)), None) => Some(target.clone()),
+            _ => None,
+        }
+    };
+    let retargeted = |src: &str| match (single(&old, src), single(&new, src)) {
+        (Some(old_target), Some(new_target)) if old_target != new_target => {
+            Some((old_target, new_target))
+        }
+        _ => None,
+    };
+    for ((src, target), exclude) in &old {
+        match new.get(&(src.clone(), target.clone())) {
+            None => match retargeted(src) {
+                Some((old_target, new_target)) => changes.push(Change::FileRetargeted {
+                    src: src.clone(),
+                    old: old_target,
+                    new: new_target,
+                }),
+                None => changes.push(Change::FileRemoved {
+                    src: src.clone(),
+                    target: target.clone(),
+                }),
+            },
+            Some(new_exclude) if new_exclude != exclude => {
+                changes.push(Change::FileExcludeChanged {
+                    src: src.clone(),
+                    target: target.clone(),
+                    old: exclude.clone(),
+                    new: new_exclude.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (src, target) in new.keys() {
+        if !old.contains_key(&(src.clone(), target.clone())) && retargeted(src).is_none() {
+            changes.push(Change::FileAdded {
+                src: src.clone(),
+                target: target.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ContentFiles, DependencyGroup, File, FrameworkAssemblies, FrameworkAssembly,
+        KnownPackageType, PackageTypes,
+    };
+
+    fn package() -> Package {
+        Package {
+            metadata: Metadata {
+                id: "example-package".to_string(),
+                version: "1.0.0".to_string(),
+                description: "An example NuGet package".to_string(),
+                authors: vec!["Author One".to_string()],
+                license: Some(License::Expression("MIT".to_string())),
+                dependencies: Some(Dependencies {
+                    group: Some(vec![DependencyGroup {
+                        target_framework: Some("net8.0".to_string()),
+                        dependency: vec![
+                            Dependency {
+                                id: "X".to_string(),
+                                version: "[1,2)".to_string(),
+                                ..Default::default()
+                            },
+                            Dependency {
+                                id: "Y".to_string(),
+                                version: "1.0".to_string(),
+                                ..Default::default()
+                            },
+                        ],
+                    }]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            files: Some(Files {
+                file: vec![
+                    File {
+                        src: "bin\\tool.exe".to_string(),
+                        target: Some("tools".to_string()),
+                        ..Default::default()
+                    },
+                    File {
+                        src: "README.md".to_string(),
+                        target: Some("".to_string()),
+                        ..Default::default()
+                    },
+                ],
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_no_changes_ignores_order_and_notation() {
+        let old = package();
+        let mut new = package();
+        let group = &mut new
+            .metadata
+            .dependencies
+            .as_mut()
+            .unwrap()
+            .group
+            .as_mut()
+            .unwrap()[0];
+        group.dependency.reverse();
+        group.dependency[0].version = "1.0.0".to_string();
+        group.target_framework = Some("NET8.0".to_string());
+        new.files.as_mut().unwrap().file.reverse();
+        new.files.as_mut().unwrap().file[1].src = "bin/tool.exe".to_string();
+        assert!(old.diff(&new).is_empty(), "{}", old.diff(&new));
+    }
+
+    #[test]
+    fn test_changes() {
+        let old = package();
+        let mut new = package();
+        new.metadata.version = "1.1.0".to_string();
+        new.metadata.project_url = Some("https://example.com".to_string());
+        new.metadata.license = Some(License::File("LICENSE.txt".to_string()));
+        let group = &mut new
+            .metadata
+            .dependencies
+            .as_mut()
+            .unwrap()
+            .group
+            .as_mut()
+            .unwrap()[0];
+        group.dependency[0].version = "[1,3)".to_string();
+        group.dependency.remove(1);
+        new.metadata.dependencies.as_mut().unwrap().dependency = Some(vec![Dependency {
+            id: "Z".to_string(),
+            version: "2.0".to_string(),
+            ..Default::default()
+        }]);
+        let files = &mut new.files.as_mut().unwrap().file;
+        files[0].target = Some("tools/net8.0".to_string());
+        files.remove(1);
+        files.push(File {
+            src: "icon.png".to_string(),
+            target: Some("images".to_string()),
+            ..Default::default()
+        });
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.to_string(),
+            r#"version changed from "1.0.0" to "1.1.0"
+projectUrl set to "https://example.com"
+license changed from "MIT" to "file LICENSE.txt"
+dependency X widened from [1,2) to [1,3) for net8.0
+dependency Y 1.0 removed for net8.0
+dependency Z 2.0 added
+file README.md removed
+file bin/tool.exe retargeted from tools to tools/net8.0
+file icon.png added with target images
+"#
+        );
+        assert_eq!(
+            diff.changes[3],
+            Change::DependencyRangeChanged {
+                target_framework: Some("net8.0".to_string()),
+                id: "X".to_string(),
+                old: "[1,2)".to_string(),
+                new: "[1,3)".to_string(),
+                range: RangeChange::Widened,
+            }
+        );
+
+        let reverse = new.diff(&old);
+        assert!(reverse.changes.contains(&Change::DependencyRangeChanged {
+            target_framework: Some("net8.0".to_string()),
+            id: "X".to_string(),
+            old: "[1,3)".to_string(),
+            new: "[1,2)".to_string(),
+            range: RangeChange::Narrowed,
+        }));
+    }
+
+    #[test]
+    fn test_collections_and_duplicates() {
+        let mut old = package();
+        let group = &mut old
+            .metadata
+            .dependencies
+            .as_mut()
+            .unwrap()
+            .group
+            .as_mut()
+            .unwrap()[0];
+        group.dependency.push(Dependency {
+            id: "x".to_string(),
+            version: "[2,3)".to_string(),
+            ..Default::default()
+        });
+        old.files.as_mut().unwrap().file.push(File {
+            src: "bin\\tool.exe".to_string(),
+            target: Some("tools/net8.0".to_string()),
+            ..Default::default()
+        });
+        old.metadata.package_types = Some(PackageTypes {
+            package_type: vec![PackageType {
+                name: KnownPackageType::Dependency,
+                version: None,
+            }],
+        });
+
+        let mut new = old.clone();
+        assert!(old.diff(&new).is_empty(), "{}", old.diff(&new));
+        let group = &mut new
+            .metadata
+            .dependencies
+            .as_mut()
+            .unwrap()
+            .group
+            .as_mut()
+            .unwrap()[0];
+        group.dependency[2].version = "[2,4)".to_string();
+        group.dependency[1].exclude = Some(vec!["Build".to_string()]);
+        let files = &mut new.files.as_mut().unwrap().file;
+        files.remove(2);
+        files[0].exclude = Some(vec!["*.pdb".to_string()]);
+        new.metadata.package_types.as_mut().unwrap().package_type[0].version =
+            Some("1.0.0".to_string());
+        new.metadata.framework_assemblies = Some(FrameworkAssemblies {
+            framework_assembly: vec![FrameworkAssembly {
+                assembly_name: "System.Net.Http".to_string(),
+                target_framework: Some("net48".to_string()),
+            }],
+        });
+        new.metadata.content_files = Some(ContentFiles {
+            files: vec![ContentFile {
+                include: "cs/**/*.cs".to_string(),
+                copy_to_output: Some(true),
+                ..Default::default()
+            }],
+        });
+
+        assert_eq!(
+            old.diff(&new).to_string(),
+            r#"packageTypes changed from "Dependency" to "Dependency 1.0.0"
+frameworkAssemblies set to "System.Net.Http for net48"
+contentFiles set to "cs/**/*.cs copyToOutput=true"
+dependency x widened from [2,3) to [2,4) for net8.0
+dependency Y assets set to "exclude=Build" for net8.0
+file bin/tool.exe exclude set to "*.pdb" with target tools
+file bin/tool.exe removed with target tools/net8.0
+"#
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json() {
+        let old = package();
+        let mut new = package();
+        new.metadata.version = "2.0.0".to_string();
+        assert_eq!(
+            old.diff(&new).to_json().unwrap(),
+            r#"[
+  {
+    "kind": "metadata_changed",
+    "field": "version",
+    "old": "1.0.0",
+    "new": "2.0.0"
+  }
+]"#
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The name of the settings file of a .NET tool package.
+pub const DOTNET_TOOL_SETTINGS_FILE: &str = "DotnetToolSettings.xml";
+
+/// A representation of the `DotnetToolSettings.xml` file, which describes the commands of
+/// a .NET tool package.
+///
+/// A native tool is split into a top-level package with the `DotnetTool` package type and
+/// a package per runtime identifier with the `DotnetToolRidPackage` package type.
+/// The settings of the top-level package in `tools/<tfm>/any/` list the runtime identifier
+/// packages, and the settings of a runtime identifier package in `tools/<tfm>/<rid>/` point
+/// to the entry point next to them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "DotNetCliTool", deny_unknown_fields)]
+pub struct DotnetToolSettings {
+    /// The version of the settings format, `2` supports the native executables.
+    #[serde(rename = "@Version")]
+    pub version: String,
+    /// The commands provided by the tool.
+    #[serde(rename = "Commands")]
+    pub commands: DotnetToolCommands,
+    /// The runtime identifier packages of a top-level package.
+    #[serde(
+        rename = "RuntimeIdentifierPackages",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub runtime_identifier_packages: Option<DotnetToolRuntimeIdentifierPackages>,
+}
+
+impl DotnetToolSettings {
+    /// Creates the settings of a tool with a single command.
+    pub fn new(command: DotnetToolCommand) -> Self {
+        DotnetToolSettings {
+            version: "2".to_string(),
+            commands: DotnetToolCommands {
+                command: vec![command],
+            },
+            runtime_identifier_packages: None,
+        }
+    }
+
+    /// Creates the settings of a top-level package, which delegates the command to the packages
+    /// `<id>.<rid>` of the given runtime identifiers.
+    pub fn for_runtime_identifiers(name: &str, id: &str, runtime_identifiers: &[String]) -> Self {
+        DotnetToolSettings {
+            runtime_identifier_packages: Some(DotnetToolRuntimeIdentifierPackages {
+                package: runtime_identifiers
+                    .iter()
+                    .map(|rid| DotnetToolRuntimeIdentifierPackage {
+                        runtime_identifier: rid.clone(),
+                        id: format!("{id}.{rid}"),
+                    })
+                    .collect(),
+            }),
+            ..DotnetToolSettings::new(DotnetToolCommand {
+                name: name.to_string(),
+                entry_point: None,
+                runner: None,
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DotnetToolCommands {
+    #[serde(rename = "Command")]
+    pub command: Vec<DotnetToolCommand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DotnetToolCommand {
+    /// The name of the command used to invoke the tool, such as `dotnet-example` or `example`.
+    #[serde(rename = "@Name")]
+    pub name: String,
+    /// The file to run, relative to the settings file, absent in a top-level package.
+    #[serde(
+        rename = "@EntryPoint",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub entry_point: Option<String>,
+    /// The way the entry point is started, absent in a top-level package.
+    #[serde(rename = "@Runner", default, skip_serializing_if = "Option::is_none")]
+    pub runner: Option<DotnetToolRunner>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DotnetToolRuntimeIdentifierPackages {
+    #[serde(rename = "RuntimeIdentifierPackage")]
+    pub package: Vec<DotnetToolRuntimeIdentifierPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DotnetToolRuntimeIdentifierPackage {
+    /// The runtime identifier, such as `linux-x64`.
+    #[serde(rename = "@RuntimeIdentifier")]
+    pub runtime_identifier: String,
+    /// The id of the package with the binary for the runtime identifier.
+    #[serde(rename = "@Id")]
+    pub id: String,
+}
+
+/// The runner of a .NET tool command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DotnetToolRunner {
+    /// A managed assembly started by the `dotnet` host.
+    Dotnet,
+    /// A native executable started directly.
+    #[default]
+    Executable,
+}
+
+impl Display for DotnetToolRunner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DotnetToolRunner::Dotnet => write!(f, "dotnet"),
+            DotnetToolRunner::Executable => write!(f, "executable"),
+        }
+    }
+}
+
+/// Returns the .NET runtime identifier, such as `linux-x64` or `win-arm64`, for a Rust target
+/// triple, such as `x86_64-unknown-linux-gnu` or `aarch64-pc-windows-msvc`.
+pub fn runtime_identifier(target: &str) -> Option<String> {
+    let mut parts = target.split('-');
+    let arch = match parts.next()? {
+        "x86_64" => "x64",
+        "i586" | "i686" => "x86",
+        "aarch64" => "arm64",
+        "armv7" | "thumbv7neon" => "arm",
+        "riscv64gc" => "riscv64",
+        "loongarch64" => "loongarch64",
+        "s390x" => "s390x",
+        "powerpc64le" => "ppc64le",
+        _ => return None,
+    };
+    let os = if target.contains("-windows") {
+        "win"
+    } else if target.contains("-apple-darwin") {
+        "osx"
+    } else if target.contains("-linux-musl") {
+        "linux-musl"
+    } else if target.contains("-linux-android") {
+        "android"
+    } else if target.contains("-linux") {
+        "linux"
+    } else if target.contains("-freebsd") {
+        "freebsd"
+    } else {
+        return None;
+    };
+    Some(format!("{os}-{arch}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_serialization() {
+        let settings = DotnetToolSettings::new(DotnetToolCommand {
+            name: "example".to_string(),
+            entry_point: Some("example.exe".to_string()),
+            runner: Some(DotnetToolRunner::Executable),
+        });
+        let serialized = quick_xml::se::to_string(&settings).unwrap();
+        assert_eq!(
+            serialized,
+            r#"<DotNetCliTool Version="2"><Commands><Command Name="example" EntryPoint="example.exe" Runner="executable"/></Commands></DotNetCliTool>"#
+        );
+        let deserialized: DotnetToolSettings = quick_xml::de::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, settings);
+    }
+
+    #[test]
+    fn test_top_level_settings_serialization() {
+        let settings = DotnetToolSettings::for_runtime_identifiers(
+            "example",
+            "Example",
+            &["linux-x64".to_string(), "win-x64".to_string()],
+        );
+        let serialized = quick_xml::se::to_string(&settings).unwrap();
+        assert_eq!(
+            serialized,
+            r#"<DotNetCliTool Version="2"><Commands><Command Name="example"/></Commands><RuntimeIdentifierPackages><RuntimeIdentifierPackage RuntimeIdentifier="linux-x64" Id="Example.linux-x64"/><RuntimeIdentifierPackage RuntimeIdentifier="win-x64" Id="Example.win-x64"/></RuntimeIdentifierPackages></DotNetCliTool>"#
+        );
+        let deserialized: DotnetToolSettings = quick_xml::de::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, settings);
+    }
+
+    #[test]
+    fn test_runtime_identifier() {
+        for (target, rid) in [
+            ("x86_64-pc-windows-msvc", Some("win-x64")),
+            ("aarch64-pc-windows-msvc", Some("win-arm64")),
+            ("i686-pc-windows-gnu", Some("win-x86")),
+            ("x86_64-unknown-linux-gnu", Some("linux-x64")),
+            ("aarch64-unknown-linux-musl", Some("linux-musl-arm64")),
+            ("armv7-unknown-linux-gnueabihf", Some("linux-arm")),
+            ("aarch64-apple-darwin", Some("osx-arm64")),
+            ("x86_64-apple-darwin", Some("osx-x64")),
+            ("wasm32-unknown-unknown", None),
+        ] {
+            assert_eq!(runtime_identifier(target).as_deref(), rid, "{target}");
+        }
+    }
+}
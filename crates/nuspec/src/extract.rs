@@ -0,0 +1,264 @@
+use crate::{HashError, NuGetConfig, NupkgReader, PackageHash, PackagePath, nupkg::unescape_part};
+use serde::{Deserialize, Serialize};
+use std::{env, error, fs, path, process};
+
+/// The parts of a package written by [`extract`], as the `PackageSaveMode` of NuGet.
+///
+/// The `.nupkg.sha512` and `.nupkg.metadata` files are always written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackageSaveMode {
+    /// The lowercase `{id}.nuspec` file.
+    pub nuspec: bool,
+    /// The `.nupkg` file itself.
+    pub nupkg: bool,
+    /// The files of the package.
+    pub files: bool,
+}
+
+impl Default for PackageSaveMode {
+    // The `Defaultv3` mode of the global packages folder
+    fn default() -> Self {
+        PackageSaveMode {
+            nuspec: true,
+            nupkg: true,
+            files: true,
+        }
+    }
+}
+
+/// The options of [`extract`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExtractOptions {
+    pub save_mode: PackageSaveMode,
+    /// The source the package comes from, recorded in the `.nupkg.metadata` file.
+    pub source: Option<String>,
+}
+
+/// The `.nupkg.metadata` file, written last to mark a complete extraction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NupkgMetadata {
+    pub version: u32,
+    /// The base64 SHA-512 hash of the package.
+    #[serde(rename = "contentHash")]
+    pub content_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl NupkgMetadata {
+    /// Parses the content hash.
+    pub fn hash(&self) -> Result<PackageHash, HashError> {
+        self.content_hash.parse()
+    }
+}
+
+/// Returns the global packages folder: the `NUGET_PACKAGES` variable, the
+/// `globalPackagesFolder` value of the configuration or `~/.nuget/packages`.
+pub fn global_packages_folder(config: Option<&NuGetConfig>) -> Option<path::PathBuf> {
+    if let Some(folder) = env::var_os("NUGET_PACKAGES").filter(|f| !f.is_empty()) {
+        return Some(path::PathBuf::from(folder));
+    }
+    if let Some(folder) = config.and_then(|c| c.config_value("globalPackagesFolder")) {
+        return Some(path::PathBuf::from(folder));
+    }
+    let home = env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })?;
+    Some(path::PathBuf::from(home).join(".nuget").join("packages"))
+}
+
+/// Extracts a `.nupkg` file into a root folder with the layout of the global packages folder,
+/// `{root}/{id}/{version}` with the lowercase id and the normalized version, and returns the
+/// folder of the version.
+///
+/// The package is extracted into a temporary folder which is then renamed, and the
+/// `.nupkg.metadata` file is written last, so an interrupted extraction is never taken for
+/// a complete one. An already extracted version is left untouched. A package with an entry
+/// pointing outside of its folder is refused.
+pub fn extract<P: AsRef<path::Path>, Q: AsRef<path::Path>>(
+    nupkg: P,
+    root: Q,
+    options: &ExtractOptions,
+) -> Result<path::PathBuf, Box<dyn error::Error>> {
+    let nupkg = nupkg.as_ref();
+    let mut reader = NupkgReader::open(nupkg)?;
+    for name in reader.entry_names() {
+        check_entry(&unescape_part(&name)).map_err(|e| format!("Unsafe package {nupkg:?}: {e}"))?;
+    }
+    let (id, version) = reader.identity()?;
+    let id = id.to_lowercase();
+    let version = version.to_normalized_string().to_lowercase();
+    let id_dir = root.as_ref().join(&id);
+    let target = id_dir.join(&version);
+    let metadata_file = target.join(format!("{id}.{version}.nupkg.metadata"));
+    if metadata_file.is_file() {
+        return Ok(target);
+    }
+
+    let temp = id_dir.join(format!(".{version}.{}.tmp", process::id()));
+    if temp.exists() {
+        fs::remove_dir_all(&temp)?;
+    }
+    fs::create_dir_all(&temp)?;
+    let result = write_package(&mut reader, nupkg, &temp, &id, &version, options);
+    let result = result.and_then(|metadata| {
+        if target.exists() {
+            // An interrupted extraction, without the metadata file
+            fs::remove_dir_all(&target)?;
+        }
+        fs::rename(&temp, &target)?;
+        fs::write(&metadata_file, metadata)?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&temp);
+        return Err(format!("Failed to extract {nupkg:?} into {target:?}: {e}").into());
+    }
+    Ok(target)
+}
+
+// Writes the parts of a package into a folder and returns the content of the metadata file.
+fn write_package(
+    reader: &mut NupkgReader<fs::File>,
+    nupkg: &path::Path,
+    dir: &path::Path,
+    id: &str,
+    version: &str,
+    options: &ExtractOptions,
+) -> Result<String, Box<dyn error::Error>> {
+    let content = fs::read(nupkg)?;
+    let hash = PackageHash::from_bytes(&content);
+    if options.save_mode.files {
+        for file in reader.files() {
+            let target = file
+                .components()
+                .fold(dir.to_path_buf(), |path, component| path.join(component));
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(target, reader.read(&file)?)?;
+        }
+    }
+    if options.save_mode.nuspec {
+        fs::write(dir.join(format!("{id}.nuspec")), reader.nuspec_bytes()?)?;
+    }
+    if options.save_mode.nupkg {
+        fs::write(dir.join(format!("{id}.{version}.nupkg")), &content)?;
+    }
+    hash.write_sidecar(dir.join(format!("{id}.{version}.nupkg")))?;
+    let metadata = NupkgMetadata {
+        version: 2,
+        content_hash: hash.to_base64(),
+        source: options.source.clone(),
+    };
+    Ok(serde_json::to_string_pretty(&metadata)?)
+}
+
+// Refuses the entries that would be written outside of the package folder.
+fn check_entry(name: &str) -> Result<(), String> {
+    let path = PackagePath::new(name);
+    let absolute = name.starts_with(['/', '\\']);
+    let drive = path.components().any(|c| c.contains(':'));
+    if absolute || drive || path.is_escaping() {
+        return Err(format!("the entry `{name}` points outside of the package"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nupkg::tests::test_package;
+    use crate::test_dir::TempDir;
+
+    #[test]
+    fn test_extract() {
+        let dir = TempDir::new("extract");
+        let nupkg = dir.join("example.nupkg");
+        let content = test_package(
+            "Example",
+            "1.0.0-Beta",
+            &[
+                ("lib/net8.0/Example.dll", "dll"),
+                ("docs/read%20me.md", "doc"),
+            ],
+        );
+        fs::write(&nupkg, &content).unwrap();
+        let root = dir.join("packages");
+        let options = ExtractOptions {
+            source: Some("https://api.nuget.org/v3/index.json".to_string()),
+            ..Default::default()
+        };
+        let target = extract(&nupkg, &root, &options).unwrap();
+        assert_eq!(target, root.join("example/1.0.0-beta"));
+        assert_eq!(
+            fs::read(target.join("lib/net8.0/Example.dll")).unwrap(),
+            b"dll"
+        );
+        assert_eq!(fs::read(target.join("docs/read me.md")).unwrap(), b"doc");
+        assert!(target.join("example.nuspec").is_file());
+        assert_eq!(
+            fs::read(target.join("example.1.0.0-beta.nupkg")).unwrap(),
+            content
+        );
+        let hash = PackageHash::from_bytes(&content).to_base64();
+        assert_eq!(
+            fs::read_to_string(target.join("example.1.0.0-beta.nupkg.sha512")).unwrap(),
+            hash
+        );
+        let metadata: NupkgMetadata = serde_json::from_str(
+            &fs::read_to_string(target.join("example.1.0.0-beta.nupkg.metadata")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(metadata.content_hash, hash);
+        assert_eq!(
+            PackageHash::verify(
+                target.join("example.1.0.0-beta.nupkg"),
+                &metadata.hash().unwrap()
+            ),
+            Ok(())
+        );
+        assert!(!target.join("_rels").exists());
+        assert!(!target.join("[Content_Types].xml").exists());
+        assert_eq!(fs::read_dir(root.join("example")).unwrap().count(), 1);
+
+        // An extracted version is kept as it is
+        fs::write(target.join("marker"), b"").unwrap();
+        extract(&nupkg, &root, &options).unwrap();
+        assert!(target.join("marker").exists());
+
+        let options = ExtractOptions {
+            save_mode: PackageSaveMode {
+                nupkg: false,
+                files: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let target = extract(&nupkg, dir.join("nuspec-only"), &options).unwrap();
+        let mut names: Vec<String> = fs::read_dir(&target)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "example.1.0.0-beta.nupkg.metadata",
+                "example.1.0.0-beta.nupkg.sha512",
+                "example.nuspec"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_traversal() {
+        let dir = TempDir::new("extract-unsafe");
+        for name in ["../evil.txt", "lib/%2E%2E/%2E%2E/evil.txt", "C:/evil.txt"] {
+            let nupkg = dir.join("unsafe.nupkg");
+            fs::write(&nupkg, test_package("Unsafe", "1.0.0", &[(name, "evil")])).unwrap();
+            let result = extract(&nupkg, dir.join("packages"), &ExtractOptions::default());
+            assert!(result.is_err(), "{name} is refused");
+        }
+        assert!(!dir.join("packages/unsafe/1.0.0").exists());
+        assert!(!dir.join("evil.txt").exists());
+    }
+}
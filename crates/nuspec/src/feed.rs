@@ -0,0 +1,461 @@
+use crate::{ResourceKind, ServiceIndex};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{error, fs, path, thread};
+use ureq::Agent;
+
+/// The service index of nuget.org.
+pub const NUGET_ORG_SOURCE: &str = "https://api.nuget.org/v3/index.json";
+
+/// The header with the API key of a feed.
+pub const API_KEY_HEADER: &str = "X-NuGet-ApiKey";
+
+// The largest package accepted by nuget.org.
+const MAX_DOWNLOAD_SIZE: u64 = 250 * 1024 * 1024;
+
+/// An error returned by a [`FeedClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedError {
+    /// The request did not reach the feed or the response can not be read.
+    Transport(String),
+    /// The feed answered with an unexpected status.
+    Status {
+        url: String,
+        status: u16,
+        message: String,
+    },
+    /// The package version already exists in the feed.
+    Conflict { url: String, message: String },
+    /// The service index of the feed does not provide a resource.
+    MissingResource(ResourceKind),
+    /// A document returned by the feed is malformed.
+    InvalidResponse(String),
+    /// A local file can not be read or written.
+    Io(String),
+}
+
+impl FeedError {
+    /// Returns true if the request may succeed when it is sent again: the transport errors,
+    /// the server errors, the timeouts and the throttling.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FeedError::Transport(_) => true,
+            FeedError::Status { status, .. } => *status >= 500 || matches!(status, 408 | 429),
+            _ => false,
+        }
+    }
+}
+
+impl Display for FeedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeedError::Transport(message) => write!(f, "the feed is not reachable: {message}"),
+            FeedError::Status {
+                url,
+                status,
+                message,
+            } => write!(f, "{url} responded with {status}: {message}"),
+            FeedError::Conflict { url, message } => {
+                write!(f, "the package already exists in {url}: {message}")
+            }
+            FeedError::MissingResource(kind) => {
+                write!(f, "the feed does not provide the `{kind}` resource")
+            }
+            FeedError::InvalidResponse(message) => {
+                write!(f, "the feed returned an invalid document: {message}")
+            }
+            FeedError::Io(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl error::Error for FeedError {}
+
+/// The options of [`FeedClient::push`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushOptions {
+    /// The API key sent in the [`API_KEY_HEADER`] header.
+    pub api_key: Option<String>,
+    /// Treats a conflict with an existing version as a success, see [`PushOutcome::AlreadyExists`].
+    pub skip_duplicate: bool,
+    /// The number of times a failed upload is retried, see [`FeedError::is_retryable`].
+    pub retries: u32,
+    /// The delay before the first retry, doubled for every next one.
+    pub retry_delay: Duration,
+}
+
+impl Default for PushOptions {
+    fn default() -> Self {
+        PushOptions {
+            api_key: None,
+            skip_duplicate: false,
+            retries: 2,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The result of a successful push.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// The package is uploaded.
+    Created,
+    /// The version already exists in the feed, reported with [`PushOptions::skip_duplicate`].
+    AlreadyExists,
+}
+
+/// The options of a [`FeedClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedOptions {
+    /// The timeout of a request, including the transfer of the body.
+    pub timeout: Duration,
+    /// How long the service index is reused before it is fetched again.
+    pub cache_ttl: Duration,
+}
+
+impl Default for FeedOptions {
+    fn default() -> Self {
+        // The same expiration as the http cache of the NuGet client
+        FeedOptions {
+            timeout: Duration::from_secs(300),
+            cache_ttl: Duration::from_secs(40 * 60),
+        }
+    }
+}
+
+/// A client of a NuGet V3 feed, identified by the URL of its service index.
+///
+/// The service index is fetched on the first use and cached, the clones of a client share
+/// the cache.
+#[derive(Debug, Clone)]
+pub struct FeedClient {
+    source: String,
+    agent: Agent,
+    options: FeedOptions,
+    service_index: Arc<Mutex<Option<CachedIndex>>>,
+}
+
+/// The service index and the time it was fetched at.
+type CachedIndex = (Instant, Arc<ServiceIndex>);
+
+impl FeedClient {
+    /// Creates a client of the feed with the service index at the `source` URL,
+    /// such as [`NUGET_ORG_SOURCE`].
+    pub fn new(source: &str) -> Self {
+        FeedClient::with_options(source, FeedOptions::default())
+    }
+
+    /// Creates a client of the feed with custom options.
+    pub fn with_options(source: &str, options: FeedOptions) -> Self {
+        let config = Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(options.timeout))
+            .user_agent(concat!("nuspec-rs/", env!("CARGO_PKG_VERSION")))
+            .build();
+        FeedClient {
+            source: source.to_string(),
+            agent: Agent::new_with_config(config),
+            options,
+            service_index: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the URL of the service index.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the service index of the feed, fetching it if the cached one is expired.
+    pub fn service_index(&self) -> Result<Arc<ServiceIndex>, FeedError> {
+        let mut cache = self.service_index.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((fetched, index)) = cache.as_ref()
+            && fetched.elapsed() < self.options.cache_ttl
+        {
+            return Ok(index.clone());
+        }
+        let body = self.get(&self.source)?;
+        let index: Arc<ServiceIndex> = Arc::new(
+            serde_json::from_str(&body).map_err(|e| FeedError::InvalidResponse(e.to_string()))?,
+        );
+        *cache = Some((Instant::now(), index.clone()));
+        Ok(index)
+    }
+
+    /// Drops the cached service index, so the next request fetches it again.
+    pub fn invalidate(&self) {
+        *self.service_index.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Returns the URL of the best supported resource of a kind, see [`ServiceIndex::find`].
+    pub fn resource_url(&self, kind: ResourceKind) -> Result<String, FeedError> {
+        self.service_index()?
+            .find(kind)
+            .map(|r| r.id.clone())
+            .ok_or(FeedError::MissingResource(kind))
+    }
+
+    /// Uploads a `.nupkg` file to the [`ResourceKind::PackagePublish`] resource of the feed.
+    ///
+    /// The upload is retried on the transport and server errors. A conflict with an existing
+    /// version is an error unless [`PushOptions::skip_duplicate`] is set.
+    pub fn push<P: AsRef<path::Path>>(
+        &self,
+        nupkg: P,
+        options: &PushOptions,
+    ) -> Result<PushOutcome, FeedError> {
+        let nupkg = nupkg.as_ref();
+        let content = fs::read(nupkg)
+            .map_err(|e| FeedError::Io(format!("Failed to read the package {nupkg:?}: {e}")))?;
+        let url = self.resource_url(ResourceKind::PackagePublish)?;
+        let (boundary, body) = multipart_body(&content);
+
+        let mut attempt = 0;
+        loop {
+            let mut request = self
+                .agent
+                .put(&url)
+                .content_type(format!("multipart/form-data; boundary={boundary}"));
+            if let Some(api_key) = &options.api_key {
+                request = request.header(API_KEY_HEADER, api_key);
+            }
+            let result = match request.send(&body) {
+                Ok(mut response) => {
+                    let status = response.status().as_u16();
+                    let message = response_message(&mut response);
+                    match status {
+                        200..=299 => Ok(PushOutcome::Created),
+                        409 if options.skip_duplicate => Ok(PushOutcome::AlreadyExists),
+                        409 => Err(FeedError::Conflict {
+                            url: url.clone(),
+                            message,
+                        }),
+                        _ => Err(FeedError::Status {
+                            url: url.clone(),
+                            status,
+                            message,
+                        }),
+                    }
+                }
+                Err(e) => Err(FeedError::Transport(e.to_string())),
+            };
+            match result {
+                Err(e) if e.is_retryable() && attempt < options.retries => {
+                    thread::sleep(options.retry_delay * 2u32.pow(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // Sends a GET request and returns the body of a successful response.
+    pub(crate) fn get(&self, url: &str) -> Result<String, FeedError> {
+        self.call(url)?
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| FeedError::Transport(e.to_string()))
+    }
+
+    // Sends a GET request and returns the binary body of a successful response, such as a package.
+    pub(crate) fn get_bytes(&self, url: &str) -> Result<Vec<u8>, FeedError> {
+        self.call(url)?
+            .body_mut()
+            .with_config()
+            .limit(MAX_DOWNLOAD_SIZE)
+            .read_to_vec()
+            .map_err(|e| FeedError::Transport(e.to_string()))
+    }
+
+    fn call(&self, url: &str) -> Result<ureq::http::Response<ureq::Body>, FeedError> {
+        let mut response = self
+            .agent
+            .get(url)
+            .call()
+            .map_err(|e| FeedError::Transport(e.to_string()))?;
+        let status = response.status().as_u16();
+        if !(200..=299).contains(&status) {
+            return Err(FeedError::Status {
+                url: url.to_string(),
+                status,
+                message: response_message(&mut response),
+            });
+        }
+        Ok(response)
+    }
+}
+
+// Returns the reason of a response: its text body, or the standard reason of the status.
+fn response_message(response: &mut ureq::http::Response<ureq::Body>) -> String {
+    let reason = response
+        .status()
+        .canonical_reason()
+        .unwrap_or_default()
+        .to_string();
+    match response.body_mut().read_to_string() {
+        Ok(body) if !body.trim().is_empty() => body.trim().chars().take(500).collect(),
+        _ => reason,
+    }
+}
+
+// Wraps a package into a `multipart/form-data` body with a boundary that does not occur in it.
+fn multipart_body(content: &[u8]) -> (String, Vec<u8>) {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mut counter = 0u128;
+    let boundary = loop {
+        let boundary = format!("nuspec-{:032x}", seed.wrapping_add(counter));
+        if !content
+            .windows(boundary.len())
+            .any(|w| w == boundary.as_bytes())
+        {
+            break boundary;
+        }
+        counter += 1;
+    };
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"package\"; filename=\"package.nupkg\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    (boundary, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+    use crate::test_server::serve;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn index(url: &str) -> String {
+        format!(
+            r#"{{"version": "3.0.0", "resources": [
+                {{"@id": "{url}/api/v2/package", "@type": "PackagePublish/2.0.0"}},
+                {{"@id": "{url}/v3/flatcontainer/", "@type": "PackageBaseAddress/3.0.0"}}
+            ]}}"#
+        )
+    }
+
+    // Writes a package into a new directory, which is removed with the returned guard.
+    fn nupkg() -> (TempDir, path::PathBuf) {
+        let dir = TempDir::new("feed");
+        let file = dir.join("example.nupkg");
+        fs::write(&file, b"PK package").unwrap();
+        (dir, file)
+    }
+
+    #[test]
+    fn test_push() {
+        let server = serve(|request, url| match request.method.as_str() {
+            "GET" => (200, index(url)),
+            _ => (201, String::new()),
+        });
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let options = PushOptions {
+            api_key: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let (_dir, file) = nupkg();
+        assert_eq!(client.push(&file, &options), Ok(PushOutcome::Created));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v3/index.json");
+        let push = &requests[1];
+        assert_eq!(push.method, "PUT");
+        assert_eq!(push.path, "/api/v2/package");
+        assert_eq!(push.header(API_KEY_HEADER), Some("secret"));
+        let content_type = push.header("Content-Type").unwrap();
+        let boundary = content_type.strip_prefix("multipart/form-data; boundary=");
+        let body = String::from_utf8_lossy(&push.body);
+        assert!(body.starts_with(&format!("--{}\r\n", boundary.unwrap())));
+        assert!(body.contains("\r\n\r\nPK package\r\n"));
+    }
+
+    #[test]
+    fn test_push_conflict() {
+        let server = serve(|request, url| match request.method.as_str() {
+            "GET" => (200, index(url)),
+            _ => (409, "Conflict".to_string()),
+        });
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let (_dir, file) = nupkg();
+        let result = client.push(&file, &PushOptions::default());
+        assert!(matches!(result, Err(FeedError::Conflict { .. })));
+        let options = PushOptions {
+            skip_duplicate: true,
+            ..Default::default()
+        };
+        assert_eq!(client.push(&file, &options), Ok(PushOutcome::AlreadyExists));
+    }
+
+    #[test]
+    fn test_push_retries() {
+        let attempts = AtomicUsize::new(0);
+        let server = serve(move |request, url| match request.method.as_str() {
+            "GET" => (200, index(url)),
+            _ if attempts.fetch_add(1, Ordering::SeqCst) < 2 => (503, String::new()),
+            _ => (202, String::new()),
+        });
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let (_dir, file) = nupkg();
+        let options = PushOptions {
+            retry_delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+        assert_eq!(client.push(&file, &options), Ok(PushOutcome::Created));
+        assert_eq!(server.requests().len(), 4);
+
+        let options = PushOptions {
+            retries: 0,
+            ..options
+        };
+        let server = serve(|request, url| match request.method.as_str() {
+            "GET" => (200, index(url)),
+            _ => (500, "Internal error".to_string()),
+        });
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let result = client.push(&file, &options);
+        assert!(matches!(result, Err(FeedError::Status { status: 500, .. })));
+    }
+
+    #[test]
+    fn test_missing_resource() {
+        let server = serve(|_, _| (200, r#"{"version": "3.0.0", "resources": []}"#.to_string()));
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let (_dir, file) = nupkg();
+        let result = client.push(&file, &PushOptions::default());
+        assert_eq!(
+            result,
+            Err(FeedError::MissingResource(ResourceKind::PackagePublish))
+        );
+    }
+
+    #[test]
+    fn test_service_index_cache() {
+        let server = serve(|_, url| (200, index(url)));
+        let source = format!("{}/v3/index.json", server.url);
+        let client = FeedClient::new(&source);
+        assert_eq!(
+            client.resource_url(ResourceKind::PackageBaseAddress),
+            Ok(format!("{}/v3/flatcontainer/", server.url))
+        );
+        client.clone().service_index().unwrap();
+        assert_eq!(server.requests().len(), 1);
+        client.invalidate();
+        client.service_index().unwrap();
+        assert_eq!(server.requests().len(), 2);
+
+        let options = FeedOptions {
+            cache_ttl: Duration::ZERO,
+            ..Default::default()
+        };
+        let client = FeedClient::with_options(&source, options);
+        client.service_index().unwrap();
+        client.service_index().unwrap();
+        assert_eq!(server.requests().len(), 4);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{FeedClient, FeedError, NuGetVersion, Package, ResourceKind};
+use serde::Deserialize;
+use std::{fs, path};
+
+// The list of the versions of a package in the flat container.
+#[derive(Deserialize)]
+struct VersionsIndex {
+    versions: Vec<String>,
+}
+
+/// Queries the [`ResourceKind::PackageBaseAddress`] resource, also known as the flat container,
+/// which lists the versions of the packages and serves their content.
+///
+/// See [Package Content](https://learn.microsoft.com/en-us/nuget/api/package-base-address-resource).
+impl FeedClient {
+    /// Returns the versions of a package in the ascending order, empty if the feed does not
+    /// know the package. The unlisted versions are included.
+    pub fn versions(&self, id: &str) -> Result<Vec<NuGetVersion>, FeedError> {
+        let url = format!("{}{}/index.json", self.base_address()?, id.to_lowercase());
+        let body = match self.get(&url) {
+            Err(FeedError::Status { status: 404, .. }) => return Ok(Vec::new()),
+            result => result?,
+        };
+        let index: VersionsIndex =
+            serde_json::from_str(&body).map_err(|e| FeedError::InvalidResponse(e.to_string()))?;
+        let mut versions = index
+            .versions
+            .iter()
+            .map(|v| v.parse())
+            .collect::<Result<Vec<NuGetVersion>, _>>()
+            .map_err(|e| FeedError::InvalidResponse(e.to_string()))?;
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// Returns true if the exact version of a package is published, the build metadata is
+    /// ignored.
+    pub fn is_published(&self, id: &str, version: &NuGetVersion) -> Result<bool, FeedError> {
+        Ok(self.versions(id)?.contains(version))
+    }
+
+    /// Returns the highest published version of a package, the pre-releases are considered only
+    /// if `prerelease` is set.
+    pub fn latest_version(
+        &self,
+        id: &str,
+        prerelease: bool,
+    ) -> Result<Option<NuGetVersion>, FeedError> {
+        Ok(self
+            .versions(id)?
+            .into_iter()
+            .rfind(|v| prerelease || !v.is_prerelease()))
+    }
+
+    /// Returns the next free pre-release of a version by appending a number to its labels,
+    /// such as `1.0.0-beta.3` for `1.0.0-beta` if `1.0.0-beta.2` is the highest published one.
+    pub fn next_prerelease(
+        &self,
+        id: &str,
+        version: &NuGetVersion,
+    ) -> Result<NuGetVersion, FeedError> {
+        let labels = version.release.len();
+        let last = self
+            .versions(id)?
+            .iter()
+            .filter(|v| {
+                (v.major, v.minor, v.patch, v.revision)
+                    == (
+                        version.major,
+                        version.minor,
+                        version.patch,
+                        version.revision,
+                    )
+                    && v.release.len() == labels + 1
+                    && v.release
+                        .iter()
+                        .zip(&version.release)
+                        .all(|(a, b)| a.eq_ignore_ascii_case(b))
+            })
+            .filter_map(|v| v.release[labels].parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        let mut next = NuGetVersion {
+            metadata: None,
+            ..version.clone()
+        };
+        next.release.push((last + 1).to_string());
+        Ok(next)
+    }
+
+    /// Downloads the `.nupkg` file of a package version into a directory and returns its path.
+    ///
+    /// The file is named `{id}.{version}.nupkg` in lowercase with the normalized version, as
+    /// in the feed.
+    pub fn download_nupkg<P: AsRef<path::Path>>(
+        &self,
+        id: &str,
+        version: &NuGetVersion,
+        out_dir: P,
+    ) -> Result<path::PathBuf, FeedError> {
+        let (url, name) = self.package_url(id, version, "nupkg")?;
+        let content = self.get_bytes(&url)?;
+        let out_dir = out_dir.as_ref();
+        let file = out_dir.join(name);
+        fs::create_dir_all(out_dir)
+            .and_then(|_| fs::write(&file, content))
+            .map_err(|e| FeedError::Io(format!("Failed to write the package {file:?}: {e}")))?;
+        Ok(file)
+    }
+
+    /// Downloads and parses the nuspec file of a package version.
+    pub fn download_nuspec(&self, id: &str, version: &NuGetVersion) -> Result<Package, FeedError> {
+        let (url, _) = self.package_url(id, version, "nuspec")?;
+        let xml = self.get(&url)?;
+        crate::from_str(&xml).map_err(|e| FeedError::InvalidResponse(e.to_string()))
+    }
+
+    // Returns the URL of the flat container with a trailing slash.
+    fn base_address(&self) -> Result<String, FeedError> {
+        let url = self.resource_url(ResourceKind::PackageBaseAddress)?;
+        Ok(format!("{}/", url.trim_end_matches('/')))
+    }
+
+    // Returns the URL and the file name of the content of a package version: the package
+    // is named with the version, the nuspec file is not.
+    fn package_url(
+        &self,
+        id: &str,
+        version: &NuGetVersion,
+        extension: &str,
+    ) -> Result<(String, String), FeedError> {
+        let id = id.to_lowercase();
+        let version = version.to_normalized_string().to_lowercase();
+        let name = match extension {
+            "nupkg" => format!("{id}.{version}.nupkg"),
+            _ => format!("{id}.{extension}"),
+        };
+        let url = format!("{}{id}/{version}/{name}", self.base_address()?);
+        Ok((url, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+    use crate::test_server::serve;
+
+    fn feed(request: &crate::test_server::Request, url: &str) -> (u16, String) {
+        match request.path.as_str() {
+            "/v3/index.json" => (
+                200,
+                format!(
+                    r#"{{"version": "3.0.0", "resources": [
+                        {{"@id": "{url}/v3/flatcontainer", "@type": "PackageBaseAddress/3.0.0"}}
+                    ]}}"#
+                ),
+            ),
+            "/v3/flatcontainer/example/index.json" => (
+                200,
+                r#"{"versions": ["1.0.0", "1.1.0-beta.1", "0.9.0", "1.1.0-beta.2", "1.0.1+sha.5114f85"]}"#
+                    .to_string(),
+            ),
+            "/v3/flatcontainer/example/1.0.1/example.1.0.1.nupkg" => {
+                (200, "PK package".to_string())
+            }
+            "/v3/flatcontainer/example/1.0.1/example.nuspec" => (
+                200,
+                r#"<package><metadata><id>Example</id><version>1.0.1</version></metadata></package>"#
+                    .to_string(),
+            ),
+            _ => (404, "Not Found".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_versions() {
+        let server = serve(feed);
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let versions = client.versions("Example").unwrap();
+        let versions: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            versions,
+            [
+                "0.9.0",
+                "1.0.0",
+                "1.0.1+sha.5114f85",
+                "1.1.0-beta.1",
+                "1.1.0-beta.2"
+            ]
+        );
+        assert_eq!(client.versions("Unknown"), Ok(Vec::new()));
+        assert_eq!(
+            client.is_published("Example", &"1.0.1".parse().unwrap()),
+            Ok(true)
+        );
+        assert_eq!(
+            client.is_published("Example", &"1.1.0".parse().unwrap()),
+            Ok(false)
+        );
+        assert_eq!(
+            client.latest_version("Example", false),
+            Ok(Some(NuGetVersion::new(1, 0, 1)))
+        );
+        assert_eq!(
+            client.latest_version("Example", true),
+            Ok(Some("1.1.0-beta.2".parse().unwrap()))
+        );
+        assert_eq!(client.latest_version("Unknown", true), Ok(None));
+    }
+
+    #[test]
+    fn test_next_prerelease() {
+        let server = serve(feed);
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let next = |version: &str| {
+            client
+                .next_prerelease("Example", &version.parse().unwrap())
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(next("1.1.0-Beta"), "1.1.0-Beta.3");
+        assert_eq!(next("1.1.0-rc+sha.5114f85"), "1.1.0-rc.1");
+        assert_eq!(next("2.0.0-beta"), "2.0.0-beta.1");
+    }
+
+    #[test]
+    fn test_download() {
+        let server = serve(feed);
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let version = NuGetVersion::new(1, 0, 1);
+        let out_dir = TempDir::new("flat");
+        let file = client
+            .download_nupkg("Example", &version, &out_dir)
+            .unwrap();
+        assert_eq!(file, out_dir.join("example.1.0.1.nupkg"));
+        assert_eq!(fs::read(&file).unwrap(), b"PK package");
+
+        let pkg = client.download_nuspec("Example", &version).unwrap();
+        assert_eq!(pkg.metadata.id, "Example");
+        let result = client.download_nupkg("Example", &NuGetVersion::new(2, 0, 0), ".");
+        assert!(matches!(result, Err(FeedError::Status { status: 404, .. })));
+    }
+}
@@ -1,7 +1,20 @@
-use crate::{File, Files, License, Package, Repository, to_string_indent};
-use serde::Deserialize;
+use crate::config::{
+    ChocolateyConfig, DotnetToolConfig, NuspecConfig, ReadmeConfig, ReleaseNotesConfig,
+    from_toml_str,
+};
+use crate::{
+    CHOCOLATEY_VERIFICATION_FILE, ChocolateyMetadata, ChocolateyPackage, ChocolateyShim,
+    DEFAULT_CHANGELOG_HEADING, DEFAULT_COPYRIGHT_TEMPLATE, DOTNET_TOOL_SETTINGS_FILE,
+    DotnetToolCommand, DotnetToolRunner, DotnetToolSettings, File, Files, KnownPackageType,
+    License, Metadata, Package, PackagePath, PackageType, PackageTypes, PathSeparator,
+    RELEASE_NOTES_LIMIT, ReadmeIssue, Repository, changelog_section, check_icon, check_readme,
+    chocolatey_install_script, chocolatey_uninstall_script, chocolatey_verification, from_str,
+    neutral, render_copyright, rewrite_readme_links, runtime_identifier, to_string_indent,
+    trim_release_notes, year_from_timestamp,
+};
+use serde::{Deserialize, Serialize};
 use std::io::Write;
-use std::{env, error, fs, io, path};
+use std::{env, error, fs, io, path, process, time};
 
 /// Generates a NuSpec file based on the Cargo package metadata.
 /// The generated file will be placed in the output directory next to the Cargo build artifacts,
@@ -23,8 +36,13 @@ pub fn generate_to(out_dir: path::PathBuf) -> Result<(), Box<dyn error::Error>>
     let file_name = out_dir
         .join(pkg.metadata.id.clone())
         .with_extension("nuspec");
-    let serialized = to_string_indent(&pkg, ' ', 2)?;
-    let mut file = fs::File::create(file_name.clone())?;
+    write_xml(&file_name, &pkg)
+}
+
+// Writes an xml document with the declaration header.
+fn write_xml<T: Serialize>(file_name: &path::Path, value: &T) -> Result<(), Box<dyn error::Error>> {
+    let serialized = to_string_indent(value, ' ', 2)?;
+    let mut file = fs::File::create(file_name)?;
     file.write_all(r#"<?xml version="1.0" encoding="utf-8"?>"#.as_bytes())?;
     file.write_all(b"\n")?;
     file.write_all(serialized.as_bytes())?;
@@ -60,13 +78,7 @@ struct ManifestPackageKeywordsWorkspace {
 
 #[derive(Debug, Clone, Deserialize, Default)]
 struct ManifestPackageMetadata {
-    pub nuspec: Option<ManifestPackageMetadataNuspec>,
-}
-
-#[derive(Debug, Clone, Deserialize, Default)]
-struct ManifestPackageMetadataNuspec {
-    pub package: Option<Package>,
-    pub out_dir: Option<String>,
+    pub nuspec: Option<NuspecConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -102,7 +114,7 @@ enum ManifestCrateType {
 fn load_package_config(out_dir: path::PathBuf) -> Result<Package, Box<dyn error::Error>> {
     let manifest_file = path::PathBuf::from(env::var("CARGO_MANIFEST_PATH")?);
     let manifest_content = fs::read_to_string(manifest_file)?;
-    let manifest: Manifest = toml::from_str(&manifest_content)?;
+    let manifest: Manifest = from_toml_str(&manifest_content)?;
     let build_artifacts_path = get_build_artifacts_path()?;
     let nuspec_config = &manifest
         .package
@@ -127,10 +139,15 @@ fn load_package_config(out_dir: path::PathBuf) -> Result<Package, Box<dyn error:
     if !out_dir.is_dir() {
         return Err(format!("The `out_dir` is not a directory: {out_dir:?}").into());
     }
-    let mut pkg = nuspec_config.package.clone().unwrap_or_default();
+    let mut pkg = nuspec_config.to_package()?;
+    if let Some(defaults) = nuspec_config.defaults.as_ref().filter(|d| !d.is_empty()) {
+        let base = load_defaults(defaults)?;
+        let policy = nuspec_config.merge_policy.clone().unwrap_or_default();
+        pkg = base.merge(&pkg, &policy);
+    }
     let mut files = pkg.files.unwrap_or_default().file;
     for file in files.iter_mut() {
-        let file_path = path::PathBuf::from(&file.src);
+        let file_path = file.source_path();
         if file_path.is_relative() {
             file.src = get_relative_path(&out_dir, &file_path)?
                 .to_string_lossy()
@@ -155,6 +172,19 @@ fn load_package_config(out_dir: path::PathBuf) -> Result<Package, Box<dyn error:
             .map(|s| s.trim().to_string())
             .collect();
     }
+    if pkg.metadata.copyright.is_none()
+        && let Some(copyright) = &nuspec_config.copyright
+    {
+        pkg.metadata.copyright = Some(render_copyright(
+            copyright
+                .template
+                .as_deref()
+                .unwrap_or(DEFAULT_COPYRIGHT_TEMPLATE),
+            &pkg.metadata.authors,
+            copyright_year()?,
+            copyright.start_year,
+        ));
+    }
     if pkg.metadata.project_url.is_none() {
         pkg.metadata.project_url = match env::var("CARGO_PKG_HOMEPAGE") {
             Ok(url) => {
@@ -233,6 +263,12 @@ fn load_package_config(out_dir: path::PathBuf) -> Result<Package, Box<dyn error:
         };
     }
 
+    if pkg.metadata.release_notes.is_none()
+        && let Some(release_notes) = &nuspec_config.release_notes
+    {
+        pkg.metadata.release_notes = load_release_notes(release_notes, &pkg.metadata.version)?;
+    }
+
     if pkg.metadata.repository.is_none() {
         pkg.metadata.repository = match env::var("CARGO_PKG_REPOSITORY") {
             Ok(url) => {
@@ -266,25 +302,71 @@ fn load_package_config(out_dir: path::PathBuf) -> Result<Package, Box<dyn error:
         };
     }
 
-    manifest.binary.unwrap_or_default().iter().for_each(|b| {
-        let base_name = build_artifacts_path.join(b.name.clone());
-        let relative_path =
-            get_relative_path(&out_dir, &base_name).unwrap_or(b.name.clone().into());
-        #[cfg(target_os = "windows")]
-        {
-            push_file(&mut files, relative_path.with_extension("exe"), "tools");
-
-            let name = b.name.clone().replace("-", "_");
-            let base_name = build_artifacts_path.join(name.clone());
-            let relative_path =
-                get_relative_path(&out_dir, &base_name).unwrap_or(name.clone().into());
-            push_file(&mut files, relative_path.with_extension("pdb"), "tools");
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            push_file(&mut files, relative_path, "tools");
+    // The debug files are only packaged into the main package on Windows, unless they are
+    // split into the symbols package
+    let mut debug_files = Vec::new();
+    let build_target = BuildTarget::from_env()?;
+    let binaries = manifest.binary.unwrap_or_default();
+    // The binary of a .NET tool goes into a separate package for the runtime identifier of
+    // the build target, together with its debug files
+    let mut tool_debug_files = Vec::new();
+    let dotnet_tool = match &nuspec_config.dotnet_tool {
+        Some(tool) => {
+            let binary = dotnet_tool_binary(tool, &binaries, &pkg_name)?;
+            let tool_package = add_dotnet_tool(
+                &mut tool_debug_files,
+                tool,
+                &binary,
+                &build_target,
+                &build_artifacts_path,
+                &out_dir,
+            )?;
+            add_dotnet_tool_settings(
+                &mut files,
+                tool,
+                &binary,
+                &pkg.metadata.id,
+                &tool_package.runtime_identifier,
+                &out_dir,
+            )?;
+            add_package_type(&mut pkg.metadata, KnownPackageType::DotnetTool);
+            Some((binary, tool_package))
         }
-    });
+        None => None,
+    };
+    binaries
+        .iter()
+        .filter(|b| Some(&b.name) != dotnet_tool.as_ref().map(|(binary, _)| binary))
+        .for_each(|b| {
+            let base_name = build_artifacts_path.join(b.name.clone());
+            let relative_path =
+                get_relative_path(&out_dir, &base_name).unwrap_or(b.name.clone().into());
+            #[cfg(target_os = "windows")]
+            {
+                push_file(&mut files, relative_path.with_extension("exe"), "tools");
+
+                let name = b.name.clone().replace("-", "_");
+                let base_name = build_artifacts_path.join(name.clone());
+                let relative_path =
+                    get_relative_path(&out_dir, &base_name).unwrap_or(name.clone().into());
+                push_file(
+                    &mut debug_files,
+                    relative_path.with_extension("pdb"),
+                    "tools",
+                );
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                push_debug_file(
+                    &mut debug_files,
+                    &relative_path,
+                    "tools",
+                    &build_target,
+                    &out_dir,
+                );
+                push_file(&mut files, relative_path, "tools");
+            }
+        });
     if let Some(l) = manifest.lib {
         let name = l.name.unwrap_or(pkg_name.clone().replace("-", "_"));
         let base_name = build_artifacts_path.join(name.clone());
@@ -306,10 +388,21 @@ fn load_package_config(out_dir: path::PathBuf) -> Result<Package, Box<dyn error:
                         #[cfg(target_os = "windows")]
                         {
                             push_file(&mut files, relative_path.with_extension("exe"), "tools");
-                            push_file(&mut files, relative_path.with_extension("pdb"), "tools");
+                            push_file(
+                                &mut debug_files,
+                                relative_path.with_extension("pdb"),
+                                "tools",
+                            );
                         }
                         #[cfg(not(target_os = "windows"))]
                         {
+                            push_debug_file(
+                                &mut debug_files,
+                                &relative_path,
+                                "tools",
+                                &build_target,
+                                &out_dir,
+                            );
                             push_file(&mut files, relative_path.clone(), "tools");
                         }
                     }
@@ -329,23 +422,31 @@ fn load_package_config(out_dir: path::PathBuf) -> Result<Package, Box<dyn error:
                         #[cfg(target_os = "windows")]
                         {
                             push_file(&mut files, relative_path.with_extension("dll"), "lib");
-                            push_file(&mut files, relative_path.with_extension("pdb"), "lib");
+                            push_file(&mut debug_files, relative_path.with_extension("pdb"), "lib");
                         }
                         #[cfg(target_os = "macos")]
                         {
-                            push_file(
-                                &mut files,
-                                relative_path.with_file_name(format!("lib{name}.dylib")),
+                            let library = relative_path.with_file_name(format!("lib{name}.dylib"));
+                            push_debug_file(
+                                &mut debug_files,
+                                &library,
                                 "lib",
+                                &build_target,
+                                &out_dir,
                             );
+                            push_file(&mut files, library, "lib");
                         }
                         #[cfg(not(any(target_os = "windows", target_os = "macos")))]
                         {
-                            push_file(
-                                &mut files,
-                                relative_path.with_file_name(format!("lib{name}.so")),
+                            let library = relative_path.with_file_name(format!("lib{name}.so"));
+                            push_debug_file(
+                                &mut debug_files,
+                                &library,
                                 "lib",
+                                &build_target,
+                                &out_dir,
                             );
+                            push_file(&mut files, library, "lib");
                         }
                     }
                     ManifestCrateType::Staticlib => {
@@ -368,15 +469,743 @@ fn load_package_config(out_dir: path::PathBuf) -> Result<Package, Box<dyn error:
         }
     };
 
+    process_readme(
+        &mut pkg.metadata,
+        &mut files,
+        &nuspec_config.readme.clone().unwrap_or_default(),
+        &out_dir,
+    )?;
+    if let Some(icon) = nuspec_config.icon.as_ref().filter(|i| !i.is_empty()) {
+        let manifest_dir = path::PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
+        let icon_path = get_relative_path(&out_dir, &manifest_dir.join(icon))?;
+        let icon_file_name = icon_path.file_name().ok_or(format!(
+            "Failed to get the file name from the icon: {icon_path:?}"
+        ))?;
+        pkg.metadata.icon = Some(icon_file_name.to_string_lossy().to_string());
+        push_file(&mut files, icon_path.clone(), "");
+    }
+    validate_icon(&pkg.metadata, &files, &out_dir)?;
+
+    let symbols = nuspec_config.symbols.unwrap_or_default();
+    if symbols {
+        // The debug files configured explicitly are moved to the symbols package as well
+        let (explicit, rest): (Vec<File>, Vec<File>) =
+            files.into_iter().partition(|f| is_debug_file(&f.src));
+        files = rest;
+        debug_files.extend(explicit);
+    } else {
+        files.extend(debug_files.drain(..).filter(|f| f.src.ends_with(".pdb")));
+    }
+
     pkg.files = if files.is_empty() {
         None
     } else {
         Some(Files { file: files })
     };
 
+    let deterministic = nuspec_config.deterministic.unwrap_or_default();
+    let separator = nuspec_config.path_separator.unwrap_or_default();
+    pkg.normalize_paths(separator);
+    if deterministic {
+        pkg.make_reproducible_with(separator);
+    }
+
+    if symbols {
+        generate_symbols(&pkg, debug_files, &out_dir, separator, deterministic)?;
+    }
+
+    let tool_package = match dotnet_tool {
+        Some((binary, tool_package)) => {
+            let tool_package = generate_dotnet_tool(
+                &pkg,
+                tool_package,
+                tool_debug_files,
+                symbols,
+                &out_dir,
+                separator,
+                deterministic,
+            )?;
+            Some((binary, tool_package))
+        }
+        None => None,
+    };
+
+    if let Some(chocolatey) = &nuspec_config.chocolatey {
+        let mut names: Vec<&str> = binaries.iter().map(|b| b.name.as_str()).collect();
+        // Chocolatey has no runtime identifier packages, so the tool binary is shipped directly
+        let mut source = pkg.clone();
+        if let Some((binary, tool_package)) = &tool_package {
+            if !names.contains(&binary.as_str()) {
+                names.push(binary);
+            }
+            let tool_files = tool_package.files.iter().flat_map(|f| f.file.iter());
+            source.files.get_or_insert_default().file.extend(
+                tool_files
+                    .filter(|f| !f.src.ends_with(DOTNET_TOOL_SETTINGS_FILE))
+                    .cloned(),
+            );
+        }
+        generate_chocolatey(
+            &source,
+            chocolatey,
+            &names,
+            &out_dir,
+            separator,
+            deterministic,
+        )?;
+    }
+
     Ok(pkg)
 }
 
+// Writes a Chocolatey package into the `chocolatey` sub-directory of the output directory.
+// The binaries are hidden from the automatic shims with `.ignore` files and get explicit shims
+// in the install script instead, so the command names do not depend on the file names.
+// The checksums are pending, because the binaries are not built yet when the build script runs.
+fn generate_chocolatey(
+    pkg: &Package,
+    config: &ChocolateyConfig,
+    binaries: &[&str],
+    out_dir: &path::Path,
+    separator: PathSeparator,
+    deterministic: bool,
+) -> Result<(), Box<dyn error::Error>> {
+    let target = env::var("TARGET")?;
+    if !target.contains("-windows") {
+        println!(
+            "cargo:warning=Chocolatey packages are meant for Windows, but the binaries are built for `{target}`"
+        );
+    }
+    let chocolatey_dir = out_dir.join("chocolatey");
+    fs::create_dir_all(&chocolatey_dir)?;
+
+    let mut chocolatey = pkg.clone();
+    chocolatey.metadata.id = config.id.clone().unwrap_or(pkg.metadata.id.to_lowercase());
+    let extension = ChocolateyMetadata {
+        license_url: config.license_url.clone(),
+        project_source_url: config.project_source_url.clone().or(pkg
+            .metadata
+            .repository
+            .as_ref()
+            .and_then(|r| r.url.clone())),
+        package_source_url: config.package_source_url.clone(),
+        docs_url: config.docs_url.clone(),
+        bug_tracker_url: config.bug_tracker_url.clone(),
+    };
+
+    // The sources are relative to the Chocolatey nuspec file, one level below the output directory
+    let mut files = chocolatey.files.take().map(|f| f.file).unwrap_or_default();
+    for file in files.iter_mut() {
+        if file.source_path().is_relative() {
+            file.src = format!("../{}", file.src);
+        }
+    }
+
+    let mut shims = Vec::new();
+    let mut checksums = Vec::new();
+    let mut generated = Vec::new();
+    for name in binaries {
+        let exe = format!("{name}.exe");
+        let binary = files.iter().find(|f| {
+            let target = f.target_path();
+            let file_name = target.file_name().unwrap_or_default();
+            file_name == *name || file_name == exe
+        });
+        let Some(binary) = binary else {
+            println!("cargo:warning=The `{name}` binary is not packaged, no shim is created");
+            continue;
+        };
+        let binary_target = binary.target_path();
+        let Some(path) = binary_target.as_str().strip_prefix("tools/") else {
+            println!(
+                "cargo:warning=The `{name}` binary is not in the `tools` directory, no shim is created"
+            );
+            continue;
+        };
+        checksums.push((path.replace('/', "\\"), None));
+        shims.push(ChocolateyShim {
+            name: name.to_string(),
+            path: path.to_string(),
+        });
+        let ignore = write_ignore_file(&chocolatey_dir, path)?;
+        generated.push((ignore, format!("{binary_target}.ignore")));
+    }
+
+    let source_url = extension.project_source_url.as_deref();
+    for (file_name, content) in [
+        ("chocolateyInstall.ps1", chocolatey_install_script(&shims)),
+        (
+            "chocolateyUninstall.ps1",
+            chocolatey_uninstall_script(&shims),
+        ),
+        (
+            CHOCOLATEY_VERIFICATION_FILE,
+            chocolatey_verification(source_url, &checksums),
+        ),
+    ] {
+        fs::write(chocolatey_dir.join(file_name), content)?;
+        generated.push((file_name.to_string(), format!("tools/{file_name}")));
+    }
+    files.extend(generated.into_iter().map(|(src, target)| File {
+        src,
+        target: Some(target),
+        ..Default::default()
+    }));
+    chocolatey.files = Some(Files { file: files });
+    chocolatey.normalize_paths(separator);
+    if deterministic {
+        chocolatey.make_reproducible_with(separator);
+    }
+
+    let file_name = chocolatey_dir.join(format!("{}.nuspec", chocolatey.metadata.id));
+    write_xml(&file_name, &ChocolateyPackage::new(chocolatey, extension))
+}
+
+// Writes the `.ignore` file of a binary at its path under `tools`, mirrored in the Chocolatey
+// directory, so the binaries with the same name in different sub-directories do not collide.
+// Returns the source of the file, relative to the Chocolatey directory.
+fn write_ignore_file(chocolatey_dir: &path::Path, path: &str) -> io::Result<String> {
+    let ignore = format!("{path}.ignore");
+    let ignore_file = chocolatey_dir.join(&ignore);
+    if let Some(parent) = ignore_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(ignore_file, "")?;
+    Ok(ignore)
+}
+
+// Returns the name of the binary packaged as a .NET tool.
+fn dotnet_tool_binary(
+    tool: &DotnetToolConfig,
+    binaries: &[ManifestBinary],
+    pkg_name: &str,
+) -> Result<String, Box<dyn error::Error>> {
+    match (&tool.bin, binaries) {
+        (Some(bin), _) => Ok(bin.clone()),
+        (None, [binary]) => Ok(binary.name.clone()),
+        (None, []) => Ok(pkg_name.to_string()),
+        (None, _) => Err("The crate has several binaries, please choose one with the `bin` key of the `dotnet-tool` table".into()),
+    }
+}
+
+// The runtime identifier package of a .NET tool, before it is written.
+struct DotnetToolPackage {
+    runtime_identifier: String,
+    files: Vec<File>,
+}
+
+// Adds the files of the runtime identifier package of a .NET tool: the binary and the settings
+// file under `tools/<tfm>/<rid>`, where `dotnet tool install` looks for the entry point.
+// The settings file is written into the `<rid>` sub-directory of the output directory, so it
+// does not collide with the settings file of the top-level package.
+fn add_dotnet_tool(
+    debug_files: &mut Vec<File>,
+    tool: &DotnetToolConfig,
+    binary: &str,
+    target: &BuildTarget,
+    build_artifacts_path: &path::Path,
+    out_dir: &path::Path,
+) -> Result<DotnetToolPackage, Box<dyn error::Error>> {
+    let runtime_identifier = runtime_identifier(&target.triple).ok_or(format!(
+        "The `{}` target has no .NET runtime identifier, it can not be packaged as a .NET tool",
+        target.triple
+    ))?;
+    let framework = tool.target_framework.as_deref().unwrap_or("net10.0");
+    let tool_path = format!("tools/{framework}/{runtime_identifier}");
+    let mut files = Vec::new();
+
+    let entry_point = if target.os == "windows" {
+        format!("{binary}.exe")
+    } else {
+        binary.to_string()
+    };
+    let relative_path = get_relative_path(out_dir, &build_artifacts_path.join(&entry_point))?;
+    if target.os == "windows" {
+        let pdb = format!("{}.pdb", binary.replace("-", "_"));
+        let pdb_path = get_relative_path(out_dir, &build_artifacts_path.join(pdb))?;
+        push_file(debug_files, pdb_path, &tool_path);
+    } else {
+        push_debug_file(debug_files, &relative_path, &tool_path, target, out_dir);
+    }
+    push_file(&mut files, relative_path, &tool_path);
+
+    let settings = DotnetToolSettings::new(DotnetToolCommand {
+        name: tool.command.clone().unwrap_or(binary.to_string()),
+        entry_point: Some(entry_point),
+        runner: Some(DotnetToolRunner::Executable),
+    });
+    let settings_dir = out_dir.join(&runtime_identifier);
+    fs::create_dir_all(&settings_dir)?;
+    write_xml(&settings_dir.join(DOTNET_TOOL_SETTINGS_FILE), &settings)?;
+    push_file(
+        &mut files,
+        path::Path::new(&runtime_identifier).join(DOTNET_TOOL_SETTINGS_FILE),
+        &tool_path,
+    );
+    Ok(DotnetToolPackage {
+        runtime_identifier,
+        files,
+    })
+}
+
+// Adds the settings file of the top-level package of a .NET tool under `tools/<tfm>/any`,
+// which lists the runtime identifier packages of the configured runtime identifiers.
+fn add_dotnet_tool_settings(
+    files: &mut Vec<File>,
+    tool: &DotnetToolConfig,
+    binary: &str,
+    id: &str,
+    runtime_identifier: &str,
+    out_dir: &path::Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let framework = tool.target_framework.as_deref().unwrap_or("net10.0");
+    let mut runtime_identifiers = tool.runtime_identifiers.clone().unwrap_or_default();
+    if !runtime_identifiers
+        .iter()
+        .any(|rid| rid == runtime_identifier)
+    {
+        runtime_identifiers.push(runtime_identifier.to_string());
+    }
+    let settings = DotnetToolSettings::for_runtime_identifiers(
+        tool.command.as_deref().unwrap_or(binary),
+        id,
+        &runtime_identifiers,
+    );
+    write_xml(&out_dir.join(DOTNET_TOOL_SETTINGS_FILE), &settings)?;
+    push_file(
+        files,
+        DOTNET_TOOL_SETTINGS_FILE.into(),
+        &format!("tools/{framework}/any"),
+    );
+    Ok(())
+}
+
+// Writes the runtime identifier package of a .NET tool with the id `<id>.<rid>` and the same
+// version as the main package. The debug files follow the main package: they are split into
+// a symbols package of their own, or only the `.pdb` files are packaged.
+fn generate_dotnet_tool(
+    pkg: &Package,
+    tool_package: DotnetToolPackage,
+    mut debug_files: Vec<File>,
+    symbols: bool,
+    out_dir: &path::Path,
+    separator: PathSeparator,
+    deterministic: bool,
+) -> Result<Package, Box<dyn error::Error>> {
+    let mut files = tool_package.files;
+    if !symbols {
+        files.extend(debug_files.drain(..).filter(|f| f.src.ends_with(".pdb")));
+    }
+    let mut rid_package = Package {
+        namespace: pkg.namespace.clone(),
+        metadata: Metadata {
+            id: format!("{}.{}", pkg.metadata.id, tool_package.runtime_identifier),
+            version: pkg.metadata.version.clone(),
+            description: pkg.metadata.description.clone(),
+            authors: pkg.metadata.authors.clone(),
+            package_types: Some(PackageTypes {
+                package_type: vec![PackageType {
+                    name: KnownPackageType::DotnetToolRidPackage,
+                    version: None,
+                }],
+            }),
+            ..Default::default()
+        },
+        files: Some(Files { file: files }),
+    };
+    rid_package.normalize_paths(separator);
+    if deterministic {
+        rid_package.make_reproducible_with(separator);
+    }
+    let file_name = out_dir.join(format!("{}.nuspec", rid_package.metadata.id));
+    write_xml(&file_name, &rid_package)?;
+    if symbols {
+        generate_symbols(&rid_package, debug_files, out_dir, separator, deterministic)?;
+    }
+    Ok(rid_package)
+}
+
+fn add_package_type(metadata: &mut Metadata, name: KnownPackageType) {
+    let package_types = metadata.package_types.get_or_insert(PackageTypes {
+        package_type: Vec::new(),
+    });
+    if !package_types.package_type.iter().any(|t| t.name == name) {
+        package_types.package_type.push(PackageType {
+            name,
+            version: None,
+        });
+    }
+}
+
+// Loads the shared defaults file. The relative file sources in the defaults file are relative to
+// the defaults file itself, so they are rebased on the crate manifest directory.
+fn load_defaults(defaults: &str) -> Result<Package, Box<dyn error::Error>> {
+    let manifest_dir = path::PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
+    let defaults_path = manifest_dir.join(defaults);
+    println!("cargo:rerun-if-changed={}", defaults_path.display());
+    let content = fs::read_to_string(&defaults_path)
+        .map_err(|e| format!("Failed to read the defaults file {defaults_path:?}: {e}"))?;
+    let mut pkg: Package = match defaults_path.extension().and_then(|e| e.to_str()) {
+        Some("nuspec") | Some("xml") => from_str(&content)?,
+        _ => from_toml_str::<neutral::Package>(&content)?.into(),
+    };
+    let defaults_dir = defaults_path
+        .parent()
+        .ok_or(format!("Failed to get the directory of {defaults_path:?}"))?;
+    for file in pkg.files.iter_mut().flat_map(|f| f.file.iter_mut()) {
+        let file_path = file.source_path();
+        if file_path.is_relative() {
+            file.src = get_relative_path(&manifest_dir, &defaults_dir.join(file_path))?
+                .to_string_lossy()
+                .to_string();
+        }
+    }
+    Ok(pkg)
+}
+
+// Returns the year of the copyright for reproducible builds: `SOURCE_DATE_EPOCH`,
+// then the date of the last git commit and the current date as the last resort.
+fn copyright_year() -> Result<i32, Box<dyn error::Error>> {
+    if let Ok(epoch) = env::var("SOURCE_DATE_EPOCH") {
+        let timestamp = epoch
+            .trim()
+            .parse::<i64>()
+            .map_err(|e| format!("Invalid SOURCE_DATE_EPOCH {epoch:?}: {e}"))?;
+        return Ok(year_from_timestamp(timestamp));
+    }
+    let manifest_dir = path::PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
+    if let Some(timestamp) =
+        git(&manifest_dir, &["log", "-1", "--format=%ct"]).and_then(|t| t.parse::<i64>().ok())
+    {
+        return Ok(year_from_timestamp(timestamp));
+    }
+    let now = time::SystemTime::now().duration_since(time::UNIX_EPOCH)?;
+    Ok(year_from_timestamp(now.as_secs() as i64))
+}
+
+// Extracts the release notes of the version from the changelog.
+fn load_release_notes(
+    config: &ReleaseNotesConfig,
+    version: &str,
+) -> Result<Option<String>, Box<dyn error::Error>> {
+    let manifest_dir = path::PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
+    let changelog = manifest_dir.join(config.changelog.as_deref().unwrap_or("CHANGELOG.md"));
+    let content = fs::read_to_string(&changelog)
+        .map_err(|e| format!("Failed to read the changelog {changelog:?}: {e}"))?;
+    let heading = config
+        .heading
+        .as_deref()
+        .unwrap_or(DEFAULT_CHANGELOG_HEADING);
+    match changelog_section(&content, version, heading) {
+        Some(notes) => {
+            let trimmed = trim_release_notes(&notes, RELEASE_NOTES_LIMIT);
+            if trimmed.len() < notes.len() {
+                println!(
+                    "cargo:warning=The release notes of {version} are trimmed to {RELEASE_NOTES_LIMIT} characters"
+                );
+            }
+            Ok(Some(trimmed))
+        }
+        None => {
+            println!(
+                "cargo:warning=No section for the version {version} in the changelog {changelog:?}"
+            );
+            Ok(None)
+        }
+    }
+}
+
+// Validates the package readme. A readme is generated from the package metadata if there is no
+// Markdown readme and the fallback is enabled, and the relative links are rewritten to the repository URLs if configured.
+fn process_readme(
+    metadata: &mut Metadata,
+    files: &mut Vec<File>,
+    config: &ReadmeConfig,
+    out_dir: &path::Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let readme_dir = out_dir.join("readme");
+    let entry = metadata.readme.as_ref().and_then(|readme| {
+        let readme = PackagePath::new(readme);
+        files
+            .iter()
+            .position(|f| f.package_path().is_some_and(|p| p.eq_ignore_case(&readme)))
+    });
+    let is_markdown = metadata
+        .readme
+        .as_ref()
+        .is_some_and(|r| r.to_lowercase().ends_with(".md"));
+    if !is_markdown && config.fallback.unwrap_or(false) {
+        if let Some(readme) = &metadata.readme {
+            println!(
+                "cargo:warning=The readme `{readme}` is not a Markdown file, a README.md is generated from the package metadata instead"
+            );
+            if let Some(entry) = entry {
+                files.remove(entry);
+            }
+        }
+        fs::create_dir_all(&readme_dir)?;
+        fs::write(readme_dir.join("README.md"), fallback_readme(metadata))?;
+        files.push(File::new(
+            path::Path::new("readme").join("README.md"),
+            &PackagePath::new("README.md"),
+        ));
+        metadata.readme = Some("README.md".to_string());
+        return Ok(());
+    }
+    let Some(readme) = metadata.readme.clone() else {
+        return Ok(());
+    };
+    let Some(entry) = entry else {
+        println!("cargo:warning=The readme `{readme}` is not packaged, it is not validated");
+        return Ok(());
+    };
+
+    let src = out_dir.join(files[entry].source_path());
+    let mut content =
+        fs::read_to_string(&src).map_err(|e| format!("Failed to read the readme {src:?}: {e}"))?;
+    if config.rewrite_links.unwrap_or_default() {
+        match readme_link_bases(metadata, &src) {
+            Some((link_base, image_base, dir)) => {
+                content = rewrite_readme_links(&content, &link_base, &image_base, &dir);
+                let file_name = src
+                    .file_name()
+                    .ok_or(format!("Failed to get the file name from readme: {src:?}"))?;
+                fs::create_dir_all(&readme_dir)?;
+                fs::write(readme_dir.join(file_name), &content)?;
+                files[entry].src = format!("readme/{}", file_name.to_string_lossy());
+            }
+            None => println!(
+                "cargo:warning=The links of the readme are not rewritten, the repository URL or commit is unknown"
+            ),
+        }
+    }
+
+    let packaged: Vec<String> = files
+        .iter()
+        .filter_map(File::package_path)
+        .map(|p| p.to_string())
+        .collect();
+    // The crates often have a readme that is not a Markdown file, so it is only an error in
+    // the strict mode
+    let strict = config.strict.unwrap_or_default();
+    let is_error = |issue: &ReadmeIssue| match issue {
+        ReadmeIssue::NotMarkdown { .. } => strict,
+        _ => issue.is_error(),
+    };
+    let issues = check_readme(&readme, &content, &packaged);
+    for issue in issues.iter().filter(|i| !is_error(i)) {
+        println!("cargo:warning=The readme is not valid for NuGet: {issue}");
+    }
+    match issues.into_iter().find(is_error) {
+        Some(issue) => Err(format!("The readme is not valid for NuGet: {issue}").into()),
+        None => Ok(()),
+    }
+}
+
+// Validates the format, size and dimensions of the package icon.
+fn validate_icon(
+    metadata: &Metadata,
+    files: &[File],
+    out_dir: &path::Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let Some(icon) = &metadata.icon else {
+        return Ok(());
+    };
+    let icon_path = PackagePath::new(icon);
+    let Some(entry) = files.iter().find(|f| {
+        f.package_path()
+            .is_some_and(|p| p.eq_ignore_case(&icon_path))
+    }) else {
+        println!("cargo:warning=The icon `{icon}` is not packaged, it is not validated");
+        return Ok(());
+    };
+    let src = out_dir.join(entry.source_path());
+    let content = fs::read(&src).map_err(|e| format!("Failed to read the icon {src:?}: {e}"))?;
+    let issues = check_icon(&content);
+    for issue in issues.iter().filter(|i| !i.is_error()) {
+        println!(
+            "cargo:warning=The icon `{icon}` does not follow the NuGet recommendations: {issue}"
+        );
+    }
+    match issues.into_iter().find(|i| i.is_error()) {
+        Some(issue) => Err(format!("The icon `{icon}` is not valid for NuGet: {issue}").into()),
+        None => Ok(()),
+    }
+}
+
+// Returns the URL bases of the links and images of the repository at the current commit, and
+// the directory of the readme within the repository.
+fn readme_link_bases(metadata: &Metadata, readme: &path::Path) -> Option<(String, String, String)> {
+    let repository = metadata.repository.as_ref()?;
+    let url = repository.url.as_ref()?;
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    let dir = path::absolute(readme.parent()?).ok()?;
+    let commit = repository
+        .commit
+        .clone()
+        .or_else(|| git(&dir, &["rev-parse", "HEAD"]))
+        .or_else(|| repository.branch.clone())?;
+    let root = path::PathBuf::from(git(&dir, &["rev-parse", "--show-toplevel"])?);
+    let mut readme_dir = String::new();
+    for component in get_relative_path(root.as_path(), dir.as_path())
+        .ok()?
+        .iter()
+    {
+        readme_dir.push_str(&component.to_string_lossy());
+        readme_dir.push('/');
+    }
+    Some((
+        format!("{url}/blob/{commit}/"),
+        format!("{url}/raw/{commit}/"),
+        readme_dir,
+    ))
+}
+
+// Runs a git command in a directory and returns its output, if git is available.
+fn git(dir: &path::Path, args: &[&str]) -> Option<String> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if output.is_empty() {
+        None
+    } else {
+        Some(output)
+    }
+}
+
+fn fallback_readme(metadata: &Metadata) -> String {
+    let title = metadata.title.as_ref().unwrap_or(&metadata.id);
+    let mut readme = format!("# {title}\n\n{}\n", metadata.description);
+    let links = [
+        ("Project", metadata.project_url.clone()),
+        (
+            "Repository",
+            metadata.repository.as_ref().and_then(|r| r.url.clone()),
+        ),
+        ("License", metadata.license.as_ref().map(|l| l.to_string())),
+    ];
+    let mut links = links
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|v| format!("* {name}: {v}\n")))
+        .peekable();
+    if links.peek().is_some() {
+        readme.push('\n');
+        readme.extend(links);
+    }
+    readme
+}
+
+// Writes the symbols package with the same id and version as the main package.
+fn generate_symbols(
+    pkg: &Package,
+    debug_files: Vec<File>,
+    out_dir: &path::Path,
+    separator: PathSeparator,
+    deterministic: bool,
+) -> Result<(), Box<dyn error::Error>> {
+    if debug_files.is_empty() {
+        println!("cargo:warning=No debug files found, the symbols package is not generated");
+        return Ok(());
+    }
+    let mut symbols = Package {
+        namespace: pkg.namespace.clone(),
+        metadata: Metadata {
+            id: pkg.metadata.id.clone(),
+            version: pkg.metadata.version.clone(),
+            description: pkg.metadata.description.clone(),
+            authors: pkg.metadata.authors.clone(),
+            package_types: Some(PackageTypes {
+                package_type: vec![PackageType {
+                    name: KnownPackageType::SymbolsPackage,
+                    version: None,
+                }],
+            }),
+            ..Default::default()
+        },
+        files: Some(Files { file: debug_files }),
+    };
+    symbols.normalize_paths(separator);
+    if deterministic {
+        symbols.make_reproducible_with(separator);
+    }
+    let file_name = out_dir.join(format!("{}.symbols.nuspec", pkg.metadata.id));
+    write_xml(&file_name, &symbols)
+}
+
+fn is_debug_file(src: &str) -> bool {
+    let src = src.trim_end_matches("/**");
+    [".pdb", ".dwp", ".debug", ".dSYM"]
+        .iter()
+        .any(|extension| src.ends_with(extension))
+}
+
+// The target the packaged artifacts are built for, which differs from the host of the build
+// script when cross-compiling.
+struct BuildTarget {
+    triple: String,
+    os: String,
+    // Whether Cargo writes the debug information into packed files next to the artifacts.
+    packed_debuginfo: bool,
+}
+
+impl BuildTarget {
+    fn from_env() -> Result<Self, Box<dyn error::Error>> {
+        let profile = env::var("PROFILE")?.to_uppercase();
+        let packed = |value: &str| value == "packed";
+        let packed_debuginfo = env::var(format!("CARGO_PROFILE_{profile}_SPLIT_DEBUGINFO"))
+            .is_ok_and(|value| packed(&value))
+            || env::var("CARGO_ENCODED_RUSTFLAGS").is_ok_and(|flags| {
+                flags
+                    .split('\x1f')
+                    .filter_map(|flag| flag.rsplit_once("split-debuginfo="))
+                    .any(|(_, value)| packed(value))
+            });
+        Ok(BuildTarget {
+            triple: env::var("TARGET")?,
+            os: env::var("CARGO_CFG_TARGET_OS")?,
+            packed_debuginfo,
+        })
+    }
+}
+
+// Adds the split debug information of a binary, which Cargo places next to it with the packed
+// `split-debuginfo`: a `.dSYM` bundle on macOS and a `.dwp` file elsewhere. The file is only
+// added if it exists or the packed debug information is configured.
+fn push_debug_file(
+    debug_files: &mut Vec<File>,
+    binary: &path::Path,
+    target: &str,
+    build_target: &BuildTarget,
+    out_dir: &path::Path,
+) {
+    let Some(file_name) = binary.file_name() else {
+        return;
+    };
+    let file_name = file_name.to_string_lossy().replace("-", "_");
+    if build_target.os == "macos" {
+        let bundle = format!("{file_name}.dSYM");
+        let path = binary.with_file_name(&bundle);
+        if build_target.packed_debuginfo || out_dir.join(&path).exists() {
+            debug_files.push(File::new(
+                path.join("**"),
+                &PackagePath::new(target).join(&bundle),
+            ));
+        }
+    } else {
+        let path = binary.with_file_name(format!("{file_name}.dwp"));
+        if build_target.packed_debuginfo || out_dir.join(&path).exists() {
+            push_file(debug_files, path, target);
+        }
+    }
+}
+
 fn push_file(files: &mut Vec<File>, src: path::PathBuf, target: &str) {
     let src_file_name = src.file_name();
     if src_file_name.is_none() {
@@ -389,16 +1218,8 @@ fn push_file(files: &mut Vec<File>, src: path::PathBuf, target: &str) {
             return;
         }
     }
-    files.push(File {
-        src: src.to_string_lossy().to_string(),
-        target: Some(
-            path::PathBuf::from(target)
-                .join(src_file_name)
-                .to_string_lossy()
-                .to_string(),
-        ),
-        ..Default::default()
-    });
+    let target = PackagePath::new(target).join(&src_file_name);
+    files.push(File::new(src, &target));
 }
 
 // Retrieves the output directory path from the environment variable `OUT_DIR`
@@ -480,3 +1301,231 @@ fn get_workspace_manifest_path() -> Result<WorkspaceManifest, Box<dyn error::Err
     }
     Err("No workspace manifest found".into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+
+    #[test]
+    fn test_dotnet_tool() {
+        let out_dir = TempDir::new("dotnet-tool");
+        let tool = DotnetToolConfig {
+            command: Some("example".to_string()),
+            ..Default::default()
+        };
+        let binaries = [ManifestBinary {
+            name: "example-cli".to_string(),
+        }];
+        let binary = dotnet_tool_binary(&tool, &binaries, "example").unwrap();
+        assert_eq!(binary, "example-cli");
+
+        let mut debug_files = Vec::new();
+        let target = BuildTarget {
+            triple: "x86_64-unknown-linux-gnu".to_string(),
+            os: "linux".to_string(),
+            packed_debuginfo: false,
+        };
+        let tool_package = add_dotnet_tool(
+            &mut debug_files,
+            &tool,
+            &binary,
+            &target,
+            &out_dir,
+            &out_dir,
+        )
+        .unwrap();
+        assert_eq!(tool_package.runtime_identifier, "linux-x64");
+        let paths: Vec<_> = tool_package
+            .files
+            .iter()
+            .map(|f| f.package_path().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                PackagePath::new("tools/net10.0/linux-x64/example-cli"),
+                PackagePath::new("tools/net10.0/linux-x64/DotnetToolSettings.xml"),
+            ]
+        );
+        let settings =
+            fs::read_to_string(out_dir.join("linux-x64").join(DOTNET_TOOL_SETTINGS_FILE)).unwrap();
+        assert!(
+            settings.contains(
+                r#"<Command Name="example" EntryPoint="example-cli" Runner="executable"/>"#
+            ),
+            "{settings}"
+        );
+
+        let tool = DotnetToolConfig {
+            runtime_identifiers: Some(vec!["win-x64".to_string()]),
+            ..tool
+        };
+        let mut files = Vec::new();
+        add_dotnet_tool_settings(&mut files, &tool, &binary, "Example", "linux-x64", &out_dir)
+            .unwrap();
+        assert_eq!(
+            files[0].package_path(),
+            Some(PackagePath::new("tools/net10.0/any/DotnetToolSettings.xml"))
+        );
+        let settings = fs::read_to_string(out_dir.join(DOTNET_TOOL_SETTINGS_FILE)).unwrap();
+        assert!(
+            settings.contains(r#"<RuntimeIdentifierPackage RuntimeIdentifier="win-x64" Id="Example.win-x64"/>"#)
+                && settings.contains(r#"<RuntimeIdentifierPackage RuntimeIdentifier="linux-x64" Id="Example.linux-x64"/>"#),
+            "{settings}"
+        );
+
+        let pkg = Package {
+            metadata: Metadata {
+                id: "Example".to_string(),
+                version: "1.0.0".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let rid_package = generate_dotnet_tool(
+            &pkg,
+            tool_package,
+            Vec::new(),
+            false,
+            &out_dir,
+            PathSeparator::Slash,
+            false,
+        )
+        .unwrap();
+        assert_eq!(rid_package.metadata.id, "Example.linux-x64");
+        assert_eq!(
+            rid_package.metadata.package_types.unwrap().package_type[0].name,
+            KnownPackageType::DotnetToolRidPackage
+        );
+        assert!(out_dir.join("Example.linux-x64.nuspec").exists());
+
+        let mut debug_files = Vec::new();
+        let tool_package = add_dotnet_tool(
+            &mut debug_files,
+            &DotnetToolConfig::default(),
+            &binary,
+            &BuildTarget {
+                triple: "x86_64-pc-windows-msvc".to_string(),
+                os: "windows".to_string(),
+                packed_debuginfo: false,
+            },
+            &out_dir,
+            &out_dir,
+        )
+        .unwrap();
+        assert_eq!(
+            tool_package.files[0].package_path(),
+            Some(PackagePath::new("tools/net10.0/win-x64/example-cli.exe"))
+        );
+        assert_eq!(
+            debug_files[0].package_path(),
+            Some(PackagePath::new("tools/net10.0/win-x64/example_cli.pdb"))
+        );
+
+        let wasm = BuildTarget {
+            triple: "wasm32-unknown-unknown".to_string(),
+            os: "unknown".to_string(),
+            packed_debuginfo: false,
+        };
+        assert!(
+            add_dotnet_tool(&mut debug_files, &tool, &binary, &wasm, &out_dir, &out_dir).is_err()
+        );
+
+        let mut metadata = Metadata::default();
+        add_package_type(&mut metadata, KnownPackageType::DotnetTool);
+        add_package_type(&mut metadata, KnownPackageType::DotnetTool);
+        assert_eq!(
+            metadata.package_types.unwrap().package_type,
+            [PackageType {
+                name: KnownPackageType::DotnetTool,
+                version: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_readme_not_markdown() {
+        let out_dir = TempDir::new("readme-not-markdown");
+        fs::write(out_dir.join("README.txt"), "Example").unwrap();
+        let files = vec![File::new(
+            path::Path::new("README.txt"),
+            &PackagePath::new("README.txt"),
+        )];
+        let mut metadata = Metadata {
+            readme: Some("README.txt".to_string()),
+            ..Default::default()
+        };
+        let mut config = ReadmeConfig::default();
+        assert!(process_readme(&mut metadata, &mut files.clone(), &config, &out_dir).is_ok());
+        config.strict = Some(true);
+        let error =
+            process_readme(&mut metadata, &mut files.clone(), &config, &out_dir).unwrap_err();
+        assert!(error.to_string().contains("not a Markdown"), "{error}");
+    }
+
+    #[test]
+    fn test_ignore_files() {
+        let chocolatey_dir = TempDir::new("ignore-files");
+        let first = write_ignore_file(&chocolatey_dir, "x64/example.exe").unwrap();
+        let second = write_ignore_file(&chocolatey_dir, "arm64/example.exe").unwrap();
+        assert_eq!(first, "x64/example.exe.ignore");
+        assert_eq!(second, "arm64/example.exe.ignore");
+        assert!(chocolatey_dir.join(&first).is_file());
+        assert!(chocolatey_dir.join(&second).is_file());
+    }
+
+    #[test]
+    fn test_debug_files() {
+        let out_dir = TempDir::new("debug-files");
+        let binary = path::Path::new("example-cli");
+        let mut target = BuildTarget {
+            triple: "x86_64-unknown-linux-gnu".to_string(),
+            os: "linux".to_string(),
+            packed_debuginfo: false,
+        };
+        let mut debug_files = Vec::new();
+        push_debug_file(&mut debug_files, binary, "tools", &target, &out_dir);
+        assert_eq!(debug_files, []);
+
+        fs::write(out_dir.join("example_cli.dwp"), "").unwrap();
+        push_debug_file(&mut debug_files, binary, "tools", &target, &out_dir);
+        assert_eq!(
+            debug_files[0].package_path(),
+            Some(PackagePath::new("tools/example_cli.dwp"))
+        );
+
+        target.os = "macos".to_string();
+        let mut debug_files = Vec::new();
+        push_debug_file(&mut debug_files, binary, "lib", &target, &out_dir);
+        assert_eq!(debug_files, []);
+        target.packed_debuginfo = true;
+        push_debug_file(&mut debug_files, binary, "lib", &target, &out_dir);
+        assert_eq!(debug_files[0].src, "example_cli.dSYM/**");
+        assert_eq!(
+            debug_files[0].target.as_deref(),
+            Some("lib/example_cli.dSYM")
+        );
+        assert!(is_debug_file(&debug_files[0].src));
+        assert!(is_debug_file("target/release/example.pdb"));
+        assert!(!is_debug_file("target/release/example.exe"));
+
+        let pkg = Package {
+            metadata: Metadata {
+                id: "Example".to_string(),
+                version: "1.0.0".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        generate_symbols(&pkg, debug_files, &out_dir, PathSeparator::Slash, false).unwrap();
+        let symbols: Package =
+            from_str(&fs::read_to_string(out_dir.join("Example.symbols.nuspec")).unwrap()).unwrap();
+        assert_eq!(symbols.metadata.version, "1.0.0");
+        assert_eq!(
+            symbols.metadata.package_types.unwrap().package_type[0].name,
+            KnownPackageType::SymbolsPackage
+        );
+        assert_eq!(symbols.files.unwrap().file[0].src, "example_cli.dSYM/**");
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::Display;
+
+/// The maximum size of a package icon accepted by NuGet, 1 MB.
+pub const ICON_SIZE_LIMIT: usize = 1024 * 1024;
+
+/// The icon resolution recommended by NuGet, in pixels.
+pub const ICON_RECOMMENDED_SIZE: u32 = 128;
+
+/// The image formats supported for package icons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconFormat {
+    Png,
+    Jpeg,
+}
+
+impl Display for IconFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IconFormat::Png => write!(f, "PNG"),
+            IconFormat::Jpeg => write!(f, "JPEG"),
+        }
+    }
+}
+
+/// The format and the dimensions of an icon image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IconInfo {
+    pub format: IconFormat,
+    /// The width and height in pixels, if they can be read from the image header.
+    pub dimensions: Option<(u32, u32)>,
+}
+
+/// A problem of a package icon, NuGet refuses the packages with the errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IconIssue {
+    /// The icon is neither a PNG nor a JPEG image.
+    UnsupportedFormat,
+    /// The icon is larger than [`ICON_SIZE_LIMIT`].
+    TooLarge { size: usize },
+    /// The icon is not 128x128 pixels.
+    Dimensions { width: u32, height: u32 },
+    /// The dimensions can not be read from the image header.
+    UnknownDimensions,
+}
+
+impl IconIssue {
+    /// Returns true if NuGet refuses a package with the issue.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            IconIssue::UnsupportedFormat | IconIssue::TooLarge { .. }
+        )
+    }
+}
+
+impl Display for IconIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IconIssue::UnsupportedFormat => write!(f, "the icon is neither a PNG nor a JPEG image"),
+            IconIssue::TooLarge { size } => write!(
+                f,
+                "the icon is {size} bytes, larger than the {ICON_SIZE_LIMIT} bytes limit"
+            ),
+            IconIssue::Dimensions { width, height } => write!(
+                f,
+                "the icon is {width}x{height} pixels, {ICON_RECOMMENDED_SIZE}x{ICON_RECOMMENDED_SIZE} is recommended"
+            ),
+            IconIssue::UnknownDimensions => {
+                write!(f, "the dimensions of the icon can not be read")
+            }
+        }
+    }
+}
+
+/// Detects the format of an icon by its magic bytes and reads its dimensions.
+pub fn icon_info(content: &[u8]) -> Option<IconInfo> {
+    if content.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // The IHDR chunk is always the first one: length, type, width, height
+        let dimensions = (content.len() >= 24 && &content[12..16] == b"IHDR").then(|| {
+            (
+                u32::from_be_bytes([content[16], content[17], content[18], content[19]]),
+                u32::from_be_bytes([content[20], content[21], content[22], content[23]]),
+            )
+        });
+        return Some(IconInfo {
+            format: IconFormat::Png,
+            dimensions,
+        });
+    }
+    if content.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(IconInfo {
+            format: IconFormat::Jpeg,
+            dimensions: jpeg_dimensions(content),
+        });
+    }
+    None
+}
+
+// Reads the dimensions from the first start-of-frame segment of a JPEG image.
+fn jpeg_dimensions(content: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    while i + 4 <= content.len() {
+        if content[i] != 0xFF {
+            return None;
+        }
+        let marker = content[i + 1];
+        // Fill bytes and the markers without a length
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            i += 2;
+            continue;
+        }
+        let length = u16::from_be_bytes([content[i + 2], content[i + 3]]) as usize;
+        let is_frame = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_frame {
+            let frame = content.get(i + 5..i + 9)?;
+            let height = u16::from_be_bytes([frame[0], frame[1]]) as u32;
+            let width = u16::from_be_bytes([frame[2], frame[3]]) as u32;
+            return Some((width, height));
+        }
+        i += 2 + length;
+    }
+    None
+}
+
+/// Validates an icon image against the NuGet rules.
+pub fn check_icon(content: &[u8]) -> Vec<IconIssue> {
+    let mut issues = Vec::new();
+    if content.len() > ICON_SIZE_LIMIT {
+        issues.push(IconIssue::TooLarge {
+            size: content.len(),
+        });
+    }
+    match icon_info(content) {
+        None => issues.push(IconIssue::UnsupportedFormat),
+        Some(IconInfo {
+            dimensions: None, ..
+        }) => issues.push(IconIssue::UnknownDimensions),
+        Some(IconInfo {
+            dimensions: Some((width, height)),
+            ..
+        }) => {
+            if (width, height) != (ICON_RECOMMENDED_SIZE, ICON_RECOMMENDED_SIZE) {
+                issues.push(IconIssue::Dimensions { width, height });
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut content = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        content.extend(width.to_be_bytes());
+        content.extend(height.to_be_bytes());
+        content.extend([8, 6, 0, 0, 0]);
+        content
+    }
+
+    fn jpeg(width: u16, height: u16) -> Vec<u8> {
+        let mut content = vec![0xFF, 0xD8];
+        // An APP0 segment before the frame
+        content.extend([0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00]);
+        content.extend([0xFF, 0xC2, 0x00, 0x0B, 0x08]);
+        content.extend(height.to_be_bytes());
+        content.extend(width.to_be_bytes());
+        content.extend([0x01, 0x01, 0x11, 0x00]);
+        content
+    }
+
+    #[test]
+    fn test_icon_info() {
+        assert_eq!(
+            icon_info(&png(128, 128)),
+            Some(IconInfo {
+                format: IconFormat::Png,
+                dimensions: Some((128, 128))
+            })
+        );
+        assert_eq!(
+            icon_info(&jpeg(64, 32)),
+            Some(IconInfo {
+                format: IconFormat::Jpeg,
+                dimensions: Some((64, 32))
+            })
+        );
+        assert_eq!(icon_info(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn test_check_icon() {
+        assert!(check_icon(&png(128, 128)).is_empty());
+        assert_eq!(
+            check_icon(&jpeg(64, 32)),
+            vec![IconIssue::Dimensions {
+                width: 64,
+                height: 32
+            }]
+        );
+        assert_eq!(
+            check_icon(&[0xFF, 0xD8, 0xFF, 0xD9]),
+            vec![IconIssue::UnknownDimensions]
+        );
+
+        let mut large = png(128, 128);
+        large.resize(ICON_SIZE_LIMIT + 1, 0);
+        assert_eq!(
+            check_icon(&large),
+            vec![IconIssue::TooLarge {
+                size: ICON_SIZE_LIMIT + 1
+            }]
+        );
+        let issues = check_icon(b"<svg/>");
+        assert_eq!(issues, vec![IconIssue::UnsupportedFormat]);
+        assert!(issues[0].is_error());
+    }
+}
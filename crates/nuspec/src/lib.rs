@@ -1,7 +1,79 @@
+mod changelog;
+#[cfg(feature = "generate")]
+mod chocolatey;
+#[cfg(feature = "generate")]
+mod config;
+mod copyright;
+mod diff;
+mod dotnet_tool;
+#[cfg(feature = "local")]
+mod extract;
+#[cfg(feature = "feed")]
+mod feed;
+#[cfg(feature = "feed")]
+mod flat_container;
 #[cfg(feature = "generate")]
 mod generate;
+mod icon;
+#[cfg(feature = "local")]
+mod local_feed;
+mod merge;
+pub mod neutral;
+mod nuget_config;
+#[cfg(feature = "local")]
+mod nupkg;
+#[cfg(feature = "pack")]
+mod pack;
+#[cfg(any(feature = "pack", feature = "local"))]
+mod package_hash;
+mod package_path;
+mod parse;
+mod readme;
+mod registration;
+mod reproducible;
+mod service_index;
+#[cfg(feature = "signature")]
+mod sign;
+#[cfg(feature = "signature")]
+mod signature;
 mod spec;
+#[cfg(test)]
+mod test_dir;
+#[cfg(all(test, any(feature = "feed", feature = "signature")))]
+mod test_server;
+mod version;
 
+pub use changelog::*;
+#[cfg(feature = "generate")]
+pub use chocolatey::*;
+pub use copyright::*;
+pub use diff::*;
+pub use dotnet_tool::*;
+#[cfg(feature = "local")]
+pub use extract::*;
+#[cfg(feature = "feed")]
+pub use feed::*;
 #[cfg(feature = "generate")]
 pub use generate::*;
+pub use icon::*;
+#[cfg(feature = "local")]
+pub use local_feed::*;
+pub use merge::*;
+pub use nuget_config::*;
+#[cfg(feature = "local")]
+pub use nupkg::*;
+#[cfg(feature = "pack")]
+pub use pack::*;
+#[cfg(any(feature = "pack", feature = "local"))]
+pub use package_hash::*;
+pub use package_path::*;
+pub use parse::*;
+pub use readme::*;
+pub use registration::*;
+pub use service_index::*;
+#[cfg(feature = "signature")]
+pub use sign::*;
+#[cfg(feature = "signature")]
+pub use signature::*;
 pub use spec::*;
+pub use version::*;
@@ -0,0 +1,374 @@
+use crate::{NuGetVersion, NupkgReader, Package, PackageHash, VersionRange, from_str};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{error, fs, io, path, process};
+
+/// The layout of a local folder feed.
+///
+/// See [Local feeds](https://learn.microsoft.com/en-us/nuget/hosting-packages/local-feeds).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FolderLayout {
+    /// The packages are in the root folder, named `{id}.{version}.nupkg`.
+    Flat,
+    /// Each version is in its own `{id}/{version}` folder with the package, the hash of
+    /// the package and the nuspec file, as written by `nuget add`. The names are lowercase.
+    #[default]
+    Hierarchical,
+}
+
+/// A package of a local folder feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalPackage {
+    pub id: String,
+    pub version: NuGetVersion,
+    /// The path of the `.nupkg` file.
+    pub path: path::PathBuf,
+}
+
+/// A NuGet feed in a local or a shared folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalFeed {
+    root: path::PathBuf,
+    layout: FolderLayout,
+}
+
+impl LocalFeed {
+    pub fn new<P: AsRef<path::Path>>(root: P, layout: FolderLayout) -> Self {
+        LocalFeed {
+            root: root.as_ref().to_path_buf(),
+            layout,
+        }
+    }
+
+    /// Opens an existing feed: it is flat if the root folder has packages, otherwise it is
+    /// hierarchical.
+    pub fn open<P: AsRef<path::Path>>(root: P) -> Result<Self, Box<dyn error::Error>> {
+        let root = root.as_ref();
+        let flat = read_dir(root)?.iter().any(|p| is_nupkg(p));
+        let layout = if flat {
+            FolderLayout::Flat
+        } else {
+            FolderLayout::Hierarchical
+        };
+        Ok(LocalFeed::new(root, layout))
+    }
+
+    pub fn root(&self) -> &path::Path {
+        &self.root
+    }
+
+    pub fn layout(&self) -> FolderLayout {
+        self.layout
+    }
+
+    /// Adds a package to the feed and returns it.
+    ///
+    /// As `nuget add` does, a version that is already in the feed is left untouched. In the
+    /// hierarchical layout the `.nupkg.sha512` file is written last, so a version is visible
+    /// only when all its files are complete.
+    pub fn add<P: AsRef<path::Path>>(
+        &self,
+        nupkg: P,
+    ) -> Result<LocalPackage, Box<dyn error::Error>> {
+        let nupkg = nupkg.as_ref();
+        let mut reader = NupkgReader::open(nupkg)?;
+        let (id, version) = reader.identity()?;
+        let path = self.package_path(&id, &version);
+        let package = LocalPackage {
+            id: id.clone(),
+            version,
+            path: path.clone(),
+        };
+
+        match self.layout {
+            FolderLayout::Flat => {
+                if !path.exists() {
+                    fs::create_dir_all(&self.root)?;
+                    write_atomically(&path, &fs::read(nupkg)?)?;
+                }
+            }
+            FolderLayout::Hierarchical => {
+                let hash_file = PackageHash::sidecar_path(&path);
+                if !hash_file.exists() {
+                    let dir = path.parent().unwrap_or(&self.root);
+                    fs::create_dir_all(dir)?;
+                    let content = fs::read(nupkg)?;
+                    write_atomically(&path, &content)?;
+                    let nuspec = dir.join(format!("{}.nuspec", id.to_lowercase()));
+                    write_atomically(&nuspec, &reader.nuspec_bytes()?)?;
+                    let hash = PackageHash::from_bytes(&content);
+                    write_atomically(&hash_file, hash.to_base64().as_bytes())?;
+                }
+            }
+        }
+        Ok(package)
+    }
+
+    /// Returns the packages of the feed sorted by the id, ignoring the case, and the version.
+    ///
+    /// The packages that can not be read, such as the partial copies, are skipped: with a warning
+    /// on the standard error in the flat layout, and silently in the hierarchical layout, where
+    /// a version is only complete with its hash file.
+    pub fn list(&self) -> Result<Vec<LocalPackage>, Box<dyn error::Error>> {
+        let mut packages = Vec::new();
+        match self.layout {
+            FolderLayout::Flat => {
+                for path in read_dir(&self.root)? {
+                    if !is_nupkg(&path) {
+                        continue;
+                    }
+                    match NupkgReader::open(&path).and_then(|mut r| r.identity()) {
+                        Ok((id, version)) => packages.push(LocalPackage { id, version, path }),
+                        Err(e) => eprintln!("warning: skipping the package {path:?}: {e}"),
+                    }
+                }
+            }
+            FolderLayout::Hierarchical => {
+                for dir in read_dir(&self.root)? {
+                    if dir.is_dir() {
+                        packages.extend(self.hierarchical_versions(&dir)?);
+                    }
+                }
+            }
+        }
+        packages.sort_by(|a, b| {
+            (a.id.to_lowercase(), &a.version).cmp(&(b.id.to_lowercase(), &b.version))
+        });
+        Ok(packages)
+    }
+
+    /// Returns the versions of a package in the ascending order.
+    pub fn versions(&self, id: &str) -> Result<Vec<LocalPackage>, Box<dyn error::Error>> {
+        let packages = match self.layout {
+            FolderLayout::Flat => self.list()?,
+            FolderLayout::Hierarchical => {
+                let mut packages =
+                    self.hierarchical_versions(&self.root.join(id.to_lowercase()))?;
+                packages.sort_by(|a, b| a.version.cmp(&b.version));
+                packages
+            }
+        };
+        Ok(packages
+            .into_iter()
+            .filter(|p| p.id.eq_ignore_ascii_case(id))
+            .collect())
+    }
+
+    /// Returns the highest version of a package that satisfies the range.
+    pub fn find(
+        &self,
+        id: &str,
+        range: &VersionRange,
+    ) -> Result<Option<LocalPackage>, Box<dyn error::Error>> {
+        Ok(self
+            .versions(id)?
+            .into_iter()
+            .rfind(|p| range.contains(&p.version)))
+    }
+
+    /// Returns the path of the `.nupkg` file of a version, which may not exist.
+    pub fn package_path(&self, id: &str, version: &NuGetVersion) -> path::PathBuf {
+        let version = version.to_normalized_string();
+        match self.layout {
+            FolderLayout::Flat => self.root.join(format!("{id}.{version}.nupkg")),
+            FolderLayout::Hierarchical => {
+                let id = id.to_lowercase();
+                let version = version.to_lowercase();
+                self.root
+                    .join(&id)
+                    .join(&version)
+                    .join(format!("{id}.{version}.nupkg"))
+            }
+        }
+    }
+
+    // Returns the complete versions in the folder of a package of a hierarchical feed.
+    fn hierarchical_versions(
+        &self,
+        dir: &path::Path,
+    ) -> Result<Vec<LocalPackage>, Box<dyn error::Error>> {
+        let Some(name) = dir.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            return Ok(Vec::new());
+        };
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut packages = Vec::new();
+        for version_dir in read_dir(dir)? {
+            let Some(version) = version_dir
+                .file_name()
+                .and_then(|v| v.to_str())
+                .and_then(|v| v.parse::<NuGetVersion>().ok())
+            else {
+                continue;
+            };
+            let path = self.package_path(&name, &version);
+            if !path.is_file() || !PackageHash::sidecar_path(&path).is_file() {
+                continue;
+            }
+            // The id is written with its original case in the nuspec file
+            let id = fs::read_to_string(version_dir.join(format!("{name}.nuspec")))
+                .ok()
+                .and_then(|xml| from_str::<Package>(xml.trim_start_matches('\u{feff}')).ok())
+                .map(|pkg| pkg.metadata.id)
+                .filter(|id| id.eq_ignore_ascii_case(&name))
+                .unwrap_or(name.clone());
+            packages.push(LocalPackage { id, version, path });
+        }
+        Ok(packages)
+    }
+}
+
+fn is_nupkg(path: &path::Path) -> bool {
+    path.is_file()
+        && path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .is_some_and(|n| n.ends_with(".nupkg") && !n.ends_with(".symbols.nupkg"))
+}
+
+// Returns the sorted entries of a folder, none if it does not exist.
+fn read_dir(dir: &path::Path) -> io::Result<Vec<path::PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+// Writes a file through a temporary one, so the readers never see a partial file.
+// The temporary name is unique per call, so the concurrent writers do not share it.
+pub(crate) fn write_atomically(path: &path::Path, content: &[u8]) -> io::Result<()> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNT.fetch_add(1, Ordering::Relaxed);
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".{}-{count}.tmp", process::id()));
+    fs::write(&temp, content)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nupkg::tests::test_package;
+    use crate::test_dir::TempDir;
+
+    fn packages(dir: &path::Path) -> Vec<path::PathBuf> {
+        fs::create_dir_all(dir).unwrap();
+        [
+            ("Example", "1.0.0"),
+            ("Example", "1.1.0-beta"),
+            ("Other", "2.0"),
+        ]
+        .iter()
+        .map(|(id, version)| {
+            let file = dir.join(format!("{id}-{version}.nupkg"));
+            fs::write(&file, test_package(id, version, &[("lib/a.dll", "a")])).unwrap();
+            file
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_hierarchical_feed() {
+        let dir = TempDir::new("local");
+        let sources = packages(&dir.join("src"));
+        let feed = LocalFeed::new(dir.join("feed"), FolderLayout::Hierarchical);
+        for source in &sources {
+            feed.add(source).unwrap();
+        }
+        let added = feed.add(&sources[0]).unwrap();
+
+        let version_dir = dir.join("feed/example/1.0.0");
+        assert_eq!(added.path, version_dir.join("example.1.0.0.nupkg"));
+        assert_eq!(
+            fs::read(&added.path).unwrap(),
+            fs::read(&sources[0]).unwrap()
+        );
+        assert_eq!(
+            PackageHash::read_sidecar(&added.path),
+            PackageHash::compute(&sources[0])
+        );
+        assert!(
+            fs::read_to_string(version_dir.join("example.nuspec"))
+                .unwrap()
+                .contains("<id>Example</id>")
+        );
+        assert_eq!(
+            fs::read_dir(&version_dir).unwrap().count(),
+            3,
+            "no temporary files are left"
+        );
+
+        // An incomplete version without the hash is ignored
+        fs::create_dir_all(dir.join("feed/example/2.0.0")).unwrap();
+        fs::write(dir.join("feed/example/2.0.0/example.2.0.0.nupkg"), b"").unwrap();
+
+        let feed = LocalFeed::open(dir.join("feed")).unwrap();
+        assert_eq!(feed.layout(), FolderLayout::Hierarchical);
+        let listed: Vec<String> = feed
+            .list()
+            .unwrap()
+            .iter()
+            .map(|p| format!("{} {}", p.id, p.version))
+            .collect();
+        assert_eq!(
+            listed,
+            ["Example 1.0.0", "Example 1.1.0-beta", "Other 2.0.0"]
+        );
+
+        let range = "[1.0, 2.0)".parse().unwrap();
+        let found = feed.find("EXAMPLE", &range).unwrap().unwrap();
+        assert_eq!(found.version.to_string(), "1.1.0-beta");
+        let range = "[1.0]".parse().unwrap();
+        assert_eq!(feed.find("example", &range).unwrap().unwrap(), added);
+        assert_eq!(feed.find("Missing", &range).unwrap(), None);
+    }
+
+    #[test]
+    fn test_flat_feed() {
+        let dir = TempDir::new("local-flat");
+        let sources = packages(&dir.join("src"));
+        let feed = LocalFeed::new(dir.join("feed"), FolderLayout::Flat);
+        for source in &sources {
+            feed.add(source).unwrap();
+        }
+        assert!(dir.join("feed/Other.2.0.0.nupkg").is_file());
+
+        let feed = LocalFeed::open(dir.join("feed")).unwrap();
+        assert_eq!(feed.layout(), FolderLayout::Flat);
+        assert_eq!(feed.list().unwrap().len(), 3);
+        let versions: Vec<String> = feed
+            .versions("example")
+            .unwrap()
+            .iter()
+            .map(|p| p.version.to_string())
+            .collect();
+        assert_eq!(versions, ["1.0.0", "1.1.0-beta"]);
+        let range = "1.0".parse().unwrap();
+        let found = feed.find("Other", &range).unwrap().unwrap();
+        assert_eq!(found.path, dir.join("feed/Other.2.0.0.nupkg"));
+
+        // A partial copy does not hide the other packages
+        fs::write(dir.join("feed/Partial.1.0.0.nupkg"), b"PK").unwrap();
+        assert_eq!(feed.list().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = TempDir::new("local-atomic");
+        let path = dir.join("example.nupkg");
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let path = &path;
+                scope.spawn(move || write_atomically(path, format!("{i}").as_bytes()).unwrap());
+            }
+        });
+        assert_eq!(fs::read_to_string(&path).unwrap().len(), 1);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    }
+}
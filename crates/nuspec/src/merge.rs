@@ -0,0 +1,615 @@
+use crate::{
+    ContentFiles, Dependencies, Dependency, DependencyGroup, Files, FrameworkAssemblies, Metadata,
+    Package, PackageTypes,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Describes how [`Package::merge`] combines a base package with an overlay.
+///
+/// The default policy lets the overlay win for single-valued fields, unions the tags,
+/// concatenates the files and merges the dependency groups by target framework.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MergePolicy {
+    /// The policy for single-valued metadata fields, such as `copyright` or `projectUrl`.
+    pub fields: FieldPolicy,
+    /// Per-field overrides of the `fields` policy, keyed by the nuspec field name,
+    /// such as `copyright` or `projectUrl`.
+    pub overrides: BTreeMap<MetadataField, FieldPolicy>,
+    /// The policy for the list of authors.
+    pub authors: ListPolicy,
+    /// The policy for the list of tags.
+    pub tags: ListPolicy,
+    /// The policy for the files, package types, framework assemblies and content files.
+    pub files: ListPolicy,
+    /// The policy for the dependencies.
+    pub dependencies: DependencyPolicy,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        MergePolicy {
+            fields: FieldPolicy::OverlayWins,
+            overrides: BTreeMap::new(),
+            authors: ListPolicy::OverlayWins,
+            tags: ListPolicy::Union,
+            files: ListPolicy::Concatenate,
+            dependencies: DependencyPolicy::MergeByFramework,
+        }
+    }
+}
+
+impl MergePolicy {
+    /// Returns a policy that keeps every value of the base package and only fills
+    /// the gaps from the overlay.
+    pub fn base_wins() -> Self {
+        MergePolicy {
+            fields: FieldPolicy::BaseWins,
+            authors: ListPolicy::BaseWins,
+            dependencies: DependencyPolicy::BaseWins,
+            ..Default::default()
+        }
+    }
+
+    fn field(&self, field: MetadataField) -> FieldPolicy {
+        self.overrides.get(&field).copied().unwrap_or(self.fields)
+    }
+}
+
+/// A single-valued metadata field whose [`FieldPolicy`] can be overridden.
+///
+/// The fields are named as in the nuspec file, such as `projectUrl`, and the snake_case and
+/// kebab-case names are accepted as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MetadataField {
+    Id,
+    Version,
+    Description,
+    #[serde(alias = "project_url", alias = "project-url")]
+    ProjectUrl,
+    License,
+    Icon,
+    Readme,
+    #[serde(
+        alias = "require_license_acceptance",
+        alias = "require-license-acceptance"
+    )]
+    RequireLicenseAcceptance,
+    #[serde(alias = "development_dependency", alias = "development-dependency")]
+    DevelopmentDependency,
+    #[serde(alias = "release_notes", alias = "release-notes")]
+    ReleaseNotes,
+    Copyright,
+    Language,
+    Repository,
+    Title,
+    #[serde(alias = "min_client_version", alias = "min-client-version")]
+    MinClientVersion,
+    References,
+}
+
+/// How to choose between two values of a single-valued field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldPolicy {
+    /// The overlay value is used if it is set.
+    #[default]
+    #[serde(alias = "overlay-wins")]
+    OverlayWins,
+    /// The base value is used if it is set.
+    #[serde(alias = "base-wins")]
+    BaseWins,
+}
+
+/// How to combine two lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ListPolicy {
+    /// The overlay list is used if it is not empty.
+    #[default]
+    #[serde(alias = "overlay-wins")]
+    OverlayWins,
+    /// The base list is used if it is not empty.
+    #[serde(alias = "base-wins")]
+    BaseWins,
+    /// The overlay items are appended to the base items.
+    Concatenate,
+    /// The overlay items are appended to the base items, skipping the duplicates.
+    Union,
+}
+
+impl ListPolicy {
+    fn apply<T: Clone + PartialEq>(self, base: &[T], overlay: &[T]) -> Vec<T> {
+        match self {
+            ListPolicy::OverlayWins if !overlay.is_empty() => overlay.to_vec(),
+            ListPolicy::OverlayWins => base.to_vec(),
+            ListPolicy::BaseWins if !base.is_empty() => base.to_vec(),
+            ListPolicy::BaseWins => overlay.to_vec(),
+            ListPolicy::Concatenate => base.iter().chain(overlay).cloned().collect(),
+            ListPolicy::Union => {
+                let mut items = base.to_vec();
+                for item in overlay {
+                    if !items.contains(item) {
+                        items.push(item.clone());
+                    }
+                }
+                items
+            }
+        }
+    }
+
+    fn apply_option<T: Clone + PartialEq>(
+        self,
+        base: &Option<Vec<T>>,
+        overlay: &Option<Vec<T>>,
+    ) -> Option<Vec<T>> {
+        if base.is_none() && overlay.is_none() {
+            return None;
+        }
+        Some(self.apply(
+            base.as_deref().unwrap_or_default(),
+            overlay.as_deref().unwrap_or_default(),
+        ))
+    }
+}
+
+/// How to combine two sets of dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyPolicy {
+    /// The overlay dependencies are used if there are any.
+    #[serde(alias = "overlay-wins")]
+    OverlayWins,
+    /// The base dependencies are used if there are any.
+    #[serde(alias = "base-wins")]
+    BaseWins,
+    /// The groups with the same target framework are merged, an overlay dependency replaces
+    /// the base dependency with the same id.
+    #[default]
+    #[serde(alias = "merge-by-framework")]
+    MergeByFramework,
+}
+
+impl Package {
+    /// Layers the `overlay` package on top of this one according to the `policy`
+    /// and returns the combined package.
+    ///
+    /// For example, a team-wide defaults file with the copyright, owners and icon can be used as
+    /// the base for every crate-specific package definition.
+    pub fn merge(&self, overlay: &Package, policy: &MergePolicy) -> Package {
+        let files = policy.files.apply_option(
+            &self.files.as_ref().map(|f| f.file.clone()),
+            &overlay.files.as_ref().map(|f| f.file.clone()),
+        );
+        Package {
+            namespace: pick(policy.fields, &self.namespace, &overlay.namespace),
+            metadata: merge_metadata(&self.metadata, &overlay.metadata, policy),
+            files: files.map(|file| Files { file }),
+        }
+    }
+}
+
+fn pick<T: Clone>(policy: FieldPolicy, base: &Option<T>, overlay: &Option<T>) -> Option<T> {
+    match policy {
+        FieldPolicy::OverlayWins => overlay.clone().or_else(|| base.clone()),
+        FieldPolicy::BaseWins => base.clone().or_else(|| overlay.clone()),
+    }
+}
+
+fn pick_string(policy: FieldPolicy, base: &str, overlay: &str) -> String {
+    let non_empty = |s: &str| {
+        if s.is_empty() {
+            None
+        } else {
+            Some(s.to_string())
+        }
+    };
+    pick(policy, &non_empty(base), &non_empty(overlay)).unwrap_or_default()
+}
+
+fn merge_metadata(base: &Metadata, overlay: &Metadata, policy: &MergePolicy) -> Metadata {
+    Metadata {
+        id: pick_string(policy.field(MetadataField::Id), &base.id, &overlay.id),
+        version: pick_string(
+            policy.field(MetadataField::Version),
+            &base.version,
+            &overlay.version,
+        ),
+        description: pick_string(
+            policy.field(MetadataField::Description),
+            &base.description,
+            &overlay.description,
+        ),
+        authors: policy.authors.apply(&base.authors, &overlay.authors),
+        project_url: pick(
+            policy.field(MetadataField::ProjectUrl),
+            &base.project_url,
+            &overlay.project_url,
+        ),
+        license: pick(
+            policy.field(MetadataField::License),
+            &base.license,
+            &overlay.license,
+        ),
+        icon: pick(policy.field(MetadataField::Icon), &base.icon, &overlay.icon),
+        readme: pick(
+            policy.field(MetadataField::Readme),
+            &base.readme,
+            &overlay.readme,
+        ),
+        require_license_acceptance: pick(
+            policy.field(MetadataField::RequireLicenseAcceptance),
+            &base.require_license_acceptance,
+            &overlay.require_license_acceptance,
+        ),
+        development_dependency: pick(
+            policy.field(MetadataField::DevelopmentDependency),
+            &base.development_dependency,
+            &overlay.development_dependency,
+        ),
+        release_notes: pick(
+            policy.field(MetadataField::ReleaseNotes),
+            &base.release_notes,
+            &overlay.release_notes,
+        ),
+        copyright: pick(
+            policy.field(MetadataField::Copyright),
+            &base.copyright,
+            &overlay.copyright,
+        ),
+        language: pick(
+            policy.field(MetadataField::Language),
+            &base.language,
+            &overlay.language,
+        ),
+        tags: policy.tags.apply_option(&base.tags, &overlay.tags),
+        repository: pick(
+            policy.field(MetadataField::Repository),
+            &base.repository,
+            &overlay.repository,
+        ),
+        title: pick(
+            policy.field(MetadataField::Title),
+            &base.title,
+            &overlay.title,
+        ),
+        min_client_version: pick(
+            policy.field(MetadataField::MinClientVersion),
+            &base.min_client_version,
+            &overlay.min_client_version,
+        ),
+        package_types: policy
+            .files
+            .apply_option(
+                &base.package_types.as_ref().map(|p| p.package_type.clone()),
+                &overlay
+                    .package_types
+                    .as_ref()
+                    .map(|p| p.package_type.clone()),
+            )
+            .map(|package_type| PackageTypes { package_type }),
+        dependencies: merge_dependencies(
+            policy.dependencies,
+            &base.dependencies,
+            &overlay.dependencies,
+        ),
+        framework_assemblies: policy
+            .files
+            .apply_option(
+                &base
+                    .framework_assemblies
+                    .as_ref()
+                    .map(|f| f.framework_assembly.clone()),
+                &overlay
+                    .framework_assemblies
+                    .as_ref()
+                    .map(|f| f.framework_assembly.clone()),
+            )
+            .map(|framework_assembly| FrameworkAssemblies { framework_assembly }),
+        references: pick(
+            policy.field(MetadataField::References),
+            &base.references,
+            &overlay.references,
+        ),
+        content_files: policy
+            .files
+            .apply_option(
+                &base.content_files.as_ref().map(|c| c.files.clone()),
+                &overlay.content_files.as_ref().map(|c| c.files.clone()),
+            )
+            .map(|files| ContentFiles { files }),
+    }
+}
+
+fn merge_dependencies(
+    policy: DependencyPolicy,
+    base: &Option<Dependencies>,
+    overlay: &Option<Dependencies>,
+) -> Option<Dependencies> {
+    let any = |d: &Dependencies| {
+        d.dependency.as_ref().is_some_and(|d| !d.is_empty())
+            || d.group.as_ref().is_some_and(|g| !g.is_empty())
+    };
+    let (base, overlay) = match (policy, base, overlay) {
+        (_, None, None) => return None,
+        (_, Some(base), None) => return Some(base.clone()),
+        (_, None, Some(overlay)) => return Some(overlay.clone()),
+        (DependencyPolicy::OverlayWins, Some(base), Some(overlay)) => {
+            return Some(if any(overlay) { overlay } else { base }.clone());
+        }
+        (DependencyPolicy::BaseWins, Some(base), Some(overlay)) => {
+            return Some(if any(base) { base } else { overlay }.clone());
+        }
+        (DependencyPolicy::MergeByFramework, Some(base), Some(overlay)) => (base, overlay),
+    };
+
+    let dependency = match (&base.dependency, &overlay.dependency) {
+        (None, None) => None,
+        (base, overlay) => Some(merge_by_id(
+            base.as_deref().unwrap_or_default(),
+            overlay.as_deref().unwrap_or_default(),
+        )),
+    };
+
+    // Keep the order of the base groups and append the new frameworks from the overlay
+    let mut groups: Vec<DependencyGroup> = base.group.clone().unwrap_or_default();
+    let mut index: BTreeMap<Option<String>, usize> = groups
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.target_framework.as_ref().map(|t| t.to_lowercase()), i))
+        .collect();
+    for group in overlay.group.iter().flatten() {
+        let key = group.target_framework.as_ref().map(|t| t.to_lowercase());
+        match index.get(&key) {
+            Some(&i) => {
+                groups[i].dependency = merge_by_id(&groups[i].dependency, &group.dependency)
+            }
+            None => {
+                index.insert(key, groups.len());
+                groups.push(group.clone());
+            }
+        }
+    }
+    let group = if base.group.is_none() && overlay.group.is_none() {
+        None
+    } else {
+        Some(groups)
+    };
+
+    Some(Dependencies { dependency, group })
+}
+
+fn merge_by_id(base: &[Dependency], overlay: &[Dependency]) -> Vec<Dependency> {
+    let mut dependencies = base.to_vec();
+    for dependency in overlay {
+        match dependencies
+            .iter_mut()
+            .find(|d| d.id.eq_ignore_ascii_case(&dependency.id))
+        {
+            Some(existing) => *existing = dependency.clone(),
+            None => dependencies.push(dependency.clone()),
+        }
+    }
+    dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{File, License};
+
+    fn defaults() -> Package {
+        Package {
+            metadata: Metadata {
+                authors: vec!["Team".to_string()],
+                copyright: Some("Copyright Contoso".to_string()),
+                project_url: Some("https://contoso.com".to_string()),
+                icon: Some("icon.png".to_string()),
+                license: Some(License::Expression("MIT".to_string())),
+                tags: Some(vec!["contoso".to_string(), "rust".to_string()]),
+                dependencies: Some(Dependencies {
+                    group: Some(vec![DependencyGroup {
+                        target_framework: Some("net8.0".to_string()),
+                        dependency: vec![
+                            Dependency {
+                                id: "Common".to_string(),
+                                version: "1.0".to_string(),
+                                ..Default::default()
+                            },
+                            Dependency {
+                                id: "Logging".to_string(),
+                                version: "2.0".to_string(),
+                                ..Default::default()
+                            },
+                        ],
+                    }]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            files: Some(Files {
+                file: vec![File {
+                    src: "icon.png".to_string(),
+                    target: Some("".to_string()),
+                    ..Default::default()
+                }],
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn crate_package() -> Package {
+        Package {
+            metadata: Metadata {
+                id: "contoso-tool".to_string(),
+                version: "1.2.0".to_string(),
+                authors: vec!["Jane".to_string()],
+                copyright: Some("Copyright Jane".to_string()),
+                tags: Some(vec!["rust".to_string(), "cli".to_string()]),
+                dependencies: Some(Dependencies {
+                    group: Some(vec![
+                        DependencyGroup {
+                            target_framework: Some("NET8.0".to_string()),
+                            dependency: vec![Dependency {
+                                id: "common".to_string(),
+                                version: "[1.5,2.0)".to_string(),
+                                ..Default::default()
+                            }],
+                        },
+                        DependencyGroup {
+                            target_framework: Some("netstandard2.0".to_string()),
+                            dependency: vec![Dependency {
+                                id: "Compat".to_string(),
+                                version: "3.0".to_string(),
+                                ..Default::default()
+                            }],
+                        },
+                    ]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            files: Some(Files {
+                file: vec![File {
+                    src: "target/release/tool".to_string(),
+                    target: Some("tools/tool".to_string()),
+                    ..Default::default()
+                }],
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let merged = defaults().merge(&crate_package(), &MergePolicy::default());
+        assert_eq!(merged.metadata.id, "contoso-tool");
+        assert_eq!(merged.metadata.authors, vec!["Jane".to_string()]);
+        assert_eq!(merged.metadata.copyright.as_deref(), Some("Copyright Jane"));
+        assert_eq!(
+            merged.metadata.project_url.as_deref(),
+            Some("https://contoso.com")
+        );
+        assert_eq!(
+            merged.metadata.tags,
+            Some(vec![
+                "contoso".to_string(),
+                "rust".to_string(),
+                "cli".to_string()
+            ])
+        );
+        let groups = merged.metadata.dependencies.unwrap().group.unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].target_framework.as_deref(), Some("net8.0"));
+        assert_eq!(
+            groups[0]
+                .dependency
+                .iter()
+                .map(|d| (d.id.as_str(), d.version.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("common", "[1.5,2.0)"), ("Logging", "2.0")]
+        );
+        assert_eq!(
+            groups[1].target_framework.as_deref(),
+            Some("netstandard2.0")
+        );
+        assert_eq!(merged.files.unwrap().file.len(), 2);
+    }
+
+    #[test]
+    fn test_per_field_policies() {
+        let policy = MergePolicy {
+            overrides: BTreeMap::from([(MetadataField::Copyright, FieldPolicy::BaseWins)]),
+            authors: ListPolicy::Concatenate,
+            tags: ListPolicy::OverlayWins,
+            files: ListPolicy::BaseWins,
+            dependencies: DependencyPolicy::OverlayWins,
+            ..Default::default()
+        };
+        let merged = defaults().merge(&crate_package(), &policy);
+        assert_eq!(
+            merged.metadata.copyright.as_deref(),
+            Some("Copyright Contoso")
+        );
+        assert_eq!(
+            merged.metadata.authors,
+            vec!["Team".to_string(), "Jane".to_string()]
+        );
+        assert_eq!(
+            merged.metadata.tags,
+            Some(vec!["rust".to_string(), "cli".to_string()])
+        );
+        assert_eq!(merged.files.unwrap().file[0].src, "icon.png");
+        assert_eq!(
+            merged.metadata.dependencies,
+            crate_package().metadata.dependencies
+        );
+
+        // An empty overlay has no dependencies to win with
+        let mut overlay = crate_package();
+        overlay.metadata.dependencies = Some(Dependencies::default());
+        let merged = defaults().merge(&overlay, &policy);
+        assert_eq!(
+            merged.metadata.dependencies,
+            defaults().metadata.dependencies
+        );
+    }
+
+    #[test]
+    fn test_base_wins() {
+        let merged = defaults().merge(&crate_package(), &MergePolicy::base_wins());
+        assert_eq!(merged.metadata.id, "contoso-tool");
+        assert_eq!(
+            merged.metadata.copyright.as_deref(),
+            Some("Copyright Contoso")
+        );
+        assert_eq!(merged.metadata.authors, vec!["Team".to_string()]);
+        assert_eq!(
+            merged.metadata.dependencies,
+            defaults().metadata.dependencies
+        );
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn test_policy_from_toml() {
+        let policy: MergePolicy = toml::from_str(
+            r#"
+            fields = "base_wins"
+            tags = "concatenate"
+            dependencies = "merge_by_framework"
+            [overrides]
+            copyright = "overlay_wins"
+            project-url = "overlay_wins"
+            release_notes = "overlay_wins"
+            minClientVersion = "overlay_wins"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.fields, FieldPolicy::BaseWins);
+        assert_eq!(
+            policy.field(MetadataField::Copyright),
+            FieldPolicy::OverlayWins
+        );
+        assert_eq!(
+            policy.overrides.keys().copied().collect::<Vec<_>>(),
+            [
+                MetadataField::ProjectUrl,
+                MetadataField::ReleaseNotes,
+                MetadataField::Copyright,
+                MetadataField::MinClientVersion,
+            ]
+        );
+        assert_eq!(policy.tags, ListPolicy::Concatenate);
+        assert_eq!(policy.files, ListPolicy::Concatenate);
+
+        let err =
+            toml::from_str::<MergePolicy>("overrides = { copyrihgt = \"base_wins\" }").unwrap_err();
+        assert!(
+            err.message().starts_with("unknown variant `copyrihgt`"),
+            "{err}"
+        );
+    }
+}
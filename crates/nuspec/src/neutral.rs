@@ -0,0 +1,828 @@
+//! A format-neutral representation of the nuspec model.
+//!
+//! The types of the crate root mirror the XML layout of a nuspec file: attributes are renamed to
+//! `@name`, the text content to `$text` and the lists are wrapped into container elements.
+//! This module provides the same model with natural keys, suitable for JSON, YAML or TOML:
+//!
+//! * all keys are in snake_case, such as `project_url` or `require_license_acceptance`, and the
+//!   kebab-case spelling, such as `project-url`, is accepted when reading;
+//! * the lists are plain arrays, such as `authors`, `tags` or `files`;
+//! * the license is either `{ "expression": "MIT" }` or `{ "file": "LICENSE.txt" }`;
+//! * the dependencies and references are split into the framework-independent list and
+//!   the `dependency_groups` / `reference_groups` lists.
+//!
+//! ```json
+//! {
+//!   "metadata": {
+//!     "id": "example-package",
+//!     "version": "1.0.0",
+//!     "description": "An example NuGet package",
+//!     "authors": ["Author One"],
+//!     "license": { "expression": "MIT" },
+//!     "dependency_groups": [
+//!       { "target_framework": "net8.0", "dependencies": [{ "id": "X", "version": "[1,2)" }] }
+//!     ]
+//!   },
+//!   "files": [{ "src": "bin/tool.exe", "target": "tools" }]
+//! }
+//! ```
+//!
+//! The types convert from and to the nuspec model with [`From`], and the module itself can be
+//! used as a serde adapter: `#[serde(with = "nuspec::neutral")] package: nuspec::Package`.
+use crate::spec;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+/// A package definition, see [`crate::Package`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Package {
+    /// The XML namespace of the nuspec file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xmlns: Option<String>,
+    #[serde(default)]
+    pub metadata: Metadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<File>>,
+}
+
+/// The package metadata, see [`crate::Metadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Metadata {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    #[serde(
+        alias = "project-url",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub project_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<License>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme: Option<String>,
+    #[serde(
+        alias = "require-license-acceptance",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub require_license_acceptance: Option<bool>,
+    #[serde(
+        alias = "development-dependency",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub development_dependency: Option<bool>,
+    #[serde(
+        alias = "release-notes",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub release_notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<Repository>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(
+        alias = "min-client-version",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_client_version: Option<String>,
+    #[serde(
+        alias = "package-types",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub package_types: Option<Vec<PackageType>>,
+    /// The dependencies that apply to all target frameworks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<Dependency>>,
+    /// The dependencies grouped by target framework.
+    #[serde(
+        alias = "dependency-groups",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub dependency_groups: Option<Vec<DependencyGroup>>,
+    #[serde(
+        alias = "framework-assemblies",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub framework_assemblies: Option<Vec<FrameworkAssembly>>,
+    /// The assembly file names that apply to all target frameworks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<Vec<String>>,
+    /// The assembly file names grouped by target framework.
+    #[serde(
+        alias = "reference-groups",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reference_groups: Option<Vec<ReferenceGroup>>,
+    #[serde(
+        alias = "content-files",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub content_files: Option<Vec<ContentFile>>,
+}
+
+/// A license expression or a license file within the package, see [`crate::License`].
+///
+/// It is represented as a single-key map, `{ "expression": "MIT" }` or `{ "file": "LICENSE.txt" }`,
+/// in every format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum License {
+    Expression(String),
+    File(String),
+}
+
+impl Serialize for License {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (key, value) = match self {
+            License::Expression(expression) => ("expression", expression),
+            License::File(file) => ("file", file),
+        };
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(key, value)?;
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for License {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Fields {
+            expression: Option<String>,
+            file: Option<String>,
+        }
+        match Fields::deserialize(deserializer)? {
+            Fields {
+                expression: Some(expression),
+                file: None,
+            } => Ok(License::Expression(expression)),
+            Fields {
+                expression: None,
+                file: Some(file),
+            } => Ok(License::File(file)),
+            _ => Err(de::Error::custom(
+                "expected exactly one of `expression` or `file`",
+            )),
+        }
+    }
+}
+
+/// The repository metadata, see [`crate::Repository`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Repository {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub repository_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+/// A package type, such as `DotnetTool`, see [`crate::PackageType`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PackageType {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// A package dependency, see [`crate::Dependency`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    pub id: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+}
+
+/// The dependencies of a target framework, see [`crate::DependencyGroup`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DependencyGroup {
+    #[serde(
+        alias = "target-framework",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub target_framework: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+}
+
+/// A framework assembly reference, see [`crate::FrameworkAssembly`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FrameworkAssembly {
+    #[serde(alias = "assembly-name")]
+    pub assembly_name: String,
+    #[serde(
+        alias = "target-framework",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub target_framework: Option<String>,
+}
+
+/// The assembly references of a target framework, see [`crate::ReferenceGroup`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ReferenceGroup {
+    #[serde(
+        alias = "target-framework",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub target_framework: Option<String>,
+    #[serde(default)]
+    pub references: Vec<String>,
+}
+
+/// A content file, see [`crate::ContentFile`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ContentFile {
+    pub include: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+    /// The MSBuild item type, such as `Compile`, `Content` or a custom one.
+    #[serde(
+        alias = "build-action",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub build_action: Option<String>,
+    #[serde(
+        alias = "copy-to-output",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub copy_to_output: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flatten: Option<bool>,
+}
+
+/// A file included in the package, see [`crate::File`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct File {
+    pub src: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+}
+
+impl From<&spec::Package> for Package {
+    fn from(value: &spec::Package) -> Self {
+        Package {
+            xmlns: value.namespace.clone(),
+            metadata: Metadata::from(&value.metadata),
+            files: value
+                .files
+                .as_ref()
+                .map(|f| f.file.iter().map(File::from).collect()),
+        }
+    }
+}
+
+impl From<Package> for spec::Package {
+    fn from(value: Package) -> Self {
+        spec::Package {
+            namespace: value.xmlns,
+            metadata: value.metadata.into(),
+            files: value.files.map(|files| spec::Files {
+                file: files.into_iter().map(spec::File::from).collect(),
+            }),
+        }
+    }
+}
+
+impl From<&spec::Metadata> for Metadata {
+    fn from(value: &spec::Metadata) -> Self {
+        let dependencies = value.dependencies.as_ref();
+        let references = value.references.as_ref();
+        Metadata {
+            id: value.id.clone(),
+            version: value.version.clone(),
+            description: value.description.clone(),
+            authors: value.authors.clone(),
+            project_url: value.project_url.clone(),
+            license: value.license.as_ref().map(License::from),
+            icon: value.icon.clone(),
+            readme: value.readme.clone(),
+            require_license_acceptance: value.require_license_acceptance,
+            development_dependency: value.development_dependency,
+            release_notes: value.release_notes.clone(),
+            copyright: value.copyright.clone(),
+            language: value.language.clone(),
+            tags: value.tags.clone(),
+            repository: value.repository.as_ref().map(Repository::from),
+            title: value.title.clone(),
+            min_client_version: value.min_client_version.clone(),
+            package_types: value.package_types.as_ref().map(|p| {
+                p.package_type
+                    .iter()
+                    .map(|t| PackageType {
+                        name: t.name.to_string(),
+                        version: t.version.clone(),
+                    })
+                    .collect()
+            }),
+            dependencies: dependencies
+                .and_then(|d| d.dependency.as_ref())
+                .map(|d| d.iter().map(Dependency::from).collect()),
+            dependency_groups: dependencies.and_then(|d| d.group.as_ref()).map(|groups| {
+                groups
+                    .iter()
+                    .map(|g| DependencyGroup {
+                        target_framework: g.target_framework.clone(),
+                        dependencies: g.dependency.iter().map(Dependency::from).collect(),
+                    })
+                    .collect()
+            }),
+            framework_assemblies: value.framework_assemblies.as_ref().map(|f| {
+                f.framework_assembly
+                    .iter()
+                    .map(|a| FrameworkAssembly {
+                        assembly_name: a.assembly_name.clone(),
+                        target_framework: a.target_framework.clone(),
+                    })
+                    .collect()
+            }),
+            references: references
+                .and_then(|r| r.reference.as_ref())
+                .map(|r| r.iter().map(|r| r.file.clone()).collect()),
+            reference_groups: references.and_then(|r| r.group.as_ref()).map(|groups| {
+                groups
+                    .iter()
+                    .map(|g| ReferenceGroup {
+                        target_framework: g.target_framework.clone(),
+                        references: g.reference.iter().map(|r| r.file.clone()).collect(),
+                    })
+                    .collect()
+            }),
+            content_files: value
+                .content_files
+                .as_ref()
+                .map(|c| c.files.iter().map(ContentFile::from).collect()),
+        }
+    }
+}
+
+impl From<Metadata> for spec::Metadata {
+    fn from(value: Metadata) -> Self {
+        let dependencies = if value.dependencies.is_none() && value.dependency_groups.is_none() {
+            None
+        } else {
+            Some(spec::Dependencies {
+                dependency: value
+                    .dependencies
+                    .map(|d| d.into_iter().map(spec::Dependency::from).collect()),
+                group: value.dependency_groups.map(|groups| {
+                    groups
+                        .into_iter()
+                        .map(|g| spec::DependencyGroup {
+                            target_framework: g.target_framework,
+                            dependency: g
+                                .dependencies
+                                .into_iter()
+                                .map(spec::Dependency::from)
+                                .collect(),
+                        })
+                        .collect()
+                }),
+            })
+        };
+        let reference = |file| spec::Reference { file };
+        let references = if value.references.is_none() && value.reference_groups.is_none() {
+            None
+        } else {
+            Some(spec::References {
+                reference: value
+                    .references
+                    .map(|r| r.into_iter().map(reference).collect()),
+                group: value.reference_groups.map(|groups| {
+                    groups
+                        .into_iter()
+                        .map(|g| spec::ReferenceGroup {
+                            target_framework: g.target_framework,
+                            reference: g.references.into_iter().map(reference).collect(),
+                        })
+                        .collect()
+                }),
+            })
+        };
+        spec::Metadata {
+            id: value.id,
+            version: value.version,
+            description: value.description,
+            authors: value.authors,
+            project_url: value.project_url,
+            license: value.license.map(spec::License::from),
+            icon: value.icon,
+            readme: value.readme,
+            require_license_acceptance: value.require_license_acceptance,
+            development_dependency: value.development_dependency,
+            release_notes: value.release_notes,
+            copyright: value.copyright,
+            language: value.language,
+            tags: value.tags,
+            repository: value.repository.map(spec::Repository::from),
+            title: value.title,
+            min_client_version: value.min_client_version,
+            package_types: value.package_types.map(|types| spec::PackageTypes {
+                package_type: types
+                    .into_iter()
+                    .map(|t| spec::PackageType {
+                        name: spec::KnownPackageType::from(t.name.as_str()),
+                        version: t.version,
+                    })
+                    .collect(),
+            }),
+            dependencies,
+            framework_assemblies: value.framework_assemblies.map(|assemblies| {
+                spec::FrameworkAssemblies {
+                    framework_assembly: assemblies
+                        .into_iter()
+                        .map(|a| spec::FrameworkAssembly {
+                            assembly_name: a.assembly_name,
+                            target_framework: a.target_framework,
+                        })
+                        .collect(),
+                }
+            }),
+            references,
+            content_files: value.content_files.map(|files| spec::ContentFiles {
+                files: files.into_iter().map(spec::ContentFile::from).collect(),
+            }),
+        }
+    }
+}
+
+impl From<&spec::License> for License {
+    fn from(value: &spec::License) -> Self {
+        match value {
+            spec::License::Expression(expression) => License::Expression(expression.clone()),
+            spec::License::File(file) => License::File(file.clone()),
+        }
+    }
+}
+
+impl From<License> for spec::License {
+    fn from(value: License) -> Self {
+        match value {
+            License::Expression(expression) => spec::License::Expression(expression),
+            License::File(file) => spec::License::File(file),
+        }
+    }
+}
+
+impl From<&spec::Repository> for Repository {
+    fn from(value: &spec::Repository) -> Self {
+        Repository {
+            repository_type: value.repository_type.clone(),
+            url: value.url.clone(),
+            branch: value.branch.clone(),
+            commit: value.commit.clone(),
+        }
+    }
+}
+
+impl From<Repository> for spec::Repository {
+    fn from(value: Repository) -> Self {
+        spec::Repository {
+            repository_type: value.repository_type,
+            url: value.url,
+            branch: value.branch,
+            commit: value.commit,
+        }
+    }
+}
+
+impl From<&spec::Dependency> for Dependency {
+    fn from(value: &spec::Dependency) -> Self {
+        Dependency {
+            id: value.id.clone(),
+            version: value.version.clone(),
+            include: value.include.clone(),
+            exclude: value.exclude.clone(),
+        }
+    }
+}
+
+impl From<Dependency> for spec::Dependency {
+    fn from(value: Dependency) -> Self {
+        spec::Dependency {
+            id: value.id,
+            version: value.version,
+            include: value.include,
+            exclude: value.exclude,
+        }
+    }
+}
+
+impl From<&spec::ContentFile> for ContentFile {
+    fn from(value: &spec::ContentFile) -> Self {
+        ContentFile {
+            include: value.include.clone(),
+            exclude: value.exclude.clone(),
+            build_action: value.build_action.as_ref().map(|b| b.to_string()),
+            copy_to_output: value.copy_to_output,
+            flatten: value.flatten,
+        }
+    }
+}
+
+impl From<ContentFile> for spec::ContentFile {
+    fn from(value: ContentFile) -> Self {
+        spec::ContentFile {
+            include: value.include,
+            exclude: value.exclude,
+            build_action: value
+                .build_action
+                .map(|b| spec::BuildAction::from(b.as_str())),
+            copy_to_output: value.copy_to_output,
+            flatten: value.flatten,
+        }
+    }
+}
+
+impl From<&spec::File> for File {
+    fn from(value: &spec::File) -> Self {
+        File {
+            src: value.src.clone(),
+            target: value.target.clone(),
+            exclude: value.exclude.clone(),
+        }
+    }
+}
+
+impl From<File> for spec::File {
+    fn from(value: File) -> Self {
+        spec::File {
+            src: value.src,
+            target: value.target,
+            exclude: value.exclude,
+        }
+    }
+}
+
+/// Serializes a nuspec package with the natural keys, to be used with `#[serde(with = "nuspec::neutral")]`.
+pub fn serialize<S>(value: &spec::Package, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    Package::from(value).serialize(serializer)
+}
+
+/// Deserializes a nuspec package from the natural keys, to be used with `#[serde(with = "nuspec::neutral")]`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<spec::Package, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Package::deserialize(deserializer).map(spec::Package::from)
+}
+
+/// Serializes a nuspec package to a pretty-printed JSON string.
+#[cfg(feature = "json")]
+pub fn to_json_string(value: &spec::Package) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&Package::from(value))
+}
+
+/// Deserializes a nuspec package from a JSON string.
+#[cfg(feature = "json")]
+pub fn from_json_str(s: &str) -> Result<spec::Package, serde_json::Error> {
+    serde_json::from_str::<Package>(s).map(spec::Package::from)
+}
+
+/// Serializes a nuspec package to a YAML string.
+#[cfg(feature = "yaml")]
+pub fn to_yaml_string(value: &spec::Package) -> Result<String, serde_yaml_ng::Error> {
+    serde_yaml_ng::to_string(&Package::from(value))
+}
+
+/// Deserializes a nuspec package from a YAML string.
+#[cfg(feature = "yaml")]
+pub fn from_yaml_str(s: &str) -> Result<spec::Package, serde_yaml_ng::Error> {
+    serde_yaml_ng::from_str::<Package>(s).map(spec::Package::from)
+}
+
+/// Serializes a nuspec package to a TOML string.
+#[cfg(feature = "toml")]
+pub fn to_toml_string(value: &spec::Package) -> Result<String, toml::ser::Error> {
+    toml::to_string(&Package::from(value))
+}
+
+/// Deserializes a nuspec package from a TOML string.
+#[cfg(feature = "toml")]
+pub fn from_toml_str(s: &str) -> Result<spec::Package, toml::de::Error> {
+    toml::from_str::<Package>(s).map(spec::Package::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package() -> spec::Package {
+        spec::Package {
+            namespace: Some(
+                "http://schemas.microsoft.com/packaging/2013/05/nuspec.xsd".to_string(),
+            ),
+            metadata: spec::Metadata {
+                id: "example-package".to_string(),
+                version: "1.0.0".to_string(),
+                description: "An example NuGet package".to_string(),
+                authors: vec!["Author One".to_string(), "Author Two".to_string()],
+                project_url: Some("https://example.com".to_string()),
+                license: Some(spec::License::Expression("MIT".to_string())),
+                tags: Some(vec!["tag1".to_string(), "tag2".to_string()]),
+                repository: Some(spec::Repository {
+                    repository_type: Some("git".to_string()),
+                    url: Some("https://github.com/example/example.git".to_string()),
+                    ..Default::default()
+                }),
+                min_client_version: Some("3.3".to_string()),
+                package_types: Some(spec::PackageTypes {
+                    package_type: vec![spec::PackageType {
+                        name: spec::KnownPackageType::DotnetTool,
+                        version: None,
+                    }],
+                }),
+                dependencies: Some(spec::Dependencies {
+                    dependency: Some(vec![spec::Dependency {
+                        id: "X".to_string(),
+                        version: "[1,2)".to_string(),
+                        include: Some(vec!["runtime".to_string()]),
+                        ..Default::default()
+                    }]),
+                    group: Some(vec![spec::DependencyGroup {
+                        target_framework: Some("net8.0".to_string()),
+                        dependency: vec![spec::Dependency {
+                            id: "Y".to_string(),
+                            version: "2.0".to_string(),
+                            ..Default::default()
+                        }],
+                    }]),
+                }),
+                references: Some(spec::References {
+                    group: Some(vec![spec::ReferenceGroup {
+                        target_framework: Some("net8.0".to_string()),
+                        reference: vec![spec::Reference {
+                            file: "Example.dll".to_string(),
+                        }],
+                    }]),
+                    ..Default::default()
+                }),
+                content_files: Some(spec::ContentFiles {
+                    files: vec![spec::ContentFile {
+                        include: "cs/any/*.cs".to_string(),
+                        build_action: Some(spec::BuildAction::Compile),
+                        ..Default::default()
+                    }],
+                }),
+                ..Default::default()
+            },
+            files: Some(spec::Files {
+                file: vec![spec::File {
+                    src: "bin/tool.exe".to_string(),
+                    target: Some("tools".to_string()),
+                    ..Default::default()
+                }],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_conversion_round_trip() {
+        let pkg = package();
+        let neutral = Package::from(&pkg);
+        assert_eq!(
+            neutral.metadata.license,
+            Some(License::Expression("MIT".to_string()))
+        );
+        assert_eq!(
+            neutral.metadata.dependency_groups.as_ref().unwrap().len(),
+            1
+        );
+        assert_eq!(
+            neutral.metadata.reference_groups.as_ref().unwrap()[0].references,
+            vec!["Example.dll".to_string()]
+        );
+        assert_eq!(spec::Package::from(neutral), pkg);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json() {
+        let json = to_json_string(&package()).unwrap();
+        assert!(json.contains(
+            r#""license": {
+      "expression": "MIT"
+    }"#
+        ));
+        assert!(json.contains(r#""project_url": "https://example.com""#));
+        assert!(!json.contains('@'));
+        assert_eq!(from_json_str(&json).unwrap(), package());
+
+        let err = from_json_str(r#"{"metadata": {"license": {"expression": "MIT", "file": "x"}}}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("expected exactly one of"));
+
+        let err = from_json_str(r#"{"metadata": {"projectUrl": "x"}}"#).unwrap_err();
+        assert!(err.to_string().contains("unknown field `projectUrl`"));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml() {
+        let yaml = to_yaml_string(&package()).unwrap();
+        assert!(yaml.contains("  license:\n    expression: MIT\n"));
+        assert_eq!(from_yaml_str(&yaml).unwrap(), package());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml() {
+        let pkg = from_toml_str(
+            r#"
+            [metadata]
+            id = "example-package"
+            version = "1.0.0"
+            license = { file = "LICENSE.txt" }
+            require_license_acceptance = true
+
+            [[metadata.dependency_groups]]
+            target_framework = "net8.0"
+            dependencies = [{ id = "Y", version = "2.0" }]
+
+            [[files]]
+            src = "LICENSE.txt"
+            target = ""
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            pkg.metadata.license,
+            Some(spec::License::File("LICENSE.txt".to_string()))
+        );
+        assert_eq!(pkg.metadata.require_license_acceptance, Some(true));
+        assert_eq!(
+            pkg.metadata.dependencies.unwrap().group.unwrap()[0].dependency[0].id,
+            "Y"
+        );
+        assert_eq!(pkg.files.unwrap().file[0].src, "LICENSE.txt");
+
+        let pkg = package();
+        assert_eq!(from_toml_str(&to_toml_string(&pkg).unwrap()).unwrap(), pkg);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_serde_adapter() {
+        #[derive(Serialize, Deserialize)]
+        struct Report {
+            #[serde(with = "crate::neutral")]
+            package: spec::Package,
+        }
+        let json = serde_json::to_string(&Report { package: package() }).unwrap();
+        assert!(json.starts_with(r#"{"package":{"xmlns":"#));
+        let report: Report = serde_json::from_str(&json).unwrap();
+        assert_eq!(report.package, package());
+    }
+}
@@ -0,0 +1,602 @@
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::{env, error, fs, path};
+
+/// The names of the NuGet configuration files, in the order they are looked up in a folder.
+pub const NUGET_CONFIG_FILES: [&str; 3] = ["nuget.config", "NuGet.config", "NuGet.Config"];
+
+/// A package source of a NuGet configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PackageSource {
+    /// The name of the source, such as `nuget.org`.
+    pub key: String,
+    /// The URL of the service index or the path of a local folder, made absolute relative to
+    /// the configuration file that declares it.
+    pub url: String,
+    pub protocol_version: Option<u32>,
+    /// False if the source is listed in the `disabledPackageSources` section.
+    pub enabled: bool,
+    pub credentials: Option<SourceCredentials>,
+}
+
+impl PackageSource {
+    /// Returns true if the source is a folder rather than an HTTP feed.
+    pub fn is_local(&self) -> bool {
+        !self.url.starts_with("http://") && !self.url.starts_with("https://")
+    }
+}
+
+/// The credentials of a package source from the `packageSourceCredentials` section.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SourceCredentials {
+    pub username: Option<String>,
+    /// The password, encrypted unless [`SourceCredentials::clear_text`] is set.
+    /// The encrypted passwords are supported only by NuGet on Windows.
+    pub password: Option<String>,
+    pub clear_text: bool,
+    /// The authentication types, such as `basic` or `negotiate`.
+    pub valid_authentication_types: Option<String>,
+}
+
+/// The effective NuGet configuration, merged from the `nuget.config` files.
+///
+/// The files are applied from the lowest priority to the highest one: a key of a higher
+/// priority file overrides the same key of a lower priority one, `<remove>` deletes a key and
+/// `<clear />` drops everything the lower priority files have declared in the section.
+/// The `%NAME%` references to the environment variables are expanded in the values.
+///
+/// See [Common NuGet configurations](https://learn.microsoft.com/en-us/nuget/consume-packages/configuring-nuget-behavior).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NuGetConfig {
+    /// The package sources in the order of their declaration, including the disabled ones.
+    pub sources: Vec<PackageSource>,
+    /// The `packageSourceMapping` section: the source keys with their package id patterns.
+    pub source_mapping: Vec<(String, Vec<String>)>,
+    /// The `apikeys` section: the source URLs with their encrypted API keys.
+    pub api_keys: Vec<(String, String)>,
+    /// The `config` section, such as `globalPackagesFolder`.
+    pub config: Vec<(String, String)>,
+    // The `disabledPackageSources` and `packageSourceCredentials` sections, applied to
+    // the sources when a file is merged.
+    disabled: Vec<(String, String)>,
+    credentials: Vec<(String, SourceCredentials)>,
+}
+
+impl NuGetConfig {
+    /// Loads the configuration that applies to a folder: the files in the folder and its
+    /// ancestors, the closest one has the highest priority, and then the user file, such as
+    /// `~/.nuget/NuGet/NuGet.Config`, with the lowest priority.
+    pub fn load<P: AsRef<path::Path>>(dir: P) -> Result<Self, Box<dyn error::Error>> {
+        let mut files: Vec<path::PathBuf> = dir
+            .as_ref()
+            .ancestors()
+            .filter_map(|dir| {
+                NUGET_CONFIG_FILES
+                    .iter()
+                    .map(|name| dir.join(name))
+                    .find(|file| file.is_file())
+            })
+            .collect();
+        if let Some(user) = user_config_file().filter(|f| f.is_file() && !files.contains(f)) {
+            files.push(user);
+        }
+        files.reverse();
+        NuGetConfig::load_files(&files)
+    }
+
+    /// Loads the configuration files, each one takes precedence over the previous ones.
+    pub fn load_files<P: AsRef<path::Path>>(files: &[P]) -> Result<Self, Box<dyn error::Error>> {
+        let mut config = NuGetConfig::default();
+        for file in files {
+            let file = file.as_ref();
+            let xml = fs::read_to_string(file)
+                .map_err(|e| format!("Failed to read the NuGet config {file:?}: {e}"))?;
+            let dir = file.parent().unwrap_or(path::Path::new("."));
+            config
+                .merge(&xml, dir)
+                .map_err(|e| format!("Invalid NuGet config {file:?}: {e}"))?;
+        }
+        Ok(config)
+    }
+
+    /// Applies a configuration document on top of the current configuration. The relative
+    /// paths of the local sources are resolved from `dir`.
+    pub fn merge(&mut self, xml: &str, dir: &path::Path) -> Result<(), Box<dyn error::Error>> {
+        self.merge_with(xml, dir, &|name| env::var(name).ok())
+    }
+
+    fn merge_with(
+        &mut self,
+        xml: &str,
+        dir: &path::Path,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let root = Element::parse(xml)?;
+        if !root.name.eq_ignore_ascii_case("configuration") {
+            return Err(format!("unexpected root element `{}`", root.name).into());
+        }
+        let expand = |value: &str| expand_variables(value, lookup);
+        for section in &root.children {
+            match section.name.as_str() {
+                "packageSources" => apply(&mut self.sources, section, |item| {
+                    let key = item.attribute("key")?;
+                    let url = resolve_source(&expand(item.attribute("value")?), dir);
+                    Some(PackageSource {
+                        key: key.to_string(),
+                        url,
+                        protocol_version: item
+                            .attribute("protocolVersion")
+                            .and_then(|v| v.parse().ok()),
+                        enabled: true,
+                        credentials: None,
+                    })
+                }),
+                "disabledPackageSources" => {
+                    apply(&mut self.disabled, section, |item| key_value(item, &expand))
+                }
+                "apikeys" => apply(&mut self.api_keys, section, |item| key_value(item, &expand)),
+                "config" => apply(&mut self.config, section, |item| key_value(item, &expand)),
+                "packageSourceMapping" => apply(&mut self.source_mapping, section, |item| {
+                    let patterns = item
+                        .children
+                        .iter()
+                        .filter(|p| p.name == "package")
+                        .filter_map(|p| p.attribute("pattern"))
+                        .map(|p| p.trim().to_string())
+                        .collect();
+                    Some((item.attribute("key")?.to_string(), patterns))
+                }),
+                "packageSourceCredentials" => {
+                    for source in &section.children {
+                        if source.name == "clear" {
+                            self.credentials.clear();
+                            continue;
+                        }
+                        let key = decode_name(&source.name);
+                        let credentials = credentials(source, &expand);
+                        match self
+                            .credentials
+                            .iter_mut()
+                            .find(|(k, _)| k.eq_ignore_ascii_case(&key))
+                        {
+                            Some(existing) => existing.1 = credentials,
+                            None => self.credentials.push((key, credentials)),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        // The sections may refer to the sources declared in the other files
+        for source in self.sources.iter_mut() {
+            source.enabled = !self.disabled.iter().any(|(k, v)| {
+                k.eq_ignore_ascii_case(&source.key) && v.eq_ignore_ascii_case("true")
+            });
+            source.credentials = self
+                .credentials
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(&source.key))
+                .map(|(_, c)| c.clone());
+        }
+        Ok(())
+    }
+
+    /// Returns the source with the key, ignoring the case.
+    pub fn source(&self, key: &str) -> Option<&PackageSource> {
+        self.sources
+            .iter()
+            .find(|s| s.key.eq_ignore_ascii_case(key))
+    }
+
+    /// Returns the enabled sources.
+    pub fn enabled_sources(&self) -> impl Iterator<Item = &PackageSource> {
+        self.sources.iter().filter(|s| s.enabled)
+    }
+
+    /// Returns the enabled sources a package is restored from.
+    ///
+    /// Without a `packageSourceMapping` section every source is used. Otherwise only the
+    /// sources with the most specific pattern matching the id are: an exact id wins over
+    /// a prefix such as `Contoso.*`, and a longer prefix wins over a shorter one.
+    pub fn sources_for(&self, id: &str) -> Vec<&PackageSource> {
+        if self.source_mapping.is_empty() {
+            return self.enabled_sources().collect();
+        }
+        let specificity = |pattern: &str| match pattern.strip_suffix('*') {
+            Some(prefix) => {
+                let matches = id
+                    .get(..prefix.len())
+                    .is_some_and(|p| p.eq_ignore_ascii_case(prefix));
+                matches.then_some(prefix.len())
+            }
+            // The exact id is more specific than any prefix
+            None => id.eq_ignore_ascii_case(pattern).then_some(usize::MAX),
+        };
+        let best = |patterns: &[String]| patterns.iter().filter_map(|p| specificity(p)).max();
+        let Some(most_specific) = self
+            .source_mapping
+            .iter()
+            .filter_map(|(_, p)| best(p))
+            .max()
+        else {
+            return Vec::new();
+        };
+        self.enabled_sources()
+            .filter(|source| {
+                self.source_mapping.iter().any(|(key, patterns)| {
+                    key.eq_ignore_ascii_case(&source.key) && best(patterns) == Some(most_specific)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the encrypted API key of a source URL.
+    pub fn api_key(&self, source: &str) -> Option<&str> {
+        lookup(&self.api_keys, source)
+    }
+
+    /// Returns a value of the `config` section, such as `globalPackagesFolder`.
+    pub fn config_value(&self, key: &str) -> Option<&str> {
+        lookup(&self.config, key)
+    }
+}
+
+// Returns the user configuration file: `%APPDATA%\NuGet\NuGet.Config` on Windows and
+// `~/.nuget/NuGet/NuGet.Config` elsewhere.
+fn user_config_file() -> Option<path::PathBuf> {
+    let dir = if cfg!(windows) {
+        path::PathBuf::from(env::var_os("APPDATA")?)
+    } else {
+        path::PathBuf::from(env::var_os("HOME")?).join(".nuget")
+    };
+    Some(dir.join("NuGet").join("NuGet.Config"))
+}
+
+// Applies the `add`, `remove` and `clear` items of a section to the items of the lower
+// priority files, the keys are compared ignoring the case.
+fn apply<T, F>(items: &mut Vec<T>, section: &Element, parse: F)
+where
+    T: Keyed,
+    F: Fn(&Element) -> Option<T>,
+{
+    for item in &section.children {
+        match item.name.as_str() {
+            "clear" => items.clear(),
+            "remove" => {
+                if let Some(key) = item.attribute("key") {
+                    items.retain(|i| !i.key().eq_ignore_ascii_case(key));
+                }
+            }
+            _ => {
+                if let Some(new) = parse(item) {
+                    match items
+                        .iter_mut()
+                        .find(|i| i.key().eq_ignore_ascii_case(new.key()))
+                    {
+                        Some(existing) => *existing = new,
+                        None => items.push(new),
+                    }
+                }
+            }
+        }
+    }
+}
+
+trait Keyed {
+    fn key(&self) -> &str;
+}
+
+impl Keyed for PackageSource {
+    fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl<T> Keyed for (String, T) {
+    fn key(&self) -> &str {
+        &self.0
+    }
+}
+
+fn key_value(item: &Element, expand: &dyn Fn(&str) -> String) -> Option<(String, String)> {
+    Some((
+        item.attribute("key")?.to_string(),
+        expand(item.attribute("value")?),
+    ))
+}
+
+fn lookup<'a>(items: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    items
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.as_str())
+}
+
+fn credentials(source: &Element, expand: &dyn Fn(&str) -> String) -> SourceCredentials {
+    let mut credentials = SourceCredentials::default();
+    for (key, value) in source.children.iter().filter_map(|i| key_value(i, expand)) {
+        match key.to_lowercase().as_str() {
+            "username" => credentials.username = Some(value),
+            "password" => credentials.password = Some(value),
+            "cleartextpassword" => {
+                credentials.password = Some(value);
+                credentials.clear_text = true;
+            }
+            "validauthenticationtypes" => credentials.valid_authentication_types = Some(value),
+            _ => {}
+        }
+    }
+    credentials
+}
+
+// Makes the path of a local source absolute, the URLs are returned as they are.
+fn resolve_source(value: &str, dir: &path::Path) -> String {
+    if value.contains("://") || path::Path::new(value).is_absolute() || value.starts_with("\\\\") {
+        return value.to_string();
+    }
+    dir.join(value).to_string_lossy().to_string()
+}
+
+/// Expands the `%NAME%` references to the variables, the unknown ones are kept as they are.
+pub fn expand_variables(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(end) if end > 0 => {
+                let name = &after[..end];
+                match lookup(name) {
+                    Some(value) => result.push_str(&value),
+                    None => {
+                        result.push('%');
+                        result.push_str(name);
+                        result.push('%');
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                result.push('%');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// Decodes the names of the elements encoded by .NET, such as `_x0020_` for a space.
+fn decode_name(name: &str) -> String {
+    let mut result = String::new();
+    let mut rest = name;
+    while let Some(start) = rest.find("_x") {
+        let code = rest
+            .get(start + 2..start + 7)
+            .filter(|c| c.ends_with('_'))
+            .and_then(|c| u32::from_str_radix(&c[..4], 16).ok())
+            .and_then(char::from_u32);
+        match code {
+            Some(c) => {
+                result.push_str(&rest[..start]);
+                result.push(c);
+                rest = &rest[start + 7..];
+            }
+            None => {
+                result.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// An element of a configuration document, the text content is not used.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn parse(xml: &str) -> Result<Element, Box<dyn error::Error>> {
+        let mut reader = Reader::from_str(xml.trim_start_matches('\u{feff}'));
+        let mut stack: Vec<Element> = Vec::new();
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => stack.push(Element::new(&e)?),
+                Event::Empty(e) => {
+                    let element = Element::new(&e)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().ok_or("unexpected closing element")?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Eof => return Err("the document has no root element".into()),
+                _ => {}
+            }
+        }
+    }
+
+    fn new(start: &BytesStart) -> Result<Element, Box<dyn error::Error>> {
+        let mut attributes = Vec::new();
+        for attribute in start.attributes() {
+            let attribute = attribute?;
+            attributes.push((
+                String::from_utf8_lossy(attribute.key.as_ref()).to_string(),
+                attribute.unescape_value()?.to_string(),
+            ));
+        }
+        Ok(Element {
+            name: String::from_utf8_lossy(start.name().as_ref()).to_string(),
+            attributes,
+            children: Vec::new(),
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+
+    const USER: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<configuration>
+  <packageSources>
+    <add key="nuget.org" value="https://api.nuget.org/v3/index.json" protocolVersion="3" />
+    <add key="Old Feed" value="https://old.example.com/v3/index.json" />
+  </packageSources>
+  <config>
+    <add key="globalPackagesFolder" value="%HOME%/packages" />
+  </config>
+</configuration>"#;
+
+    const REPO: &str = r#"<configuration>
+  <packageSources>
+    <remove key="old feed" />
+    <add key="Contoso Feed" value="https://contoso.example.com/v3/index.json" />
+    <add key="offline" value="packages" />
+  </packageSources>
+  <disabledPackageSources>
+    <add key="offline" value="true" />
+  </disabledPackageSources>
+  <packageSourceCredentials>
+    <Contoso_x0020_Feed>
+      <add key="Username" value="build" />
+      <add key="ClearTextPassword" value="%CONTOSO_TOKEN%" />
+    </Contoso_x0020_Feed>
+  </packageSourceCredentials>
+  <apikeys>
+    <add key="https://contoso.example.com/v3/index.json" value="secret" />
+  </apikeys>
+  <packageSourceMapping>
+    <packageSource key="nuget.org">
+      <package pattern="*" />
+    </packageSource>
+    <packageSource key="Contoso Feed">
+      <package pattern="Contoso.*" />
+      <package pattern="Contoso.Tools.Special" />
+    </packageSource>
+  </packageSourceMapping>
+</configuration>"#;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/build".to_string()),
+            "CONTOSO_TOKEN" => Some("token".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut config = NuGetConfig::default();
+        config
+            .merge_with(USER, path::Path::new("/home/build"), &lookup)
+            .unwrap();
+        config
+            .merge_with(REPO, path::Path::new("/src/repo"), &lookup)
+            .unwrap();
+
+        let keys: Vec<&str> = config.sources.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["nuget.org", "Contoso Feed", "offline"]);
+        assert_eq!(
+            config.source("NuGet.org").unwrap().protocol_version,
+            Some(3)
+        );
+        let offline = config.source("offline").unwrap();
+        assert!(!offline.enabled);
+        assert!(offline.is_local());
+        assert_eq!(
+            offline.url,
+            path::Path::new("/src/repo")
+                .join("packages")
+                .to_string_lossy()
+        );
+        assert_eq!(config.enabled_sources().count(), 2);
+        let credentials = config
+            .source("Contoso Feed")
+            .unwrap()
+            .credentials
+            .clone()
+            .unwrap();
+        assert_eq!(credentials.username.as_deref(), Some("build"));
+        assert_eq!(credentials.password.as_deref(), Some("token"));
+        assert!(credentials.clear_text);
+        assert_eq!(
+            config.api_key("https://contoso.example.com/v3/index.json"),
+            Some("secret")
+        );
+        assert_eq!(
+            config.config_value("globalpackagesfolder"),
+            Some("/home/build/packages")
+        );
+
+        let sources = |id: &str| -> Vec<String> {
+            config
+                .sources_for(id)
+                .iter()
+                .map(|s| s.key.clone())
+                .collect()
+        };
+        assert_eq!(sources("Newtonsoft.Json"), ["nuget.org"]);
+        assert_eq!(sources("contoso.core"), ["Contoso Feed"]);
+        assert_eq!(sources("Contoso.Tools.Special"), ["Contoso Feed"]);
+
+        // A clear in a higher priority file drops the sources of the lower ones
+        config
+            .merge_with(
+                r#"<configuration><packageSources><clear /></packageSources>
+                   <packageSourceMapping><clear /></packageSourceMapping></configuration>"#,
+                path::Path::new("/src/repo/sub"),
+                &lookup,
+            )
+            .unwrap();
+        assert!(config.sources.is_empty());
+        assert!(config.sources_for("Contoso.Core").is_empty());
+        assert!(
+            config
+                .merge_with("<settings />", path::Path::new("."), &lookup)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = TempDir::new("nuget-config");
+        fs::create_dir_all(dir.join("sub/project")).unwrap();
+        fs::write(dir.join("NuGet.Config"), USER).unwrap();
+        fs::write(dir.join("sub/nuget.config"), REPO).unwrap();
+        let config = NuGetConfig::load(dir.join("sub/project")).unwrap();
+        assert!(config.source("nuget.org").is_some());
+        assert!(config.source("Old Feed").is_none());
+        assert!(config.source("Contoso Feed").is_some());
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        assert_eq!(
+            expand_variables("%HOME%/x/%MISSING%/100%", &lookup),
+            "/home/build/x/%MISSING%/100%"
+        );
+        assert_eq!(expand_variables("%%", &lookup), "%%");
+        assert_eq!(decode_name("Contoso_x0020_Feed_x"), "Contoso Feed_x");
+    }
+}
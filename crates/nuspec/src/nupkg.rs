@@ -0,0 +1,203 @@
+use crate::{NuGetVersion, Package, PackagePath, from_str};
+use std::io::{Read, Seek};
+use std::{error, fs, path};
+use zip::ZipArchive;
+
+/// The entry with the signature of a signed package.
+pub const SIGNATURE_FILE: &str = ".signature.p7s";
+
+/// A reader of a `.nupkg` file.
+pub struct NupkgReader<R> {
+    archive: ZipArchive<R>,
+}
+
+impl NupkgReader<fs::File> {
+    /// Opens a `.nupkg` file.
+    pub fn open<P: AsRef<path::Path>>(nupkg: P) -> Result<Self, Box<dyn error::Error>> {
+        let nupkg = nupkg.as_ref();
+        let file = fs::File::open(nupkg)
+            .map_err(|e| format!("Failed to open the package {nupkg:?}: {e}"))?;
+        NupkgReader::new(file).map_err(|e| format!("Invalid package {nupkg:?}: {e}").into())
+    }
+}
+
+impl<R: Read + Seek> NupkgReader<R> {
+    pub fn new(reader: R) -> Result<Self, Box<dyn error::Error>> {
+        Ok(NupkgReader {
+            archive: ZipArchive::new(reader)?,
+        })
+    }
+
+    /// Returns the name of the nuspec file, the only `.nuspec` entry at the root of the package.
+    pub fn nuspec_name(&self) -> Result<String, Box<dyn error::Error>> {
+        let mut names = self
+            .archive
+            .file_names()
+            .filter(|name| !name.contains('/') && name.to_lowercase().ends_with(".nuspec"));
+        match (names.next(), names.next()) {
+            (Some(name), None) => Ok(unescape_part(name)),
+            (None, _) => Err("The package has no nuspec file".into()),
+            (Some(_), Some(_)) => Err("The package has more than one nuspec file".into()),
+        }
+    }
+
+    /// Returns the content of the nuspec file as it is stored in the package.
+    pub fn nuspec_bytes(&mut self) -> Result<Vec<u8>, Box<dyn error::Error>> {
+        let name = self.nuspec_name()?;
+        self.read(&PackagePath::new(&name))
+    }
+
+    /// Parses the nuspec file of the package.
+    pub fn nuspec(&mut self) -> Result<Package, Box<dyn error::Error>> {
+        let bytes = self.nuspec_bytes()?;
+        let xml = String::from_utf8(bytes)?;
+        // The byte order mark written by the .NET tools
+        Ok(from_str(xml.trim_start_matches('\u{feff}'))?)
+    }
+
+    /// Returns the id and the version of the package.
+    pub fn identity(&mut self) -> Result<(String, NuGetVersion), Box<dyn error::Error>> {
+        let metadata = self.nuspec()?.metadata;
+        let version = metadata.version.parse()?;
+        Ok((metadata.id, version))
+    }
+
+    /// Returns the paths of the files of the package, without the nuspec file, the signature
+    /// and the parts of the package format, such as `[Content_Types].xml`.
+    pub fn files(&self) -> Vec<PackagePath> {
+        let nuspec = self.nuspec_name().ok();
+        self.archive
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .map(unescape_part)
+            .filter(|name| !is_package_part(name) && Some(name) != nuspec.as_ref())
+            .map(|name| PackagePath::new(&name))
+            .collect()
+    }
+
+    /// Returns the content of the signature file, `None` for an unsigned package.
+    pub fn signature(&mut self) -> Result<Option<Vec<u8>>, Box<dyn error::Error>> {
+        if self.archive.index_for_name(SIGNATURE_FILE).is_none() {
+            return Ok(None);
+        }
+        self.read(&PackagePath::new(SIGNATURE_FILE)).map(Some)
+    }
+
+    /// Returns the names of all the entries as they are stored, including the package format
+    /// parts.
+    pub fn entry_names(&self) -> Vec<String> {
+        self.archive.file_names().map(String::from).collect()
+    }
+
+    /// Reads a file of the package.
+    pub fn read(&mut self, path: &PackagePath) -> Result<Vec<u8>, Box<dyn error::Error>> {
+        let name = self
+            .archive
+            .file_names()
+            .find(|name| PackagePath::new(&unescape_part(name)) == *path)
+            .map(String::from)
+            .ok_or(format!("The package has no file {path}"))?;
+        let mut entry = self.archive.by_name(&name)?;
+        // The declared size comes from the archive, so it is not trusted to pre-size the buffer
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        Ok(content)
+    }
+}
+
+// Returns true for the parts of the package format, which are not files of the package.
+fn is_package_part(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower == "[content_types].xml"
+        || lower == SIGNATURE_FILE
+        || lower.starts_with("_rels/")
+        || lower.starts_with("package/")
+}
+
+// Decodes an OPC part name, the reverse of the escaping done by `pack`.
+pub(crate) fn unescape_part(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{self, Write};
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    /// Writes a minimal package with the given files, which are stored as they are named.
+    pub(crate) fn test_package(id: &str, version: &str, files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        zip.start_file(format!("{id}.nuspec"), options).unwrap();
+        write!(
+            zip,
+            "\u{feff}<?xml version=\"1.0\" encoding=\"utf-8\"?><package><metadata><id>{id}</id><version>{version}</version><description>Test</description><authors>Jane Doe</authors></metadata></package>"
+        )
+        .unwrap();
+        for (name, content) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.start_file("_rels/.rels", options).unwrap();
+        zip.start_file("[Content_Types].xml", options).unwrap();
+        zip.start_file(
+            "package/services/metadata/core-properties/0.psmdcp",
+            options,
+        )
+        .unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_reader() {
+        let content = test_package(
+            "Example",
+            "1.0.0.0",
+            &[
+                ("lib/net8.0/Example.dll", "dll"),
+                ("docs/read%20me.md", "# Example"),
+                (SIGNATURE_FILE, "signature"),
+            ],
+        );
+        let mut reader = NupkgReader::new(io::Cursor::new(content)).unwrap();
+        assert_eq!(reader.nuspec_name().unwrap(), "Example.nuspec");
+        let (id, version) = reader.identity().unwrap();
+        assert_eq!(id, "Example");
+        assert_eq!(version.to_normalized_string(), "1.0.0");
+        assert_eq!(
+            reader.files(),
+            [
+                PackagePath::new("lib/net8.0/Example.dll"),
+                PackagePath::new("docs/read me.md")
+            ]
+        );
+        assert_eq!(
+            reader.read(&PackagePath::new("docs/read me.md")).unwrap(),
+            b"# Example"
+        );
+        assert!(reader.read(&PackagePath::new("missing.txt")).is_err());
+        assert_eq!(reader.signature().unwrap(), Some(b"signature".to_vec()));
+        assert_eq!(unescape_part("a%2Bb%25%zz"), "a+b%%zz");
+    }
+}
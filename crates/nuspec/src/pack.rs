@@ -0,0 +1,574 @@
+use crate::copyright::civil_from_timestamp;
+use crate::{KnownPackageType, NuGetVersion, Package, PackagePath, from_str, to_string_indent};
+use quick_xml::escape::escape;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::{env, error, fs, io, path};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+/// The modification time of the package entries if `SOURCE_DATE_EPOCH` is not set,
+/// 1980-01-01, the earliest date of the zip format.
+pub const ZIP_EPOCH: i64 = 315_532_800;
+
+/// The options of [`pack`].
+///
+/// A package is always written the same way for the same inputs: the entries are sorted,
+/// all of them have the same modification time and permissions and the compression is fixed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackOptions {
+    /// The modification time of all the entries, as a Unix timestamp.
+    pub timestamp: i64,
+    /// The deflate compression level, from 0 to 9.
+    pub compression_level: i64,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        PackOptions {
+            timestamp: ZIP_EPOCH,
+            compression_level: 6,
+        }
+    }
+}
+
+impl PackOptions {
+    /// Returns the default options with the timestamp from the `SOURCE_DATE_EPOCH` variable.
+    pub fn from_env() -> Result<Self, Box<dyn error::Error>> {
+        let mut options = PackOptions::default();
+        if let Ok(epoch) = env::var("SOURCE_DATE_EPOCH") {
+            options.timestamp = epoch
+                .trim()
+                .parse()
+                .map_err(|e| format!("Invalid SOURCE_DATE_EPOCH {epoch:?}: {e}"))?;
+        }
+        Ok(options)
+    }
+}
+
+/// A file of a package: the path on disk and the path within the package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageEntry {
+    pub source: path::PathBuf,
+    pub path: PackagePath,
+}
+
+/// Resolves the files of a package relative to a base directory, usually the directory of
+/// the nuspec file.
+///
+/// The `*`, `?` and `**` wildcards of the sources are expanded, skipping the hidden files,
+/// and the `exclude` patterns are applied. The entries are sorted by their package path.
+pub fn package_entries(
+    pkg: &Package,
+    base_dir: &path::Path,
+) -> Result<Vec<PackageEntry>, Box<dyn error::Error>> {
+    let mut entries: Vec<PackageEntry> = Vec::new();
+    for file in pkg.files.iter().flat_map(|f| f.file.iter()) {
+        let target = file.target_path();
+        let excludes: Vec<Vec<&str>> = file
+            .exclude
+            .iter()
+            .flatten()
+            .map(|e| split_pattern(e))
+            .collect();
+
+        let components = split_pattern(&file.src);
+        let wildcard = components.iter().position(|c| c.contains(['*', '?']));
+        let matches = match wildcard {
+            Some(i) => {
+                let root = base_dir.join(components[..i].join("/"));
+                let mut found = Vec::new();
+                walk(&root, &mut Vec::new(), &mut found)?;
+                found
+                    .into_iter()
+                    .filter(|relative| {
+                        let relative: Vec<&str> = relative.iter().map(String::as_str).collect();
+                        let full: Vec<&str> = components[..i]
+                            .iter()
+                            .copied()
+                            .chain(relative.clone())
+                            .collect();
+                        match_components(&components[i..], &relative)
+                            && !excludes.iter().any(|e| match_components(e, &full))
+                    })
+                    .map(|relative| {
+                        let relative = relative.join("/");
+                        (root.join(&relative), target.join(&relative))
+                    })
+                    .collect()
+            }
+            None => {
+                let source = base_dir.join(file.source_path());
+                if !source.is_file() {
+                    return Err(format!("The file {source:?} does not exist").into());
+                }
+                let path = file
+                    .package_path()
+                    .ok_or(format!("Failed to get the file name from {:?}", file.src))?;
+                vec![(source, path)]
+            }
+        };
+        for (source, path) in matches {
+            if path.is_escaping() {
+                return Err(format!("The package path `{path}` is outside of the package").into());
+            }
+            if entries.iter().any(|e| e.path.eq_ignore_case(&path)) {
+                return Err(format!("The package path `{path}` is used more than once").into());
+            }
+            entries.push(PackageEntry { source, path });
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Packs a nuspec file into a `{id}.{version}.nupkg` file in the output directory and returns
+/// its path. A package of the `SymbolsPackage` type is written to a `{id}.{version}.snupkg` file.
+///
+/// The files are resolved relative to the directory of the nuspec file, see
+/// [`package_entries`]. The output is reproducible: two runs with the same inputs and options
+/// produce byte-identical packages.
+///
+/// A Chocolatey package whose `tools/VERIFICATION.txt` file still has pending checksums is
+/// refused, see `update_chocolatey_verification`.
+pub fn pack<P: AsRef<path::Path>, Q: AsRef<path::Path>>(
+    nuspec_file: P,
+    out_dir: Q,
+    options: &PackOptions,
+) -> Result<path::PathBuf, Box<dyn error::Error>> {
+    let nuspec_file = nuspec_file.as_ref();
+    let base_dir = nuspec_file
+        .parent()
+        .ok_or(format!("Failed to get the directory of {nuspec_file:?}"))?;
+    let mut pkg: Package = from_str(&fs::read_to_string(nuspec_file)?)?;
+    let entries = package_entries(&pkg, base_dir)?;
+    check_verification(&entries, nuspec_file)?;
+
+    let id = pkg.metadata.id.clone();
+    let version = pkg
+        .metadata
+        .version
+        .parse::<NuGetVersion>()
+        .map(|v| v.to_normalized_string())
+        .unwrap_or_else(|_| pkg.metadata.version.clone());
+    pkg.files = None;
+    let manifest = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}",
+        to_string_indent(&pkg, ' ', 2)?
+    );
+    let manifest_path = format!("{id}.nuspec");
+    let properties_path = format!(
+        "package/services/metadata/core-properties/{}.psmdcp",
+        hex(&Sha256::digest(manifest.as_bytes()))[..32].to_lowercase()
+    );
+
+    let is_symbols = pkg
+        .metadata
+        .package_types
+        .iter()
+        .flat_map(|types| types.package_type.iter())
+        .any(|t| t.name == KnownPackageType::SymbolsPackage);
+    let extension = if is_symbols { "snupkg" } else { "nupkg" };
+    let nupkg = out_dir.as_ref().join(format!("{id}.{version}.{extension}"));
+    let mut zip = ZipWriter::new(fs::File::create(&nupkg)?);
+    let entry_options = entry_options(options)?;
+
+    zip.start_file(escape_part(&manifest_path), entry_options)?;
+    zip.write_all(manifest.as_bytes())?;
+    for entry in &entries {
+        zip.start_file(escape_part(entry.path.as_str()), entry_options)?;
+        io::copy(&mut fs::File::open(&entry.source)?, &mut zip)?;
+    }
+    zip.start_file("_rels/.rels", entry_options)?;
+    zip.write_all(relationships(&manifest_path, &properties_path).as_bytes())?;
+    zip.start_file("[Content_Types].xml", entry_options)?;
+    let mut parts: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+    parts.push(&manifest_path);
+    zip.write_all(content_types(&parts).as_bytes())?;
+    zip.start_file(&properties_path, entry_options)?;
+    zip.write_all(core_properties(&pkg).as_bytes())?;
+    zip.finish()?;
+
+    Ok(nupkg)
+}
+
+// Refuses the Chocolatey packages generated before the binaries were built: their
+// `VERIFICATION.txt` file has pending checksums until they are updated.
+fn check_verification(
+    entries: &[PackageEntry],
+    nuspec_file: &path::Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let Some(verification) = entries.iter().find(|e| {
+        e.path
+            .as_str()
+            .eq_ignore_ascii_case("tools/VERIFICATION.txt")
+    }) else {
+        return Ok(());
+    };
+    let text = fs::read_to_string(&verification.source)?;
+    if text.lines().any(|line| line.trim() == "Checksum: pending") {
+        return Err(format!(
+            "The checksums in {:?} are pending, call `update_chocolatey_verification` with {nuspec_file:?} once the binaries are built",
+            verification.source
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn entry_options(options: &PackOptions) -> Result<SimpleFileOptions, Box<dyn error::Error>> {
+    let (year, month, day, seconds) = civil_from_timestamp(options.timestamp.max(ZIP_EPOCH));
+    let modified = DateTime::from_date_and_time(
+        u16::try_from(year)?,
+        month as u8,
+        day as u8,
+        (seconds / 3600) as u8,
+        (seconds / 60 % 60) as u8,
+        (seconds % 60) as u8,
+    )
+    .map_err(|e| format!("Invalid timestamp {}: {e}", options.timestamp))?;
+    Ok(SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .compression_level(Some(options.compression_level))
+        .last_modified_time(modified)
+        .unix_permissions(0o644))
+}
+
+fn relationships(manifest_path: &str, properties_path: &str) -> String {
+    let relationship = |kind: &str, target: &str| {
+        let id = &hex(&Sha256::digest(target.as_bytes()))[..16];
+        format!(r#"<Relationship Type="{kind}" Target="/{target}" Id="R{id}" />"#)
+    };
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{}{}</Relationships>"#,
+        relationship(
+            "http://schemas.microsoft.com/packaging/2010/07/manifest",
+            &escape_part(manifest_path)
+        ),
+        relationship(
+            "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties",
+            properties_path
+        ),
+    )
+}
+
+fn content_types(parts: &[&str]) -> String {
+    let mut extensions = vec![
+        (
+            "rels".to_string(),
+            "application/vnd.openxmlformats-package.relationships+xml",
+        ),
+        (
+            "psmdcp".to_string(),
+            "application/vnd.openxmlformats-package.core-properties+xml",
+        ),
+    ];
+    let mut overrides = Vec::new();
+    for part in parts {
+        let name = part.rsplit('/').next().unwrap_or_default();
+        match name.rsplit_once('.') {
+            Some((_, extension)) if !extension.is_empty() => {
+                let extension = extension.to_lowercase();
+                if !extensions.iter().any(|(e, _)| *e == extension) {
+                    extensions.push((extension, "application/octet"));
+                }
+            }
+            _ => overrides.push(escape_part(part)),
+        }
+    }
+    extensions[2..].sort();
+    overrides.sort();
+
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="utf-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    );
+    for (extension, content_type) in extensions {
+        xml.push_str(&format!(
+            r#"<Default Extension="{}" ContentType="{content_type}" />"#,
+            escape(&extension)
+        ));
+    }
+    for part in overrides {
+        xml.push_str(&format!(
+            r#"<Override PartName="/{}" ContentType="application/octet" />"#,
+            escape(&part)
+        ));
+    }
+    xml.push_str("</Types>");
+    xml
+}
+
+fn core_properties(pkg: &Package) -> String {
+    let metadata = &pkg.metadata;
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="utf-8"?><coreProperties xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns="http://schemas.openxmlformats.org/package/2006/metadata/core-properties">"#,
+    );
+    xml.push_str(&format!(
+        "<dc:creator>{}</dc:creator><dc:description>{}</dc:description><dc:identifier>{}</dc:identifier><version>{}</version>",
+        escape(metadata.authors.join(", ")),
+        escape(&metadata.description),
+        escape(&metadata.id),
+        escape(&metadata.version),
+    ));
+    if let Some(title) = &metadata.title {
+        xml.push_str(&format!("<dc:title>{}</dc:title>", escape(title)));
+    }
+    if let Some(tags) = &metadata.tags {
+        xml.push_str(&format!("<keywords>{}</keywords>", escape(tags.join(" "))));
+    }
+    xml.push_str(&format!(
+        "<lastModifiedBy>nuspec, Version={}</lastModifiedBy></coreProperties>",
+        env!("CARGO_PKG_VERSION")
+    ));
+    xml
+}
+
+// Escapes a package path as an OPC part name, as NuGet does.
+fn escape_part(path: &str) -> String {
+    let mut escaped = String::new();
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/!$&'()*,;=@".contains(&byte) {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    escaped
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+fn split_pattern(pattern: &str) -> Vec<&str> {
+    pattern
+        .split(['/', '\\'])
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect()
+}
+
+// Collects the files under a directory in a sorted order, skipping the hidden ones.
+fn walk(
+    dir: &path::Path,
+    prefix: &mut Vec<String>,
+    found: &mut Vec<Vec<String>>,
+) -> io::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let mut names: Vec<(String, bool)> = fs::read_dir(dir)?
+        .map(|entry| {
+            let entry = entry?;
+            Ok((
+                entry.file_name().to_string_lossy().to_string(),
+                entry.path().is_dir(),
+            ))
+        })
+        .collect::<io::Result<_>>()?;
+    names.sort();
+    for (name, is_dir) in names {
+        if name.starts_with('.') {
+            continue;
+        }
+        prefix.push(name);
+        if is_dir {
+            walk(&dir.join(prefix.last().unwrap()), prefix, found)?;
+        } else {
+            found.push(prefix.clone());
+        }
+        prefix.pop();
+    }
+    Ok(())
+}
+
+// Matches the path components with the pattern ones, where `**` matches any number of
+// directories.
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| match_components(rest, &path[i..])),
+        Some((first, rest)) => {
+            !path.is_empty() && match_name(first, path[0]) && match_components(rest, &path[1..])
+        }
+    }
+}
+
+// Matches a name with the `*` and `?` wildcards, ignoring the case as NuGet does.
+fn match_name(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+    use std::io::Read;
+
+    #[test]
+    fn test_match_components() {
+        assert!(match_components(&["*.dll"], &["a.DLL"]));
+        assert!(!match_components(&["*.dll"], &["sub", "a.dll"]));
+        assert!(match_components(&["**", "*.dll"], &["a.dll"]));
+        assert!(match_components(
+            &["**", "*.dll"],
+            &["sub", "deep", "a.dll"]
+        ));
+        assert!(match_components(&["**"], &["sub", "a.txt"]));
+        assert!(match_components(&["a?c.*"], &["abc.txt"]));
+        assert!(!match_components(&["a?c.*"], &["ac.txt"]));
+    }
+
+    #[test]
+    fn test_pack() {
+        let dir = TempDir::new("pack");
+        fs::create_dir_all(dir.join("bin/sub")).unwrap();
+        fs::write(dir.join("bin/tool.dll"), b"tool").unwrap();
+        fs::write(dir.join("bin/sub/helper.dll"), b"helper").unwrap();
+        fs::write(dir.join("bin/sub/helper.pdb"), b"symbols").unwrap();
+        fs::write(dir.join("bin/.hidden.dll"), b"hidden").unwrap();
+        fs::write(dir.join("read me.md"), b"# Example").unwrap();
+        fs::write(
+            dir.join("example.nuspec"),
+            r#"<package>
+  <metadata>
+    <id>Example</id>
+    <version>1.0</version>
+    <description>An example</description>
+    <authors>Jane Doe</authors>
+  </metadata>
+  <files>
+    <file src="read me.md" target="docs"/>
+    <file src="bin\**" target="lib\net8.0" exclude="**\*.pdb"/>
+  </files>
+</package>"#,
+        )
+        .unwrap();
+
+        let entries = package_entries(
+            &from_str(&fs::read_to_string(dir.join("example.nuspec")).unwrap()).unwrap(),
+            &dir,
+        )
+        .unwrap();
+        let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "docs/read me.md",
+                "lib/net8.0/sub/helper.dll",
+                "lib/net8.0/tool.dll"
+            ]
+        );
+
+        let options = PackOptions {
+            timestamp: 1_700_000_000,
+            ..Default::default()
+        };
+        fs::create_dir_all(dir.join("out")).unwrap();
+        let nupkg = pack(dir.join("example.nuspec"), dir.join("out"), &options).unwrap();
+        assert_eq!(nupkg, dir.join("out/Example.1.0.0.nupkg"));
+        let first = fs::read(&nupkg).unwrap();
+        fs::remove_file(&nupkg).unwrap();
+        let second =
+            fs::read(pack(dir.join("example.nuspec"), dir.join("out"), &options).unwrap()).unwrap();
+        assert_eq!(first, second);
+
+        // The symbols package does not overwrite the main package
+        fs::write(
+            dir.join("example.symbols.nuspec"),
+            r#"<package>
+  <metadata>
+    <id>Example</id>
+    <version>1.0</version>
+    <description>An example</description>
+    <authors>Jane Doe</authors>
+    <packageTypes><packageType name="SymbolsPackage"/></packageTypes>
+  </metadata>
+  <files>
+    <file src="bin\sub\helper.pdb" target="lib\net8.0\sub"/>
+  </files>
+</package>"#,
+        )
+        .unwrap();
+        let snupkg = pack(
+            dir.join("example.symbols.nuspec"),
+            dir.join("out"),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(snupkg, dir.join("out/Example.1.0.0.snupkg"));
+        assert_eq!(
+            fs::read(dir.join("out/Example.1.0.0.nupkg")).unwrap(),
+            first
+        );
+
+        let mut archive = zip::ZipArchive::new(io::Cursor::new(first)).unwrap();
+        let names: Vec<String> = archive.file_names().map(String::from).collect();
+        assert!(names.contains(&"Example.nuspec".to_string()));
+        assert!(names.contains(&"docs/read%20me.md".to_string()));
+        assert!(names.contains(&"[Content_Types].xml".to_string()));
+        let mut manifest = String::new();
+        archive
+            .by_name("Example.nuspec")
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        assert!(!manifest.contains("<files>"));
+        let entry = archive.by_name("lib/net8.0/tool.dll").unwrap();
+        assert_eq!(entry.last_modified().unwrap().year(), 2023);
+    }
+    #[test]
+    fn test_pending_verification() {
+        let dir = TempDir::new("pack-verification");
+        fs::write(dir.join("example.exe"), b"example").unwrap();
+        fs::write(
+            dir.join("VERIFICATION.txt"),
+            "  File: example.exe\n  Checksum Type: sha256\n  Checksum: pending\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("example.nuspec"),
+            r#"<package>
+  <metadata>
+    <id>example</id>
+    <version>1.0</version>
+    <description>An example</description>
+    <authors>Jane Doe</authors>
+  </metadata>
+  <files>
+    <file src="example.exe" target="tools"/>
+    <file src="VERIFICATION.txt" target="tools"/>
+  </files>
+</package>"#,
+        )
+        .unwrap();
+        let options = PackOptions::default();
+        let error = pack(dir.join("example.nuspec"), &dir, &options).unwrap_err();
+        assert!(error.to_string().contains("pending"), "{error}");
+
+        fs::write(
+            dir.join("VERIFICATION.txt"),
+            "  File: example.exe\n  Checksum Type: sha256\n  Checksum: 0123\n",
+        )
+        .unwrap();
+        assert!(pack(dir.join("example.nuspec"), &dir, &options).is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sha2::{Digest, Sha512};
+use std::fmt::Display;
+use std::io::Read;
+use std::str::FromStr;
+use std::{error, fs, path};
+
+/// An error returned by the [`PackageHash`] functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashError {
+    /// A package or a hash file can not be read or written.
+    Io(String),
+    /// The text is not a base64 SHA-512 hash.
+    Invalid(String),
+    /// The package does not have the expected hash, both are in base64.
+    Mismatch { expected: String, actual: String },
+}
+
+impl Display for HashError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashError::Io(message) => write!(f, "{message}"),
+            HashError::Invalid(value) => write!(f, "invalid SHA-512 hash `{value}`"),
+            HashError::Mismatch { expected, actual } => {
+                write!(f, "the package hash is {actual}, expected {expected}")
+            }
+        }
+    }
+}
+
+impl error::Error for HashError {}
+
+/// The content hash of a package as NuGet records it in the `.nupkg.sha512` files, the
+/// `.nupkg.metadata` files and the `packages.lock.json` files: the SHA-512 digest of
+/// the `.nupkg` file, encoded in base64.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PackageHash([u8; 64]);
+
+impl PackageHash {
+    /// The extension appended to the name of a package for its hash file.
+    pub const SIDECAR_EXTENSION: &str = "sha512";
+
+    /// Returns the hash of the content of a package.
+    pub fn from_bytes(content: &[u8]) -> Self {
+        PackageHash(Sha512::digest(content).into())
+    }
+
+    /// Computes the hash of a `.nupkg` file without loading it into the memory.
+    pub fn compute<P: AsRef<path::Path>>(nupkg: P) -> Result<Self, HashError> {
+        let nupkg = nupkg.as_ref();
+        let io_error = |e| HashError::Io(format!("Failed to read the package {nupkg:?}: {e}"));
+        let mut file = fs::File::open(nupkg).map_err(io_error)?;
+        let mut hasher = Sha512::new();
+        let mut buffer = [0; 64 * 1024];
+        loop {
+            match file.read(&mut buffer).map_err(io_error)? {
+                0 => break,
+                n => hasher.update(&buffer[..n]),
+            }
+        }
+        Ok(PackageHash(hasher.finalize().into()))
+    }
+
+    /// Returns the raw digest.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the hash encoded in base64, as NuGet writes it.
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.0)
+    }
+
+    /// Returns the path of the hash file of a package, `{nupkg}.sha512`.
+    pub fn sidecar_path<P: AsRef<path::Path>>(nupkg: P) -> path::PathBuf {
+        let mut path = nupkg.as_ref().as_os_str().to_owned();
+        path.push(".");
+        path.push(Self::SIDECAR_EXTENSION);
+        path::PathBuf::from(path)
+    }
+
+    /// Writes the hash file of a package and returns its path.
+    pub fn write_sidecar<P: AsRef<path::Path>>(
+        &self,
+        nupkg: P,
+    ) -> Result<path::PathBuf, HashError> {
+        let sidecar = Self::sidecar_path(nupkg);
+        fs::write(&sidecar, self.to_base64())
+            .map_err(|e| HashError::Io(format!("Failed to write {sidecar:?}: {e}")))?;
+        Ok(sidecar)
+    }
+
+    /// Reads the hash file of a package.
+    pub fn read_sidecar<P: AsRef<path::Path>>(nupkg: P) -> Result<Self, HashError> {
+        let sidecar = Self::sidecar_path(nupkg);
+        fs::read_to_string(&sidecar)
+            .map_err(|e| HashError::Io(format!("Failed to read {sidecar:?}: {e}")))?
+            .parse()
+    }
+
+    /// Checks that a `.nupkg` file has the expected hash.
+    pub fn verify<P: AsRef<path::Path>>(nupkg: P, expected: &PackageHash) -> Result<(), HashError> {
+        let actual = PackageHash::compute(nupkg)?;
+        if actual != *expected {
+            return Err(HashError::Mismatch {
+                expected: expected.to_base64(),
+                actual: actual.to_base64(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Display for PackageHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_base64())
+    }
+}
+
+impl FromStr for PackageHash {
+    type Err = HashError;
+
+    /// Parses a base64 hash, the surrounding whitespace is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || HashError::Invalid(s.to_string());
+        let bytes = STANDARD.decode(s.trim()).map_err(|_| invalid())?;
+        Ok(PackageHash(bytes.try_into().map_err(|_| invalid())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+
+    #[test]
+    fn test_package_hash() {
+        assert_eq!(
+            PackageHash::from_bytes(b"").to_string(),
+            "z4PhNX7vuL3xVChQ1m2AB9Yg5AULVxXcg/SpIdNs6c5H0NE8XYXysP+DGNKHfuwvY7kxvUdBeoGlODJ6+SfaPg=="
+        );
+        let dir = TempDir::new("hash");
+        let nupkg = dir.join("example.1.0.0.nupkg");
+        fs::write(&nupkg, b"PK package").unwrap();
+
+        let hash = PackageHash::compute(&nupkg).unwrap();
+        assert_eq!(hash, PackageHash::from_bytes(b"PK package"));
+        assert_eq!(
+            hash.to_string(),
+            "CxpQoWatTGVKqWy0LQAyLtXJbCutgrUiHEno1oHC0X61RX5nw3IPb3J8EAW4hNQ0L0vu3BRAIne1acrU+3mhpA=="
+        );
+        let sidecar = hash.write_sidecar(&nupkg).unwrap();
+        assert_eq!(sidecar, dir.join("example.1.0.0.nupkg.sha512"));
+        assert_eq!(PackageHash::read_sidecar(&nupkg), Ok(hash.clone()));
+        assert_eq!(PackageHash::verify(&nupkg, &hash), Ok(()));
+
+        fs::write(&nupkg, b"PK changed").unwrap();
+        let result = PackageHash::verify(&nupkg, &hash);
+        assert!(matches!(result, Err(HashError::Mismatch { .. })));
+        assert!(matches!(
+            "bm90IGEgaGFzaA==".parse::<PackageHash>(),
+            Err(HashError::Invalid(_))
+        ));
+    }
+}
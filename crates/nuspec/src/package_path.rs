@@ -0,0 +1,300 @@
+use crate::{File, Package};
+use serde::Deserialize;
+use std::fmt::Display;
+use std::path;
+
+/// The separator of the package paths written to a nuspec file.
+///
+/// NuGet accepts both separators on every host, the slash is the default because it is the one
+/// used inside the packages and by the tools on Linux and macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathSeparator {
+    #[default]
+    Slash,
+    Backslash,
+}
+
+impl PathSeparator {
+    pub fn as_char(&self) -> char {
+        match self {
+            PathSeparator::Slash => '/',
+            PathSeparator::Backslash => '\\',
+        }
+    }
+}
+
+/// A path within a package, such as `lib/net8.0/example.dll`.
+///
+/// The path is normalized on creation: the backslashes are replaced with slashes, and the
+/// empty and `.` components are removed, so the root of the package is an empty path.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PackagePath(String);
+
+impl PackagePath {
+    /// The separator of the package paths.
+    pub const SEPARATOR: char = '/';
+
+    pub fn new(path: &str) -> Self {
+        let components: Vec<&str> = path
+            .split(['/', '\\'])
+            .filter(|c| !c.is_empty() && *c != ".")
+            .collect();
+        PackagePath(components.join("/"))
+    }
+
+    /// Returns true if the path is the root of the package.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        self.0.split(Self::SEPARATOR).filter(|c| !c.is_empty())
+    }
+
+    /// Returns the last component of the path.
+    pub fn file_name(&self) -> Option<&str> {
+        self.components().last()
+    }
+
+    /// Returns the extension of the last component, without the dot.
+    pub fn extension(&self) -> Option<&str> {
+        self.file_name()
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, extension)| extension)
+            .filter(|extension| !extension.is_empty())
+    }
+
+    /// Returns the path of the parent directory, `None` for the root.
+    pub fn parent(&self) -> Option<PackagePath> {
+        if self.is_root() {
+            return None;
+        }
+        Some(PackagePath(
+            self.0
+                .rsplit_once(Self::SEPARATOR)
+                .map(|(parent, _)| parent.to_string())
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// Appends a relative path, which may use either separator.
+    pub fn join(&self, path: &str) -> PackagePath {
+        PackagePath::new(&format!("{}/{path}", self.0))
+    }
+
+    /// Returns true if the path points outside of the package with a `..` component.
+    pub fn is_escaping(&self) -> bool {
+        self.components().any(|c| c == "..")
+    }
+
+    /// Compares the paths ignoring the case, as NuGet does.
+    pub fn eq_ignore_case(&self, other: &PackagePath) -> bool {
+        self.0.to_lowercase() == other.0.to_lowercase()
+    }
+
+    /// Returns the path with the given separator.
+    pub fn to_string_with(&self, separator: PathSeparator) -> String {
+        self.0
+            .replace(Self::SEPARATOR, &separator.as_char().to_string())
+    }
+}
+
+impl Display for PackagePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<&str> for PackagePath {
+    fn from(value: &str) -> Self {
+        PackagePath::new(value)
+    }
+}
+
+impl AsRef<str> for PackagePath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl File {
+    /// Creates a file with a source path of the host and a target within the package.
+    ///
+    /// The source is written with slashes, which NuGet accepts on every host, so the nuspec file
+    /// is the same whatever the host it is generated on.
+    pub fn new<P: AsRef<path::Path>>(src: P, target: &PackagePath) -> Self {
+        File {
+            src: portable_source(src.as_ref()),
+            target: Some(target.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the source path converted to the separators of the host.
+    pub fn source_path(&self) -> path::PathBuf {
+        path::PathBuf::from(self.src.replace(['/', '\\'], path::MAIN_SEPARATOR_STR))
+    }
+
+    /// Returns the target of the file, the root of the package if it is not set.
+    pub fn target_path(&self) -> PackagePath {
+        PackagePath::new(self.target.as_deref().unwrap_or_default())
+    }
+
+    /// Returns true if the source has the `*` or `?` wildcards.
+    pub fn has_wildcards(&self) -> bool {
+        self.src.contains(['*', '?'])
+    }
+
+    /// Returns the path of the file within the package, `None` if the source has wildcards.
+    ///
+    /// As NuGet does, the target is the path of the file if it has the same extension as the
+    /// source, including none for both, otherwise it is the directory the file is placed in.
+    /// A target ending with a separator is always a directory.
+    pub fn package_path(&self) -> Option<PackagePath> {
+        if self.has_wildcards() {
+            return None;
+        }
+        let source = PackagePath::new(&self.src);
+        let name = source.file_name()?;
+        let target = self.target_path();
+        let is_dir = target.is_root()
+            || self
+                .target
+                .as_deref()
+                .is_some_and(|t| t.ends_with(['/', '\\']));
+        let same_extension = match (target.extension(), source.extension()) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            (None, None) => true,
+            _ => false,
+        };
+        if same_extension && !is_dir {
+            Some(target)
+        } else {
+            Some(target.join(name))
+        }
+    }
+}
+
+impl Package {
+    /// Applies the path separator policy to the files: the targets are written with the given
+    /// separator and the sources with slashes.
+    pub fn normalize_paths(&mut self, separator: PathSeparator) {
+        for file in self.files.iter_mut().flat_map(|f| f.file.iter_mut()) {
+            file.src = file.src.replace('\\', "/");
+            if let Some(target) = file.target.as_mut() {
+                // Keeps the trailing separator, which marks a directory
+                let is_dir = target.ends_with(['/', '\\']);
+                *target = PackagePath::new(target).to_string_with(separator);
+                if is_dir && !target.is_empty() {
+                    target.push(separator.as_char());
+                }
+            }
+        }
+    }
+}
+
+fn portable_source(src: &path::Path) -> String {
+    src.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_path() {
+        let path = PackagePath::new("\\lib\\net8.0//./example.dll");
+        assert_eq!(path.as_str(), "lib/net8.0/example.dll");
+        assert_eq!(path.file_name(), Some("example.dll"));
+        assert_eq!(path.extension(), Some("dll"));
+        assert_eq!(path.parent(), Some(PackagePath::new("lib/net8.0")));
+        assert_eq!(
+            path.to_string_with(PathSeparator::Backslash),
+            "lib\\net8.0\\example.dll"
+        );
+        assert!(PackagePath::new("").is_root());
+        assert_eq!(PackagePath::new("").join("README.md").as_str(), "README.md");
+        assert!(PackagePath::new("lib/../../etc").is_escaping());
+        assert!(path.eq_ignore_case(&PackagePath::new("LIB/net8.0/Example.dll")));
+    }
+
+    #[test]
+    fn test_file_paths() {
+        let file = File::new(
+            path::Path::new("target").join("release").join("tool.exe"),
+            &PackagePath::new("tools"),
+        );
+        assert_eq!(file.src, "target/release/tool.exe");
+        assert_eq!(file.target.as_deref(), Some("tools"));
+        assert_eq!(
+            file.package_path(),
+            Some(PackagePath::new("tools/tool.exe"))
+        );
+        assert_eq!(
+            file.source_path(),
+            path::Path::new("target").join("release").join("tool.exe")
+        );
+
+        let renamed = File {
+            src: "docs\\guide.md".to_string(),
+            target: Some("README.md".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(renamed.package_path(), Some(PackagePath::new("README.md")));
+        let wildcard = File {
+            src: "bin/**".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(wildcard.package_path(), None);
+
+        // An extensionless binary is placed at its target, or in it with a trailing separator
+        let binary = File::new(
+            path::Path::new("target").join("release").join("mytool"),
+            &PackagePath::new("tools/net10.0/linux-x64/mytool"),
+        );
+        assert_eq!(
+            binary.package_path(),
+            Some(PackagePath::new("tools/net10.0/linux-x64/mytool"))
+        );
+        let binary = File {
+            src: "target/release/mytool".to_string(),
+            target: Some("tools\\".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            binary.package_path(),
+            Some(PackagePath::new("tools/mytool"))
+        );
+        let binary = File {
+            src: "target/release/mytool".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(binary.package_path(), Some(PackagePath::new("mytool")));
+    }
+
+    #[test]
+    fn test_normalize_paths() {
+        let mut pkg = Package {
+            files: Some(crate::Files {
+                file: vec![File {
+                    src: "..\\target\\tool.exe".to_string(),
+                    target: Some("tools/net8.0/".to_string()),
+                    ..Default::default()
+                }],
+            }),
+            ..Default::default()
+        };
+        pkg.normalize_paths(PathSeparator::Backslash);
+        let file = &pkg.files.as_ref().unwrap().file[0];
+        assert_eq!(file.src, "../target/tool.exe");
+        assert_eq!(file.target.as_deref(), Some("tools\\net8.0\\"));
+        pkg.normalize_paths(PathSeparator::Slash);
+        let file = &pkg.files.as_ref().unwrap().file[0];
+        assert_eq!(file.target.as_deref(), Some("tools/net8.0/"));
+    }
+}
@@ -0,0 +1,516 @@
+use crate::Package;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use serde::Deserialize;
+use serde_path_to_error::Segment;
+use std::error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// An error returned when a nuspec document can not be parsed.
+///
+/// Unlike the bare serde message produced by `quick_xml`, it carries the position of the offending
+/// element or attribute, the element path in the document and, when possible, a hint how to fix
+/// the mistake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The error message reported by the XML parser or the deserializer.
+    pub message: String,
+    /// The 1-based line of the offending element or attribute.
+    pub line: usize,
+    /// The 1-based column of the offending element or attribute.
+    pub column: usize,
+    /// The path of the offending element, such as `package/metadata/dependencies/group[2]/dependency`.
+    /// Attributes are appended as the last segment with the `@` prefix.
+    pub path: String,
+    /// A hint how to fix a common mistake, such as a misspelled element name.
+    pub suggestion: Option<String>,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {} (`{}`)",
+            self.message, self.line, self.column, self.path
+        )?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "; {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Deserializes a value, usually a [`Package`], from a nuspec document.
+///
+/// On failure the returned [`ParseError`] points to the line, column and element path of
+/// the mistake.
+pub fn from_str<'de, T>(xml: &'de str) -> Result<T, ParseError>
+where
+    T: Deserialize<'de>,
+{
+    let mut deserializer = quick_xml::de::Deserializer::from_str(xml);
+    serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+        let document = match Document::scan(xml) {
+            Ok(document) => document,
+            Err(err) => return err,
+        };
+        let message = err.inner().to_string();
+        let (node, attribute) = document.locate(err.path(), &message);
+        let offset = match attribute {
+            Some(attribute) => document.attribute_offset(xml, node, attribute),
+            None => document.nodes[node].start,
+        };
+        let (line, column) = line_column(xml, offset);
+        let mut path = document.path(node);
+        if let Some(attribute) = attribute {
+            path.push_str("/@");
+            path.push_str(attribute);
+        }
+        ParseError {
+            suggestion: suggest(&message),
+            message,
+            line,
+            column,
+            path,
+        }
+    })
+}
+
+impl FromStr for Package {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_str(s)
+    }
+}
+
+/// A lightweight element tree of the document with byte offsets, used to map a deserializer
+/// error back to the source.
+struct Document {
+    nodes: Vec<Node>,
+}
+
+struct Node {
+    name: String,
+    start: usize,
+    end: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+impl Document {
+    fn scan(xml: &str) -> Result<Self, ParseError> {
+        let mut reader = Reader::from_str(xml);
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut stack: Vec<usize> = Vec::new();
+        loop {
+            let start = reader.buffer_position() as usize;
+            let event = match reader.read_event() {
+                Ok(event) => event,
+                Err(err) => {
+                    let document = Document { nodes };
+                    let (line, column) = line_column(xml, reader.error_position() as usize);
+                    return Err(ParseError {
+                        message: err.to_string(),
+                        line,
+                        column,
+                        path: stack
+                            .last()
+                            .map(|&node| document.path(node))
+                            .unwrap_or_default(),
+                        suggestion: None,
+                    });
+                }
+            };
+            let end = reader.buffer_position() as usize;
+            let is_empty = matches!(event, Event::Empty(_));
+            match event {
+                Event::Start(e) | Event::Empty(e) => {
+                    let index = nodes.len();
+                    let parent = stack.last().copied();
+                    nodes.push(Node {
+                        name: String::from_utf8_lossy(e.name().as_ref()).to_string(),
+                        start,
+                        end,
+                        parent,
+                        children: Vec::new(),
+                    });
+                    if let Some(parent) = parent {
+                        nodes[parent].children.push(index);
+                    }
+                    if !is_empty {
+                        stack.push(index);
+                    }
+                }
+                Event::End(_) => {
+                    if let Some(index) = stack.pop() {
+                        nodes[index].end = end;
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        if nodes.is_empty() {
+            return Err(ParseError {
+                message: "the document has no root element".to_string(),
+                line: 1,
+                column: 1,
+                path: String::new(),
+                suggestion: None,
+            });
+        }
+        Ok(Document { nodes })
+    }
+
+    /// Walks the deserializer path from the root element and returns the deepest matching element
+    /// and, if the path ends on an attribute, the attribute name.
+    fn locate<'a>(
+        &self,
+        path: &'a serde_path_to_error::Path,
+        message: &'a str,
+    ) -> (usize, Option<&'a str>) {
+        let mut node = 0;
+        let mut attribute = None;
+        let mut segments = path.iter().peekable();
+        while let Some(segment) = segments.next() {
+            let Segment::Map { key } = segment else {
+                continue;
+            };
+            if let Some(name) = key.strip_prefix('@') {
+                attribute = Some(name);
+                break;
+            }
+            if key.starts_with('$') {
+                continue;
+            }
+            let index = match segments.peek() {
+                Some(Segment::Seq { index }) => {
+                    segments.next();
+                    *index
+                }
+                _ => 0,
+            };
+            match self.children(node, key).nth(index) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        // A duplicate field is reported on the parent, so point at the repeated element instead
+        if attribute.is_none()
+            && let Some(name) = quoted(message, "duplicate field `")
+        {
+            if let Some(name) = name.strip_prefix('@') {
+                attribute = Some(name);
+            } else if let Some(child) = self.children(node, name).last() {
+                node = child;
+            }
+        }
+        (node, attribute)
+    }
+
+    fn children<'a>(&'a self, node: usize, name: &'a str) -> impl Iterator<Item = usize> + 'a {
+        self.nodes[node]
+            .children
+            .iter()
+            .copied()
+            .filter(move |&child| self.nodes[child].name == name)
+    }
+
+    /// Returns the element path, such as `package/metadata/dependencies/group[2]/dependency`.
+    /// An index is added only if the element has siblings with the same name.
+    fn path(&self, node: usize) -> String {
+        let mut segments = Vec::new();
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let current_node = &self.nodes[index];
+            let mut segment = current_node.name.clone();
+            if let Some(parent) = current_node.parent {
+                let siblings: Vec<usize> = self.children(parent, &current_node.name).collect();
+                if siblings.len() > 1 {
+                    let position = siblings.iter().position(|&s| s == index).unwrap_or(0);
+                    segment.push_str(&format!("[{}]", position + 1));
+                }
+            }
+            segments.push(segment);
+            current = current_node.parent;
+        }
+        segments.reverse();
+        segments.join("/")
+    }
+
+    /// Returns the offset of the attribute name inside the start tag of the element,
+    /// or the offset of the element itself if the attribute is not present.
+    fn attribute_offset(&self, xml: &str, node: usize, attribute: &str) -> usize {
+        let node = &self.nodes[node];
+        let tag_end = xml[node.start..node.end]
+            .find('>')
+            .map_or(node.end, |i| node.start + i);
+        let tag = &xml[node.start..tag_end];
+        let bytes = tag.as_bytes();
+        for (i, _) in tag.match_indices(attribute) {
+            let before = i.checked_sub(1).map(|b| bytes[b]);
+            let after = tag[i + attribute.len()..].trim_start().as_bytes().first();
+            if before.is_some_and(|b| b.is_ascii_whitespace()) && after == Some(&b'=') {
+                return node.start + i;
+            }
+        }
+        node.start
+    }
+}
+
+fn line_column(xml: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(xml.len());
+    let before = &xml[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Returns the text between `prefix` and the next backtick.
+pub(crate) fn quoted<'a>(message: &'a str, prefix: &str) -> Option<&'a str> {
+    let start = message.find(prefix)? + prefix.len();
+    let end = message[start..].find('`')?;
+    Some(&message[start..start + end])
+}
+
+/// Returns all names listed in the `expected ...` part of a serde message.
+pub(crate) fn expected(message: &str) -> Vec<&str> {
+    let Some(start) = message.find("expected ") else {
+        return Vec::new();
+    };
+    message[start..]
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .filter(|name| !name.starts_with('$'))
+        .collect()
+}
+
+/// Builds a hint for the common mistakes in hand-written nuspec files.
+fn suggest(message: &str) -> Option<String> {
+    if let Some(name) = quoted(message, "missing field `") {
+        return Some(match name.strip_prefix('@') {
+            Some(attribute) => format!("add the `{attribute}` attribute"),
+            None => format!("add the `<{name}>` element"),
+        });
+    }
+    let name = quoted(message, "unknown field `").or(quoted(message, "unknown variant `"))?;
+    let candidates = expected(message);
+    let is_attribute = name.starts_with('@');
+    let bare = name.trim_start_matches('@');
+
+    // An attribute written as an element or vice versa
+    if !is_attribute && candidates.contains(&format!("@{bare}").as_str()) {
+        return Some(format!(
+            "`{bare}` is an attribute, write it as `{bare}=\"...\"` on the parent element"
+        ));
+    }
+    if is_attribute && candidates.contains(&bare) {
+        return Some(format!(
+            "`{bare}` is a child element, write it as `<{bare}>...</{bare}>`"
+        ));
+    }
+
+    // Prefer the names used in the xml file over the snake_case aliases
+    let mut candidates: Vec<&str> = candidates
+        .into_iter()
+        .filter(|c| c.starts_with('@') == is_attribute || c.contains('_'))
+        .collect();
+    candidates.sort_by_key(|c| c.contains('_'));
+
+    let candidate = closest_name(bare, &candidates)?;
+    let candidate_name = candidate.trim_start_matches('@');
+    if normalize(candidate) == normalize(bare) && !candidate.contains('_') {
+        return Some(format!(
+            "did you mean `{candidate_name}`? names are case-sensitive and use camelCase"
+        ));
+    }
+    Some(format!("did you mean `{candidate_name}`?"))
+}
+
+/// Returns the candidate closest to a misspelled name: the first one that differs only in case or
+/// separators, or else the one within a small edit distance.
+pub(crate) fn closest_name<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let normalized = normalize(name);
+    if let Some(candidate) = candidates.iter().find(|c| normalize(c) == normalized) {
+        return Some(candidate);
+    }
+    let threshold = (normalized.len() / 4).max(1);
+    candidates
+        .iter()
+        .map(|c| (edit_distance(&normalized, &normalize(c)), *c))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '_' | '-' | '@'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Returns the edit distance between two names, counting a transposition of two adjacent
+/// characters as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_document() {
+        let xml = r#"<package><metadata><id>example-package</id><version>1.0.0</version></metadata></package>"#;
+        let pkg: Package = xml.parse().unwrap();
+        assert_eq!(pkg.metadata.id, "example-package");
+    }
+
+    #[test]
+    fn test_misspelled_element() {
+        let xml = "<package>\n  <metadata>\n    <id>x</id>\n    <desciption>text</desciption>\n  </metadata>\n</package>";
+        let err = from_str::<Package>(xml).unwrap_err();
+        assert_eq!(err.line, 4);
+        assert_eq!(err.column, 5);
+        assert_eq!(err.path, "package/metadata/desciption");
+        assert_eq!(
+            err.suggestion.as_deref(),
+            Some("did you mean `description`?")
+        );
+    }
+
+    #[test]
+    fn test_wrong_case_element() {
+        let xml =
+            "<package><metadata><ProjectUrl>https://example.com</ProjectUrl></metadata></package>";
+        let err = from_str::<Package>(xml).unwrap_err();
+        assert_eq!(err.path, "package/metadata/ProjectUrl");
+        assert_eq!(
+            err.suggestion.as_deref(),
+            Some("did you mean `projectUrl`? names are case-sensitive and use camelCase")
+        );
+    }
+
+    #[test]
+    fn test_attribute_written_as_element() {
+        let xml =
+            "<package><metadata><minClientVersion>5.0</minClientVersion></metadata></package>";
+        let err = from_str::<Package>(xml).unwrap_err();
+        assert_eq!(err.path, "package/metadata/minClientVersion");
+        assert_eq!(
+            err.suggestion.as_deref(),
+            Some(
+                "`minClientVersion` is an attribute, write it as `minClientVersion=\"...\"` on the parent element"
+            )
+        );
+    }
+
+    #[test]
+    fn test_missing_attribute_in_group() {
+        let xml = r#"<package>
+  <metadata>
+    <dependencies>
+      <group targetFramework="net5.0">
+        <dependency id="a" version="1.0"/>
+      </group>
+      <group targetFramework="net8.0">
+        <dependency id="b" version="1.0"/>
+        <dependency version="2.0"/>
+      </group>
+    </dependencies>
+  </metadata>
+</package>"#;
+        let err = from_str::<Package>(xml).unwrap_err();
+        assert_eq!(err.message, "missing field `@id`");
+        assert_eq!(
+            err.path,
+            "package/metadata/dependencies/group[2]/dependency[2]"
+        );
+        assert_eq!((err.line, err.column), (9, 9));
+        assert_eq!(err.suggestion.as_deref(), Some("add the `id` attribute"));
+    }
+
+    #[test]
+    fn test_misspelled_attribute() {
+        let xml = r#"<package><metadata><dependencies><dependency id="a" verison="1.0"/></dependencies></metadata></package>"#;
+        let err = from_str::<Package>(xml).unwrap_err();
+        assert_eq!(
+            err.path,
+            "package/metadata/dependencies/dependency/@verison"
+        );
+        assert_eq!(err.column, 53);
+        assert_eq!(err.suggestion.as_deref(), Some("did you mean `version`?"));
+    }
+
+    #[test]
+    fn test_misspelled_variant() {
+        let xml =
+            r#"<package><metadata><license type="expresion">MIT</license></metadata></package>"#;
+        let err = from_str::<Package>(xml).unwrap_err();
+        assert_eq!(err.path, "package/metadata/license/@type");
+        assert_eq!(
+            err.suggestion.as_deref(),
+            Some("did you mean `expression`?")
+        );
+    }
+
+    #[test]
+    fn test_duplicate_element() {
+        let xml =
+            "<package><metadata><owners>a</owners>\n<authors>b</authors></metadata></package>";
+        let err = from_str::<Package>(xml).unwrap_err();
+        assert_eq!(err.path, "package/metadata/authors");
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn test_invalid_xml() {
+        let xml = "<package>\n  <metadata>\n    <id>x</version>\n  </metadata>\n</package>";
+        let err = from_str::<Package>(xml).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.path, "package/metadata/id");
+        assert!(err.suggestion.is_none());
+    }
+
+    #[test]
+    fn test_display() {
+        let err = ParseError {
+            message: "unknown field `foo`".to_string(),
+            line: 2,
+            column: 3,
+            path: "package/foo".to_string(),
+            suggestion: Some("did you mean `files`?".to_string()),
+        };
+        assert_eq!(
+            err.to_string(),
+            "unknown field `foo` at line 2, column 3 (`package/foo`); did you mean `files`?"
+        );
+    }
+}
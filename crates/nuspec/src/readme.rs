@@ -0,0 +1,442 @@
+use std::fmt::Display;
+use std::ops::Range;
+
+/// The maximum size of a package readme file accepted by NuGet, 1 MB.
+pub const README_SIZE_LIMIT: usize = 1024 * 1024;
+
+/// A link or an image found in a Markdown readme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadmeLink {
+    /// The 1-based line number of the link.
+    pub line: usize,
+    /// The link destination as written in the file.
+    pub destination: String,
+    /// Whether the link is an image, written as `![alt](destination)` or pointing to an image file.
+    pub is_image: bool,
+    /// The byte range of the destination in the readme content.
+    pub range: Range<usize>,
+}
+
+impl ReadmeLink {
+    /// Returns true if the destination is a path relative to the readme file or to the repository
+    /// root, rather than a URL or an anchor within the readme.
+    pub fn is_relative(&self) -> bool {
+        let scheme = self.destination.split_once(':').is_some_and(|(scheme, _)| {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+')
+        });
+        !(scheme
+            || self.destination.is_empty()
+            || self.destination.starts_with('#')
+            || self.destination.starts_with("//"))
+    }
+}
+
+/// A problem of a package readme, NuGet refuses the packages with the errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadmeIssue {
+    /// The readme is not a Markdown file.
+    NotMarkdown { file_name: String },
+    /// The readme is larger than [`README_SIZE_LIMIT`].
+    TooLarge { size: usize },
+    /// A relative image link to a file that is not packaged.
+    ImageNotPackaged { line: usize, destination: String },
+    /// A relative link, which does not resolve when the readme is displayed by nuget.org.
+    RelativeLink { line: usize, destination: String },
+    /// An HTML tag, which nuget.org strips when rendering the readme.
+    HtmlTag { line: usize, tag: String },
+}
+
+impl ReadmeIssue {
+    /// Returns true if NuGet refuses a package with the issue.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            ReadmeIssue::NotMarkdown { .. } | ReadmeIssue::TooLarge { .. }
+        )
+    }
+}
+
+impl Display for ReadmeIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadmeIssue::NotMarkdown { file_name } => {
+                write!(f, "the readme `{file_name}` is not a Markdown (.md) file")
+            }
+            ReadmeIssue::TooLarge { size } => write!(
+                f,
+                "the readme is {size} bytes, larger than the {README_SIZE_LIMIT} bytes limit"
+            ),
+            ReadmeIssue::ImageNotPackaged { line, destination } => write!(
+                f,
+                "the image `{destination}` at line {line} of the readme is not packaged"
+            ),
+            ReadmeIssue::RelativeLink { line, destination } => write!(
+                f,
+                "the relative link `{destination}` at line {line} of the readme does not resolve on nuget.org"
+            ),
+            ReadmeIssue::HtmlTag { line, tag } => write!(
+                f,
+                "the `<{tag}>` tag at line {line} of the readme is stripped by nuget.org"
+            ),
+        }
+    }
+}
+
+/// Validates a readme against the NuGet rules.
+///
+/// The `packaged` list contains the paths of all files within the package, the relative images
+/// are resolved against the `readme` path within the package.
+pub fn check_readme(readme: &str, content: &str, packaged: &[String]) -> Vec<ReadmeIssue> {
+    let mut issues = Vec::new();
+    if !readme.to_lowercase().ends_with(".md") {
+        issues.push(ReadmeIssue::NotMarkdown {
+            file_name: readme.to_string(),
+        });
+    }
+    if content.len() > README_SIZE_LIMIT {
+        issues.push(ReadmeIssue::TooLarge {
+            size: content.len(),
+        });
+    }
+    let readme_dir = match readme.replace('\\', "/").rsplit_once('/') {
+        Some((dir, _)) => format!("{dir}/"),
+        None => String::new(),
+    };
+    for link in readme_links(content)
+        .into_iter()
+        .filter(ReadmeLink::is_relative)
+    {
+        let destination = link.destination.clone();
+        if !link.is_image {
+            issues.push(ReadmeIssue::RelativeLink {
+                line: link.line,
+                destination,
+            });
+            continue;
+        }
+        let path = match destination.strip_prefix('/') {
+            Some(path) => path.to_string(),
+            None => normalize_path(&format!("{readme_dir}{destination}")),
+        };
+        if !packaged
+            .iter()
+            .any(|p| p.replace('\\', "/").eq_ignore_ascii_case(&path))
+        {
+            issues.push(ReadmeIssue::ImageNotPackaged {
+                line: link.line,
+                destination,
+            });
+        }
+    }
+    for (line, tag) in readme_html_tags(content) {
+        issues.push(ReadmeIssue::HtmlTag { line, tag });
+    }
+    issues
+}
+
+/// Rewrites the relative links of a readme to absolute URLs.
+///
+/// The links are joined with `link_base` and the images with `image_base`, the URLs of the
+/// repository root at a commit, such as `https://github.com/owner/repo/blob/<commit>/` and
+/// `https://github.com/owner/repo/raw/<commit>/`. The relative paths are resolved against
+/// `readme_dir`, the directory of the readme within the repository, such as `docs/`,
+/// and the paths starting with `/` against the repository root.
+pub fn rewrite_readme_links(
+    content: &str,
+    link_base: &str,
+    image_base: &str,
+    readme_dir: &str,
+) -> String {
+    let mut rewritten = String::with_capacity(content.len());
+    let mut last = 0;
+    for link in readme_links(content)
+        .into_iter()
+        .filter(ReadmeLink::is_relative)
+    {
+        rewritten.push_str(&content[last..link.range.start]);
+        let base = if link.is_image { image_base } else { link_base };
+        let path = match link.destination.strip_prefix('/') {
+            Some(path) => normalize_path(path),
+            None => normalize_path(&format!("{readme_dir}{}", link.destination)),
+        };
+        rewritten.push_str(base);
+        rewritten.push_str(&path);
+        last = link.range.end;
+    }
+    rewritten.push_str(&content[last..]);
+    rewritten
+}
+
+/// Returns all links and images of a Markdown readme, except the ones in code.
+pub fn readme_links(content: &str) -> Vec<ReadmeLink> {
+    let mut links = Vec::new();
+    for (number, offset, line) in text_lines(content) {
+        // A link reference definition: `[label]: destination`
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[')
+            && let Some(end) = trimmed.find("]:")
+        {
+            let start = line.len() - trimmed.len() + end + 2;
+            let destination = line[start..].split_whitespace().next().unwrap_or_default();
+            if !destination.is_empty() {
+                let start = start + line[start..].find(destination).unwrap_or_default();
+                links.push(link(number, offset + start, destination, false));
+            }
+            continue;
+        }
+        let bytes = line.as_bytes();
+        let mut i = 0;
+        while let Some(found) = line[i..].find("](") {
+            let close = i + found;
+            let start = close + 2;
+            let destination: &str = line[start..]
+                .split(|c: char| c.is_whitespace() || c == ')')
+                .next()
+                .unwrap_or_default();
+            let destination = destination.trim_start_matches('<').trim_end_matches('>');
+            let start = start + line[start..].find(destination).unwrap_or_default();
+            // Find the opening bracket to tell the images from the links
+            let mut depth = 0;
+            let mut is_image = false;
+            for j in (0..close).rev() {
+                match bytes[j] {
+                    b']' => depth += 1,
+                    b'[' if depth == 0 => {
+                        is_image = j > 0 && bytes[j - 1] == b'!';
+                        break;
+                    }
+                    b'[' => depth -= 1,
+                    _ => {}
+                }
+            }
+            if !destination.is_empty() {
+                links.push(link(number, offset + start, destination, is_image));
+            }
+            // `start` is past the `](` and on a character boundary, so the search moves forward
+            i = start + destination.len();
+            if i >= line.len() {
+                break;
+            }
+        }
+    }
+    links
+}
+
+fn link(line: usize, start: usize, destination: &str, is_image: bool) -> ReadmeLink {
+    let lower = destination.to_lowercase();
+    let lower = lower.split(['?', '#']).next().unwrap_or_default();
+    let is_image = is_image
+        || [".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp"]
+            .iter()
+            .any(|extension| lower.ends_with(extension));
+    ReadmeLink {
+        line,
+        destination: destination.to_string(),
+        is_image,
+        range: start..start + destination.len(),
+    }
+}
+
+/// Returns the names of the HTML tags of a Markdown readme with their line numbers,
+/// except the ones in code, comments and autolinks such as `<https://example.com>`.
+pub fn readme_html_tags(content: &str) -> Vec<(usize, String)> {
+    let mut tags = Vec::new();
+    for (number, _, line) in text_lines(content) {
+        let mut rest = line.as_str();
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            let name: String = rest
+                .trim_start_matches('/')
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+                .collect();
+            let after = &rest.trim_start_matches('/')[name.len()..];
+            let is_tag = name.starts_with(|c: char| c.is_ascii_alphabetic())
+                && after.starts_with(['>', ' ', '/', '\t']);
+            if is_tag {
+                let name = name.to_lowercase();
+                if !rest.starts_with('/') && !tags.contains(&(number, name.clone())) {
+                    tags.push((number, name));
+                }
+            }
+        }
+    }
+    tags
+}
+
+// Returns the lines of a Markdown document with their numbers and byte offsets, skipping fenced
+// code blocks and blanking inline code spans.
+fn text_lines(content: &str) -> Vec<(usize, usize, String)> {
+    let mut lines = Vec::new();
+    let mut fence: Option<String> = None;
+    let mut offset = 0;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+        if let Some(marker) = &fence {
+            if trimmed.starts_with(marker.as_str()) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(trimmed[..3].to_string());
+            continue;
+        }
+        let mut text = String::with_capacity(line.len());
+        let mut in_code = false;
+        for c in line.trim_end_matches(['\r', '\n']).chars() {
+            if c == '`' {
+                in_code = !in_code;
+            }
+            if in_code || c == '`' {
+                // Keep the byte offsets of the remaining text
+                text.extend(std::iter::repeat_n(' ', c.len_utf8()));
+            } else {
+                text.push(c);
+            }
+        }
+        lines.push((index + 1, line_offset, text));
+    }
+    lines
+}
+
+// Resolves the `.` and `..` components of a relative path.
+fn normalize_path(path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    components.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const README: &str = r#"# Example
+
+[![Build](https://github.com/example/example/actions/workflows/ci.yml/badge.svg)](https://github.com/example/example/actions)
+See the [guide](docs/guide.md "Guide") and the [changelog][changelog].
+
+![Logo](images/logo.png) ![Diagram](../diagram.svg)
+<p align="center"><img src="images/logo.png"></p>
+Use `<T>` or `[x](y)` in code, and <https://example.com> as an autolink.
+
+```html
+<div>![Not an image](nothing.png)</div>
+```
+
+[changelog]: /CHANGELOG.md
+"#;
+
+    #[test]
+    fn test_links() {
+        let links = readme_links(README);
+        let found: Vec<(usize, &str, bool)> = links
+            .iter()
+            .map(|l| (l.line, l.destination.as_str(), l.is_image))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    3,
+                    "https://github.com/example/example/actions/workflows/ci.yml/badge.svg",
+                    true
+                ),
+                (3, "https://github.com/example/example/actions", false),
+                (4, "docs/guide.md", false),
+                (6, "images/logo.png", true),
+                (6, "../diagram.svg", true),
+                (14, "/CHANGELOG.md", false),
+            ]
+        );
+        for link in links {
+            assert_eq!(&README[link.range.clone()], link.destination);
+        }
+
+        // A line ending with an opening destination, or with a multibyte whitespace after it
+        assert_eq!(readme_links("See [the docs](\n"), vec![]);
+        assert_eq!(
+            readme_links("See [the docs](\u{3000}x) and [more](\u{3000}"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_check_readme() {
+        let packaged = vec!["images/logo.png".to_string(), "README.md".to_string()];
+        let issues = check_readme("README.md", README, &packaged);
+        assert_eq!(
+            issues,
+            vec![
+                ReadmeIssue::RelativeLink {
+                    line: 4,
+                    destination: "docs/guide.md".to_string()
+                },
+                ReadmeIssue::ImageNotPackaged {
+                    line: 6,
+                    destination: "../diagram.svg".to_string()
+                },
+                ReadmeIssue::RelativeLink {
+                    line: 14,
+                    destination: "/CHANGELOG.md".to_string()
+                },
+                ReadmeIssue::HtmlTag {
+                    line: 7,
+                    tag: "p".to_string()
+                },
+                ReadmeIssue::HtmlTag {
+                    line: 7,
+                    tag: "img".to_string()
+                },
+            ]
+        );
+        assert!(issues.iter().all(|i| !i.is_error()));
+
+        let issues = check_readme("README.txt", &"x".repeat(README_SIZE_LIMIT + 1), &[]);
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(ReadmeIssue::is_error));
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let rewritten = rewrite_readme_links(
+            README,
+            "https://github.com/example/example/blob/abc/",
+            "https://github.com/example/example/raw/abc/",
+            "crates/example/",
+        );
+        assert!(rewritten.contains(
+            "[guide](https://github.com/example/example/blob/abc/crates/example/docs/guide.md \"Guide\")"
+        ));
+        assert!(rewritten.contains(
+            "![Logo](https://github.com/example/example/raw/abc/crates/example/images/logo.png)"
+        ));
+        assert!(
+            rewritten
+                .contains("[changelog]: https://github.com/example/example/blob/abc/CHANGELOG.md")
+        );
+        assert!(rewritten.contains("`[x](y)`"));
+        assert!(rewritten.contains("![Not an image](nothing.png)"));
+        assert!(
+            check_readme("README.md", &rewritten, &[])
+                .iter()
+                .all(|i| !matches!(
+                    i,
+                    ReadmeIssue::RelativeLink { .. } | ReadmeIssue::ImageNotPackaged { .. }
+                ))
+        );
+    }
+}
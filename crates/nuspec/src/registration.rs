@@ -0,0 +1,473 @@
+use crate::{Dependencies, Dependency, DependencyGroup, License, Metadata};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// The registration index of a package, the metadata of all its versions grouped in pages.
+///
+/// See [Package metadata](https://learn.microsoft.com/en-us/nuget/api/registration-base-url-resource).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct RegistrationIndex {
+    /// The URL of the registration index.
+    #[serde(rename = "@id")]
+    pub id: String,
+    /// The number of the pages.
+    pub count: usize,
+    pub items: Vec<RegistrationPage>,
+}
+
+/// A page of the registration index with the versions between `lower` and `upper`.
+///
+/// The feed may omit the leaves of a page, then they are fetched from the URL of the page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct RegistrationPage {
+    /// The URL of the page.
+    #[serde(rename = "@id")]
+    pub id: String,
+    /// The number of the leaves.
+    pub count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<RegistrationLeaf>>,
+    /// The lowest version in the page.
+    pub lower: String,
+    /// The highest version in the page.
+    pub upper: String,
+}
+
+/// The metadata of a version of a package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct RegistrationLeaf {
+    /// The URL of the registration leaf.
+    #[serde(rename = "@id")]
+    pub id: String,
+    #[serde(rename = "catalogEntry")]
+    pub catalog_entry: CatalogEntry,
+    /// The URL of the `.nupkg` file.
+    #[serde(rename = "packageContent")]
+    pub package_content: String,
+}
+
+/// The metadata of a version of a package, as it is recorded in the catalog of the feed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    /// The URL of the catalog entry.
+    #[serde(rename = "@id")]
+    pub url: String,
+    pub id: String,
+    pub version: String,
+    #[serde(default, deserialize_with = "string_or_list")]
+    pub authors: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_expression: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_license_acceptance: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_client_version: Option<String>,
+    #[serde(default, deserialize_with = "string_or_list")]
+    pub tags: Vec<String>,
+    /// False if the version is unlisted, see [`CatalogEntry::is_listed`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listed: Option<bool>,
+    /// The publication time, `1900-01-01T00:00:00+00:00` for the unlisted versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependency_groups: Option<Vec<DependencyGroupEntry>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation: Option<Deprecation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vulnerabilities: Option<Vec<Vulnerability>>,
+}
+
+/// The dependencies of a version for a target framework.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyGroupEntry {
+    /// The target framework, such as `net8.0`, `None` for the dependencies of every framework.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_framework: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<DependencyEntry>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct DependencyEntry {
+    pub id: String,
+    /// The version range, any version if it is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+}
+
+/// The deprecation of a version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Deprecation {
+    /// The reasons, such as `Legacy`, `CriticalBugs` or `Other`.
+    pub reasons: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// The package recommended instead of the deprecated one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate_package: Option<AlternatePackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct AlternatePackage {
+    pub id: String,
+    /// The version range, `*` for any version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+}
+
+/// A known vulnerability of a version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vulnerability {
+    /// The URL of the advisory.
+    pub advisory_url: String,
+    pub severity: VulnerabilitySeverity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum VulnerabilitySeverity {
+    #[serde(rename = "0")]
+    Low,
+    #[serde(rename = "1")]
+    Moderate,
+    #[serde(rename = "2")]
+    High,
+    #[serde(rename = "3")]
+    Critical,
+}
+
+impl CatalogEntry {
+    /// Returns false if the version is unlisted: it can still be restored, but it is hidden
+    /// from the search.
+    pub fn is_listed(&self) -> bool {
+        self.listed.unwrap_or(true)
+            && !self
+                .published
+                .as_deref()
+                .is_some_and(|p| p.starts_with("1900-"))
+    }
+
+    /// Returns true if the version is deprecated.
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecation.is_some()
+    }
+
+    /// Converts the entry to the metadata of a nuspec file, so a package of a feed is handled
+    /// as a local one. The values that a nuspec file does not have, such as the deprecation,
+    /// are dropped.
+    pub fn to_metadata(&self) -> Metadata {
+        Metadata {
+            id: self.id.clone(),
+            version: self.version.clone(),
+            description: self.description.clone(),
+            authors: self.authors.clone(),
+            title: self.title.clone(),
+            project_url: non_empty(&self.project_url),
+            license: non_empty(&self.license_expression).map(License::Expression),
+            require_license_acceptance: self.require_license_acceptance,
+            language: self.language.clone(),
+            min_client_version: self.min_client_version.clone(),
+            tags: (!self.tags.is_empty()).then(|| self.tags.clone()),
+            dependencies: self.to_dependencies(),
+            ..Default::default()
+        }
+    }
+
+    /// Converts the dependency groups to the dependencies of a nuspec file.
+    pub fn to_dependencies(&self) -> Option<Dependencies> {
+        let groups = self.dependency_groups.as_ref()?;
+        let group = groups
+            .iter()
+            .map(|g| DependencyGroup {
+                target_framework: g.target_framework.clone(),
+                dependency: g
+                    .dependencies
+                    .iter()
+                    .flatten()
+                    .map(|d| Dependency {
+                        id: d.id.clone(),
+                        version: d.range.clone().unwrap_or_default(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            })
+            .collect();
+        Some(Dependencies {
+            dependency: None,
+            group: Some(group),
+        })
+    }
+}
+
+// The feeds write the missing URLs and the license expression as empty strings.
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.clone().filter(|v| !v.is_empty())
+}
+
+// Accepts both forms of the lists written by the feeds: a comma-separated string or an array.
+fn string_or_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        String(String),
+        List(Vec<String>),
+    }
+
+    Ok(match StringOrList::deserialize(deserializer)? {
+        StringOrList::String(s) => s
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        StringOrList::List(list) => list,
+    })
+}
+
+#[cfg(feature = "feed")]
+mod client {
+    use super::*;
+    use crate::{FeedClient, FeedError, NuGetVersion, ResourceKind};
+
+    /// Queries the [`ResourceKind::RegistrationsBaseUrl`] resource with the metadata of the
+    /// packages.
+    impl FeedClient {
+        /// Returns the registration index of a package, `None` if the feed does not know it.
+        pub fn registration(&self, id: &str) -> Result<Option<RegistrationIndex>, FeedError> {
+            let base = self.resource_url(ResourceKind::RegistrationsBaseUrl)?;
+            let url = format!(
+                "{}/{}/index.json",
+                base.trim_end_matches('/'),
+                id.to_lowercase()
+            );
+            match self.get_json(&url) {
+                Err(FeedError::Status { status: 404, .. }) => Ok(None),
+                result => result.map(Some),
+            }
+        }
+
+        /// Returns the metadata of all the versions of a package in the order of the feed,
+        /// fetching the pages that are not inlined in the index.
+        pub fn registration_leaves(&self, id: &str) -> Result<Vec<RegistrationLeaf>, FeedError> {
+            let Some(index) = self.registration(id)? else {
+                return Ok(Vec::new());
+            };
+            let mut leaves = Vec::new();
+            for page in index.items {
+                leaves.extend(self.page_leaves(page)?);
+            }
+            Ok(leaves)
+        }
+
+        /// Returns the metadata of a version of a package, `None` if it is not published.
+        pub fn catalog_entry(
+            &self,
+            id: &str,
+            version: &NuGetVersion,
+        ) -> Result<Option<CatalogEntry>, FeedError> {
+            let Some(index) = self.registration(id)? else {
+                return Ok(None);
+            };
+            let parse = |v: &str| {
+                v.parse::<NuGetVersion>()
+                    .map_err(|e| FeedError::InvalidResponse(e.to_string()))
+            };
+            for page in index.items {
+                // Only the page with the version in its bounds is fetched
+                if parse(&page.lower)? > *version || parse(&page.upper)? < *version {
+                    continue;
+                }
+                for leaf in self.page_leaves(page)? {
+                    if parse(&leaf.catalog_entry.version)? == *version {
+                        return Ok(Some(leaf.catalog_entry));
+                    }
+                }
+            }
+            Ok(None)
+        }
+
+        fn page_leaves(&self, page: RegistrationPage) -> Result<Vec<RegistrationLeaf>, FeedError> {
+            match page.items {
+                Some(items) => Ok(items),
+                None => {
+                    let page: RegistrationPage = self.get_json(&page.id)?;
+                    Ok(page.items.unwrap_or_default())
+                }
+            }
+        }
+
+        fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, FeedError> {
+            let body = self.get(url)?;
+            serde_json::from_str(&body).map_err(|e| FeedError::InvalidResponse(e.to_string()))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    fn leaf(url: &str, version: &str) -> String {
+        format!(
+            r#"{{
+  "@id": "{url}/v3/registration/example/{version}.json",
+  "@type": "Package",
+  "commitId": "1c5a3f2e-0f3a-4c59-9ab0-9d2b5b3a8f41",
+  "catalogEntry": {{
+    "@id": "{url}/v3/catalog0/data/example.{version}.json",
+    "@type": "PackageDetails",
+    "authors": "Sergey Vilgelm, Example Team",
+    "dependencyGroups": [
+      {{
+        "@id": "{url}/v3/catalog0/data/example.{version}.json#dependencygroup/net8.0",
+        "targetFramework": "net8.0",
+        "dependencies": [
+          {{"@id": "{url}/dep", "id": "Newtonsoft.Json", "range": "[13.0.3, )", "registration": "{url}/v3/registration/newtonsoft.json/index.json"}}
+        ]
+      }},
+      {{"targetFramework": "netstandard2.0"}}
+    ],
+    "deprecation": {{
+      "reasons": ["Legacy"],
+      "message": "Use Example.Next",
+      "alternatePackage": {{"id": "Example.Next", "range": "*"}}
+    }},
+    "description": "An example package",
+    "iconUrl": "{url}/icon.png",
+    "id": "Example",
+    "licenseExpression": "MIT",
+    "listed": true,
+    "packageContent": "{url}/example.{version}.nupkg",
+    "projectUrl": "https://github.com/sv-tools/nuspec-rs",
+    "published": "2024-05-01T10:00:00+00:00",
+    "requireLicenseAcceptance": false,
+    "tags": ["rust", "nuget"],
+    "version": "{version}",
+    "vulnerabilities": [
+      {{"advisoryUrl": "https://github.com/advisories/GHSA-0000-0000-0000", "severity": "2"}}
+    ]
+  }},
+  "packageContent": "{url}/example.{version}.nupkg",
+  "registration": "{url}/v3/registration/example/index.json"
+}}"#
+        )
+    }
+
+    #[test]
+    fn test_catalog_entry() {
+        let leaf: RegistrationLeaf = serde_json::from_str(&leaf("https://feed", "1.0.0")).unwrap();
+        let entry = &leaf.catalog_entry;
+        assert!(entry.is_listed());
+        assert!(entry.is_deprecated());
+        assert_eq!(
+            entry.vulnerabilities.as_ref().unwrap()[0].severity,
+            VulnerabilitySeverity::High
+        );
+        let metadata = entry.to_metadata();
+        assert_eq!(metadata.authors, ["Sergey Vilgelm", "Example Team"]);
+        assert_eq!(
+            metadata.license,
+            Some(License::Expression("MIT".to_string()))
+        );
+        assert_eq!(
+            metadata.tags,
+            Some(vec!["rust".to_string(), "nuget".to_string()])
+        );
+        let groups = metadata.dependencies.unwrap().group.unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].target_framework.as_deref(), Some("net8.0"));
+        assert_eq!(groups[0].dependency[0].id, "Newtonsoft.Json");
+        assert_eq!(groups[0].dependency[0].version, "[13.0.3, )");
+        assert!(groups[1].dependency.is_empty());
+
+        let unlisted = CatalogEntry {
+            published: Some("1900-01-01T00:00:00+00:00".to_string()),
+            project_url: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(!unlisted.is_listed());
+        assert_eq!(unlisted.to_metadata().project_url, None);
+    }
+
+    #[cfg(feature = "feed")]
+    #[test]
+    fn test_registration_client() {
+        use crate::test_server::serve;
+        use crate::{FeedClient, NuGetVersion};
+
+        let server = serve(|request, url| match request.path.as_str() {
+            "/v3/index.json" => (
+                200,
+                format!(
+                    r#"{{"version": "3.0.0", "resources": [
+                        {{"@id": "{url}/v3/registration/", "@type": "RegistrationsBaseUrl/3.6.0"}}
+                    ]}}"#
+                ),
+            ),
+            // The first page is inlined, the second one is fetched
+            "/v3/registration/example/index.json" => (
+                200,
+                format!(
+                    r#"{{"@id": "{url}/v3/registration/example/index.json", "count": 2, "items": [
+                        {{"@id": "{url}/v3/registration/example/page/0.1.0/1.0.0.json", "count": 1,
+                          "lower": "0.1.0", "upper": "1.0.0", "items": [{}]}},
+                        {{"@id": "{url}/v3/registration/example/page/1.1.0/2.0.0.json", "count": 1,
+                          "lower": "1.1.0", "upper": "2.0.0"}}
+                    ]}}"#,
+                    leaf(url, "1.0.0")
+                ),
+            ),
+            "/v3/registration/example/page/1.1.0/2.0.0.json" => (
+                200,
+                format!(
+                    r#"{{"@id": "{url}/v3/registration/example/page/1.1.0/2.0.0.json", "count": 1,
+                      "lower": "1.1.0", "upper": "2.0.0", "items": [{}]}}"#,
+                    leaf(url, "2.0.0")
+                ),
+            ),
+            _ => (404, "Not Found".to_string()),
+        });
+        let client = FeedClient::new(&format!("{}/v3/index.json", server.url));
+        let leaves = client.registration_leaves("Example").unwrap();
+        let versions: Vec<&str> = leaves
+            .iter()
+            .map(|l| l.catalog_entry.version.as_str())
+            .collect();
+        assert_eq!(versions, ["1.0.0", "2.0.0"]);
+        assert_eq!(client.registration("Unknown"), Ok(None));
+
+        let before = server.requests().len();
+        let entry = client
+            .catalog_entry("Example", &NuGetVersion::new(1, 0, 0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.version, "1.0.0");
+        // The inlined page is enough
+        assert_eq!(server.requests().len(), before + 1);
+        assert_eq!(
+            client.catalog_entry("Example", &NuGetVersion::new(1, 5, 0)),
+            Ok(None)
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{Dependency, Package, PathSeparator};
+
+impl Package {
+    /// Puts the package into a canonical form, so the same definition is always written
+    /// byte for byte the same way, whatever the host or the order of the inputs.
+    ///
+    /// The files are sorted by their target, the dependencies by their id and the groups by
+    /// their target framework, and the backslashes of the file paths are replaced with slashes.
+    /// The order of the tags, the package types and the content files is preserved, because it
+    /// is meaningful.
+    pub fn make_reproducible(&mut self) {
+        self.make_reproducible_with(PathSeparator::Slash);
+    }
+
+    /// Puts the package into a canonical form as [`Package::make_reproducible`] does, but writes
+    /// the file targets with the given separator, see [`Package::normalize_paths`].
+    pub fn make_reproducible_with(&mut self, separator: PathSeparator) {
+        self.normalize_paths(separator);
+        if let Some(files) = self.files.as_mut() {
+            files
+                .file
+                .sort_by(|a, b| (&a.target, &a.src).cmp(&(&b.target, &b.src)));
+        }
+
+        let metadata = &mut self.metadata;
+        if let Some(dependencies) = metadata.dependencies.as_mut() {
+            if let Some(dependency) = dependencies.dependency.as_mut() {
+                sort_dependencies(dependency);
+            }
+            if let Some(groups) = dependencies.group.as_mut() {
+                for group in groups.iter_mut() {
+                    sort_dependencies(&mut group.dependency);
+                }
+                groups.sort_by(|a, b| a.target_framework.cmp(&b.target_framework));
+            }
+        }
+        if let Some(assemblies) = metadata.framework_assemblies.as_mut() {
+            assemblies.framework_assembly.sort_by(|a, b| {
+                (&a.assembly_name, &a.target_framework)
+                    .cmp(&(&b.assembly_name, &b.target_framework))
+            });
+        }
+        if let Some(references) = metadata.references.as_mut() {
+            if let Some(reference) = references.reference.as_mut() {
+                reference.sort_by(|a, b| a.file.cmp(&b.file));
+            }
+            if let Some(groups) = references.group.as_mut() {
+                for group in groups.iter_mut() {
+                    group.reference.sort_by(|a, b| a.file.cmp(&b.file));
+                }
+                groups.sort_by(|a, b| a.target_framework.cmp(&b.target_framework));
+            }
+        }
+    }
+}
+
+// NuGet compares the package ids case-insensitively.
+fn sort_dependencies(dependencies: &mut [Dependency]) {
+    dependencies.sort_by(|a, b| {
+        a.id.to_lowercase()
+            .cmp(&b.id.to_lowercase())
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PathSeparator, from_str};
+
+    #[test]
+    fn test_make_reproducible() {
+        let xml = r#"<package>
+  <metadata>
+    <id>example</id>
+    <dependencies>
+      <group targetFramework="net8.0">
+        <dependency id="Zeta" version="1.0.0"/>
+        <dependency id="alpha" version="1.0.0"/>
+      </group>
+      <group targetFramework="net6.0">
+        <dependency id="Beta" version="2.0.0"/>
+      </group>
+    </dependencies>
+  </metadata>
+  <files>
+    <file src="target\release\tool.exe" target="tools\tool.exe"/>
+    <file src="README.md" target=""/>
+  </files>
+</package>"#;
+        let mut first: crate::Package = from_str(xml).unwrap();
+        first.make_reproducible();
+        let files = first.files.as_ref().unwrap();
+        assert_eq!(files.file[0].src, "README.md");
+        assert_eq!(files.file[1].src, "target/release/tool.exe");
+        assert_eq!(files.file[1].target.as_deref(), Some("tools/tool.exe"));
+        let groups = first
+            .metadata
+            .dependencies
+            .as_ref()
+            .unwrap()
+            .group
+            .as_ref()
+            .unwrap();
+        assert_eq!(groups[0].target_framework.as_deref(), Some("net6.0"));
+        assert_eq!(groups[1].dependency[0].id, "alpha");
+
+        let mut second = first.clone();
+        second.files.as_mut().unwrap().file.reverse();
+        second.make_reproducible();
+        assert_eq!(first, second);
+
+        let mut backslash: crate::Package = from_str(xml).unwrap();
+        backslash.make_reproducible_with(PathSeparator::Backslash);
+        let files = backslash.files.as_ref().unwrap();
+        assert_eq!(files.file[1].src, "target/release/tool.exe");
+        assert_eq!(files.file[1].target.as_deref(), Some("tools\\tool.exe"));
+    }
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The service index of a NuGet V3 feed, the `index.json` document listing its resources.
+///
+/// See [Service index](https://learn.microsoft.com/en-us/nuget/api/service-index).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ServiceIndex {
+    /// The schema version of the document, such as `3.0.0`.
+    pub version: String,
+    pub resources: Vec<ServiceResource>,
+}
+
+/// A resource of a feed, such as the endpoint to push packages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ServiceResource {
+    /// The URL of the resource.
+    #[serde(rename = "@id")]
+    pub id: String,
+    /// The type of the resource with its version, such as `PackagePublish/2.0.0`.
+    #[serde(rename = "@type")]
+    pub resource_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The minimal version of the NuGet client required by the resource.
+    #[serde(
+        rename = "clientVersion",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub client_version: Option<String>,
+}
+
+impl ServiceResource {
+    /// Returns the name of the resource type without the version, such as `PackagePublish`.
+    pub fn name(&self) -> &str {
+        self.resource_type
+            .split_once('/')
+            .map_or(self.resource_type.as_str(), |(name, _)| name)
+    }
+
+    /// Returns the version of the resource type, such as `2.0.0`, if it has one.
+    pub fn version(&self) -> Option<&str> {
+        self.resource_type
+            .split_once('/')
+            .map(|(_, version)| version)
+    }
+}
+
+/// The kinds of the feed resources used by the crate.
+///
+/// See [Resources](https://learn.microsoft.com/en-us/nuget/api/overview#resources-and-schema).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    /// The flat container to list the versions and download the packages.
+    PackageBaseAddress,
+    /// The package metadata.
+    RegistrationsBaseUrl,
+    /// The search of the packages.
+    SearchQueryService,
+    /// The push and delete of the packages.
+    PackagePublish,
+    /// The push of the symbol packages.
+    SymbolPackagePublish,
+    /// The certificates used to sign the packages of the feed.
+    RepositorySignatures,
+}
+
+impl ResourceKind {
+    /// Returns the resource types supported by the crate, in the order of preference.
+    ///
+    /// A type without a version, as some local feeds announce them, is accepted last.
+    pub fn supported_types(&self) -> &'static [&'static str] {
+        match self {
+            ResourceKind::PackageBaseAddress => &["PackageBaseAddress/3.0.0", "PackageBaseAddress"],
+            // The gzipped and SemVer 2.0.0 registrations are a superset of the older ones
+            ResourceKind::RegistrationsBaseUrl => &[
+                "RegistrationsBaseUrl/3.6.0",
+                "RegistrationsBaseUrl/3.4.0",
+                "RegistrationsBaseUrl/Versioned",
+                "RegistrationsBaseUrl/3.0.0-rc",
+                "RegistrationsBaseUrl/3.0.0-beta",
+                "RegistrationsBaseUrl",
+            ],
+            ResourceKind::SearchQueryService => &[
+                "SearchQueryService/3.5.0",
+                "SearchQueryService/3.0.0-rc",
+                "SearchQueryService/3.0.0-beta",
+                "SearchQueryService",
+            ],
+            ResourceKind::PackagePublish => &["PackagePublish/2.0.0", "PackagePublish"],
+            ResourceKind::SymbolPackagePublish => {
+                &["SymbolPackagePublish/4.9.0", "SymbolPackagePublish"]
+            }
+            ResourceKind::RepositorySignatures => &[
+                "RepositorySignatures/5.0.0",
+                "RepositorySignatures/4.9.0",
+                "RepositorySignatures/4.7.0",
+                "RepositorySignatures",
+            ],
+        }
+    }
+}
+
+impl Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ResourceKind::PackageBaseAddress => "PackageBaseAddress",
+            ResourceKind::RegistrationsBaseUrl => "RegistrationsBaseUrl",
+            ResourceKind::SearchQueryService => "SearchQueryService",
+            ResourceKind::PackagePublish => "PackagePublish",
+            ResourceKind::SymbolPackagePublish => "SymbolPackagePublish",
+            ResourceKind::RepositorySignatures => "RepositorySignatures",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl ServiceIndex {
+    /// Returns the first resource of the exact type, such as `PackagePublish/2.0.0`.
+    pub fn resource(&self, resource_type: &str) -> Option<&ServiceResource> {
+        self.resources
+            .iter()
+            .find(|r| r.resource_type.eq_ignore_ascii_case(resource_type))
+    }
+
+    /// Returns the resource of the most preferred of the types, see
+    /// [`ResourceKind::supported_types`].
+    pub fn negotiate(&self, resource_types: &[&str]) -> Option<&ServiceResource> {
+        resource_types.iter().find_map(|t| self.resource(t))
+    }
+
+    /// Returns the best supported resource of a kind.
+    pub fn find(&self, kind: ResourceKind) -> Option<&ServiceResource> {
+        self.negotiate(kind.supported_types())
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        let index: ServiceIndex = serde_json::from_str(
+            r#"{
+  "version": "3.0.0",
+  "resources": [
+    {"@id": "https://api.nuget.org/v3/registration5-semver1/", "@type": "RegistrationsBaseUrl", "comment": "Base URL of the registrations"},
+    {"@id": "https://api.nuget.org/v3/registration5-gz-semver2/", "@type": "RegistrationsBaseUrl/3.6.0"},
+    {"@id": "https://api.nuget.org/v3-flatcontainer/", "@type": "PackageBaseAddress/3.0.0"},
+    {"@id": "https://www.nuget.org/api/v2/package", "@type": "PackagePublish/2.0.0"},
+    {"@id": "https://api.nuget.org/v3/catalog0/index.json", "@type": "Catalog/3.0.0"},
+    {"@id": "https://api.nuget.org/v3-index/repository-signatures/5.0.0/index.json", "@type": "RepositorySignatures/5.0.0", "clientVersion": "5.0.0"}
+  ],
+  "@context": {"@vocab": "http://schema.nuget.org/services#"}
+}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            index.find(ResourceKind::RegistrationsBaseUrl).unwrap().id,
+            "https://api.nuget.org/v3/registration5-gz-semver2/"
+        );
+        let publish = index.find(ResourceKind::PackagePublish).unwrap();
+        assert_eq!(publish.name(), "PackagePublish");
+        assert_eq!(publish.version(), Some("2.0.0"));
+        assert_eq!(
+            index
+                .find(ResourceKind::RepositorySignatures)
+                .unwrap()
+                .client_version
+                .as_deref(),
+            Some("5.0.0")
+        );
+        assert!(index.find(ResourceKind::SearchQueryService).is_none());
+
+        // A local feed announcing the types without versions
+        let local = ServiceIndex {
+            version: "3.0.0".to_string(),
+            resources: vec![ServiceResource {
+                id: "http://localhost:5000/v3/search".to_string(),
+                resource_type: "SearchQueryService".to_string(),
+                ..Default::default()
+            }],
+        };
+        assert_eq!(
+            local
+                .find(ResourceKind::SearchQueryService)
+                .unwrap()
+                .version(),
+            None
+        );
+    }
+}
@@ -0,0 +1,957 @@
+use crate::{NupkgReader, SIGNATURE_FILE};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use cms::cert::CertificateChoices;
+use cms::content_info::ContentInfo;
+use cms::signed_data::{SignedData, SignerIdentifier, SignerInfo};
+use der::asn1::{
+    GeneralizedTime, Ia5StringRef, ObjectIdentifier, OctetString, Uint, Utf8StringRef,
+};
+use der::{Any, Decode, Encode, Sequence};
+use ring::{digest, signature as ring_signature};
+use std::fmt::Display;
+use std::time::SystemTime;
+use std::{error, fs, io, path};
+use x509_cert::Certificate;
+use x509_cert::attr::Attributes;
+use x509_cert::ext::Extensions;
+use x509_cert::ext::pkix::SubjectKeyIdentifier;
+use x509_cert::spki::AlgorithmIdentifierOwned;
+use x509_cert::time::Time;
+
+pub(crate) const DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+pub(crate) const SIGNED_DATA: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+pub(crate) const TST_INFO: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+pub(crate) const CONTENT_TYPE: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.3");
+pub(crate) const MESSAGE_DIGEST: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+pub(crate) const SIGNING_TIME: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.5");
+const COUNTERSIGNATURE: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.6");
+pub(crate) const TIMESTAMP_TOKEN: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.2.14");
+pub(crate) const COMMITMENT_TYPE: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.2.16");
+pub(crate) const SIGNING_CERTIFICATE_V2: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.2.47");
+pub(crate) const PROOF_OF_ORIGIN: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.6.1");
+const PROOF_OF_RECEIPT: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.6.2");
+// The attributes of the repository signatures defined by NuGet
+const SERVICE_INDEX: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.84.2.1.1.1");
+const PACKAGE_OWNERS: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.84.2.1.1.2");
+pub(crate) const RSA_ENCRYPTION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+pub(crate) const SHA256_WITH_RSA: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+pub(crate) const SHA384_WITH_RSA: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.12");
+pub(crate) const SHA512_WITH_RSA: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.13");
+const EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+pub(crate) const ECDSA_WITH_SHA256: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+pub(crate) const ECDSA_WITH_SHA384: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.3");
+pub(crate) const P256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+pub(crate) const P384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+
+/// An error returned when a package signature is read, verified or created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// A package can not be read.
+    Io(String),
+    /// The package has no signature file.
+    NotSigned,
+    /// The package or its signature is malformed.
+    Invalid(String),
+    /// The signature uses an algorithm which is not supported.
+    Unsupported(String),
+    /// A signature or a hash does not match the signed content.
+    Mismatch(String),
+    /// The package is signed already, see [`SignOptions::overwrite`](crate::SignOptions::overwrite).
+    AlreadySigned,
+    /// The timestamp authority failed or returned an invalid response.
+    Timestamp(String),
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureError::Io(message) => write!(f, "{message}"),
+            SignatureError::NotSigned => write!(f, "the package is not signed"),
+            SignatureError::Invalid(message) => write!(f, "invalid package signature: {message}"),
+            SignatureError::Unsupported(message) => {
+                write!(f, "unsupported package signature: {message}")
+            }
+            SignatureError::Mismatch(message) => {
+                write!(f, "the package signature is not valid: {message}")
+            }
+            SignatureError::AlreadySigned => write!(f, "the package is already signed"),
+            SignatureError::Timestamp(message) => {
+                write!(f, "failed to timestamp the signature: {message}")
+            }
+        }
+    }
+}
+
+impl error::Error for SignatureError {}
+
+impl From<der::Error> for SignatureError {
+    fn from(e: der::Error) -> Self {
+        SignatureError::Invalid(e.to_string())
+    }
+}
+
+/// The hash algorithms of the package signatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    const SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+    const SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2");
+    const SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3");
+
+    /// Returns the object identifier of the algorithm.
+    pub fn oid(&self) -> ObjectIdentifier {
+        match self {
+            HashAlgorithm::Sha256 => Self::SHA256,
+            HashAlgorithm::Sha384 => Self::SHA384,
+            HashAlgorithm::Sha512 => Self::SHA512,
+        }
+    }
+
+    pub(crate) fn from_oid(oid: &ObjectIdentifier) -> Result<Self, SignatureError> {
+        match *oid {
+            Self::SHA256 => Ok(HashAlgorithm::Sha256),
+            Self::SHA384 => Ok(HashAlgorithm::Sha384),
+            Self::SHA512 => Ok(HashAlgorithm::Sha512),
+            _ => Err(SignatureError::Unsupported(format!(
+                "the hash algorithm {oid}"
+            ))),
+        }
+    }
+
+    /// Returns the digest of a content.
+    pub fn digest(&self, content: &[u8]) -> Vec<u8> {
+        let algorithm = match self {
+            HashAlgorithm::Sha256 => &digest::SHA256,
+            HashAlgorithm::Sha384 => &digest::SHA384,
+            HashAlgorithm::Sha512 => &digest::SHA512,
+        };
+        digest::digest(algorithm, content).as_ref().to_vec()
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashAlgorithm::Sha256 => write!(f, "SHA256"),
+            HashAlgorithm::Sha384 => write!(f, "SHA384"),
+            HashAlgorithm::Sha512 => write!(f, "SHA512"),
+        }
+    }
+}
+
+/// The kind of a signature, given by its commitment type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureKind {
+    /// The signature of the author of the package.
+    Author,
+    /// The signature of the repository the package is published to.
+    Repository,
+    /// A signature without a known commitment type.
+    Unknown,
+}
+
+/// A timestamp of a signature, from an RFC 3161 timestamp authority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    pub time: SystemTime,
+    /// The subject of the certificate of the timestamp authority.
+    pub subject: String,
+    /// The SHA-256 fingerprint of the certificate of the timestamp authority.
+    pub fingerprint: String,
+}
+
+/// A signer of a package, as described by its certificate and its signed attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signer {
+    pub kind: SignatureKind,
+    /// The subject of the certificate in the RFC 4514 form, such as `O=Example,CN=Example`.
+    pub subject: String,
+    pub issuer: String,
+    /// The serial number of the certificate in hexadecimal.
+    pub serial_number: String,
+    /// The SHA-256 fingerprint of the certificate in uppercase hexadecimal, as NuGet shows it.
+    pub fingerprint: String,
+    pub not_before: SystemTime,
+    pub not_after: SystemTime,
+    /// The signing time claimed by the signer.
+    pub signing_time: Option<SystemTime>,
+    pub timestamp: Option<Timestamp>,
+    /// The service index of the repository, for a repository signature.
+    pub service_index: Option<String>,
+    /// The owners of the package on the repository, for a repository signature.
+    pub owners: Vec<String>,
+}
+
+/// The signature of a signed package, the `.signature.p7s` file.
+///
+/// The signature is a CMS `SignedData` of the hash of the package without its signature file.
+/// The primary signature is the author or the repository signature, and a repository can
+/// countersign an author signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSignature {
+    pub signer: Signer,
+    /// The repository countersignature of an author signature.
+    pub countersignature: Option<Signer>,
+    pub hash_algorithm: HashAlgorithm,
+    /// The base64 hash of the package without its signature file.
+    pub content_hash: String,
+    signed_data: SignedData,
+    content: Vec<u8>,
+}
+
+impl PackageSignature {
+    /// Parses a `.signature.p7s` file.
+    pub fn from_der(bytes: &[u8]) -> Result<Self, SignatureError> {
+        let signed_data = decode_signed_data(bytes)?;
+        if signed_data.encap_content_info.econtent_type != DATA {
+            return Err(SignatureError::Invalid(
+                "the signed content is not data".to_string(),
+            ));
+        }
+        let content = encapsulated_content(&signed_data)?;
+        let (hash_algorithm, content_hash) = parse_content(&content)?;
+        let certificates = certificates(&signed_data);
+        let info = single_signer(&signed_data)?;
+        let signer = describe(info, &certificates)?;
+        let countersignature = countersignature(info)?
+            .map(|counter| describe(&counter, &certificates))
+            .transpose()?;
+        Ok(PackageSignature {
+            signer,
+            countersignature,
+            hash_algorithm,
+            content_hash,
+            signed_data,
+            content,
+        })
+    }
+
+    /// Reads the signature of a package, `None` for an unsigned package.
+    pub fn read<P: AsRef<path::Path>>(nupkg: P) -> Result<Option<Self>, SignatureError> {
+        let nupkg = nupkg.as_ref();
+        let mut reader = NupkgReader::open(nupkg).map_err(|e| SignatureError::Io(e.to_string()))?;
+        let bytes = reader.signature().map_err(|e| {
+            SignatureError::Io(format!("Failed to read the signature of {nupkg:?}: {e}"))
+        })?;
+        bytes
+            .map(|bytes| PackageSignature::from_der(&bytes))
+            .transpose()
+    }
+
+    /// Verifies the signature against the content of the signed package: the hash of the
+    /// package without its signature file, the message digests and the cryptographic
+    /// signatures of the signers and of their timestamps, and the validity period of the
+    /// certificates at the time of the timestamp, or now without a timestamp.
+    ///
+    /// The certificates are not checked against a trust store, compare the fingerprints
+    /// with the expected ones for that.
+    pub fn verify(&self, nupkg: &[u8]) -> Result<(), SignatureError> {
+        let unsigned = unsigned_package(nupkg)?;
+        let actual = STANDARD.encode(self.hash_algorithm.digest(&unsigned));
+        if actual != self.content_hash {
+            return Err(SignatureError::Mismatch(format!(
+                "the package hash is {actual}, the signed hash is {}",
+                self.content_hash
+            )));
+        }
+        let certificates = certificates(&self.signed_data);
+        let info = single_signer(&self.signed_data)?;
+        verify_signer(info, &certificates, &self.content, Some(DATA))
+            .map_err(|e| context(e, "the primary signature"))?;
+        if let Some(counter) = countersignature(info)? {
+            verify_signer(&counter, &certificates, info.signature.as_bytes(), None)
+                .map_err(|e| context(e, "the repository countersignature"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads and verifies the signature of a package, see [`PackageSignature::verify`].
+pub fn verify_package<P: AsRef<path::Path>>(nupkg: P) -> Result<PackageSignature, SignatureError> {
+    let nupkg = nupkg.as_ref();
+    let content = fs::read(nupkg)
+        .map_err(|e| SignatureError::Io(format!("Failed to read the package {nupkg:?}: {e}")))?;
+    let mut reader = NupkgReader::new(io::Cursor::new(content.as_slice()))
+        .map_err(|e| SignatureError::Invalid(format!("{nupkg:?}: {e}")))?;
+    let bytes = reader
+        .signature()
+        .map_err(|e| SignatureError::Invalid(format!("{nupkg:?}: {e}")))?
+        .ok_or(SignatureError::NotSigned)?;
+    let signature = PackageSignature::from_der(&bytes)?;
+    signature.verify(&content)?;
+    Ok(signature)
+}
+
+fn context(e: SignatureError, what: &str) -> SignatureError {
+    match e {
+        SignatureError::Invalid(message) => SignatureError::Invalid(format!("{what}: {message}")),
+        SignatureError::Mismatch(message) => SignatureError::Mismatch(format!("{what}: {message}")),
+        e => e,
+    }
+}
+
+// Returns the content of a package before its signature file was appended: NuGet requires
+// the signature to be the last entry of the archive, so the signed hash covers the other
+// entries, the central directory without the signature record and the adjusted end of the
+// central directory.
+pub(crate) fn unsigned_package(content: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    let archive = CentralDirectory::read(content)?;
+    let (last, records) = archive
+        .records
+        .split_last()
+        .ok_or(SignatureError::NotSigned)?;
+    let is_last = last.name == SIGNATURE_FILE.as_bytes()
+        && records
+            .iter()
+            .all(|r| r.name != SIGNATURE_FILE.as_bytes() && r.local_offset < last.local_offset);
+    if !is_last {
+        return Err(SignatureError::Invalid(format!(
+            "the {SIGNATURE_FILE} file is not the last entry of the package"
+        )));
+    }
+    let signature_start = last.local_offset as usize;
+    let directory_start = archive.offset as usize;
+    let directory_end = (directory_start + archive.size as usize)
+        .checked_sub(last.length)
+        .filter(|&end| {
+            signature_start <= directory_start && directory_start <= end && end <= content.len()
+        })
+        .ok_or_else(|| {
+            SignatureError::Invalid(format!(
+                "the {SIGNATURE_FILE} file overlaps the central directory"
+            ))
+        })?;
+    let mut unsigned = Vec::with_capacity(content.len());
+    unsigned.extend_from_slice(&content[..signature_start]);
+    unsigned.extend_from_slice(&content[directory_start..directory_end]);
+    let mut end = content[archive.end..].to_vec();
+    let entries = records.len() as u16;
+    end[8..10].copy_from_slice(&entries.to_le_bytes());
+    end[10..12].copy_from_slice(&entries.to_le_bytes());
+    end[12..16].copy_from_slice(&((directory_end - directory_start) as u32).to_le_bytes());
+    end[16..20].copy_from_slice(&(last.local_offset).to_le_bytes());
+    unsigned.extend_from_slice(&end);
+    Ok(unsigned)
+}
+
+// The central directory of a ZIP archive.
+pub(crate) struct CentralDirectory {
+    pub(crate) offset: u32,
+    pub(crate) size: u32,
+    // The position of the end of central directory record
+    pub(crate) end: usize,
+    pub(crate) records: Vec<CentralRecord>,
+}
+
+pub(crate) struct CentralRecord {
+    pub(crate) name: Vec<u8>,
+    pub(crate) local_offset: u32,
+    pub(crate) length: usize,
+}
+
+impl CentralDirectory {
+    const END_SIGNATURE: u32 = 0x06054b50;
+    const RECORD_SIGNATURE: u32 = 0x02014b50;
+    const END_LENGTH: usize = 22;
+    const RECORD_LENGTH: usize = 46;
+
+    pub(crate) fn read(content: &[u8]) -> Result<Self, SignatureError> {
+        let truncated = || SignatureError::Invalid("the package is not a ZIP archive".to_string());
+        let last = content
+            .len()
+            .checked_sub(Self::END_LENGTH)
+            .ok_or_else(truncated)?;
+        // The end record is followed by a comment of up to 64 KiB
+        let end = (last.saturating_sub(u16::MAX as usize)..=last)
+            .rev()
+            .find(|&i| {
+                u32_at(content, i) == Some(Self::END_SIGNATURE)
+                    && u16_at(content, i + 20).map(|len| i + Self::END_LENGTH + len as usize)
+                        == Some(content.len())
+            })
+            .ok_or_else(truncated)?;
+        let entries = u16_at(content, end + 10).ok_or_else(truncated)?;
+        let size = u32_at(content, end + 12).ok_or_else(truncated)?;
+        let offset = u32_at(content, end + 16).ok_or_else(truncated)?;
+        if entries == u16::MAX || size == u32::MAX || offset == u32::MAX {
+            return Err(SignatureError::Unsupported(
+                "the ZIP64 packages".to_string(),
+            ));
+        }
+        if offset as usize + size as usize != end {
+            return Err(SignatureError::Invalid(
+                "the central directory does not precede its end record".to_string(),
+            ));
+        }
+        let mut records = Vec::with_capacity(entries as usize);
+        let mut position = offset as usize;
+        for _ in 0..entries {
+            if u32_at(content, position) != Some(Self::RECORD_SIGNATURE) {
+                return Err(truncated());
+            }
+            let field = |at: usize| u16_at(content, position + at).map(usize::from);
+            let (name, extra, comment) = (field(28), field(30), field(32));
+            let (Some(name), Some(extra), Some(comment)) = (name, extra, comment) else {
+                return Err(truncated());
+            };
+            let start = position + Self::RECORD_LENGTH;
+            let length = Self::RECORD_LENGTH + name + extra + comment;
+            records.push(CentralRecord {
+                name: content
+                    .get(start..start + name)
+                    .ok_or_else(truncated)?
+                    .to_vec(),
+                local_offset: u32_at(content, position + 42).ok_or_else(truncated)?,
+                length,
+            });
+            position += length;
+        }
+        if position != end {
+            return Err(truncated());
+        }
+        Ok(CentralDirectory {
+            offset,
+            size,
+            end,
+            records,
+        })
+    }
+}
+
+fn u16_at(content: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        content.get(at..at + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(content: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        content.get(at..at + 4)?.try_into().ok()?,
+    ))
+}
+
+// Parses the signed content: `Version:1`, an empty line and the `{oid}-Hash:{base64}` line.
+fn parse_content(content: &[u8]) -> Result<(HashAlgorithm, String), SignatureError> {
+    let invalid = |message: &str| SignatureError::Invalid(message.to_string());
+    let text =
+        std::str::from_utf8(content).map_err(|_| invalid("the signed content is not text"))?;
+    let mut lines = text.lines().filter(|line| !line.is_empty());
+    if lines.next() != Some("Version:1") {
+        return Err(SignatureError::Unsupported(
+            "the signed content version".to_string(),
+        ));
+    }
+    let (oid, hash) = lines
+        .find_map(|line| line.split_once("-Hash:"))
+        .ok_or_else(|| invalid("the signed content has no package hash"))?;
+    let oid = ObjectIdentifier::new(oid).map_err(|_| invalid("the hash algorithm is malformed"))?;
+    Ok((HashAlgorithm::from_oid(&oid)?, hash.to_string()))
+}
+
+fn decode_signed_data(bytes: &[u8]) -> Result<SignedData, SignatureError> {
+    let info = ContentInfo::from_der(bytes)?;
+    if info.content_type != SIGNED_DATA {
+        return Err(SignatureError::Invalid(
+            "the signature is not a CMS SignedData".to_string(),
+        ));
+    }
+    Ok(info.content.decode_as()?)
+}
+
+fn encapsulated_content(signed_data: &SignedData) -> Result<Vec<u8>, SignatureError> {
+    let content =
+        signed_data
+            .encap_content_info
+            .econtent
+            .as_ref()
+            .ok_or(SignatureError::Invalid(
+                "the signed content is detached".to_string(),
+            ))?;
+    Ok(content.decode_as::<OctetString>()?.into_bytes())
+}
+
+fn certificates(signed_data: &SignedData) -> Vec<Certificate> {
+    signed_data
+        .certificates
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(cert) => Some(cert.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn single_signer(signed_data: &SignedData) -> Result<&SignerInfo, SignatureError> {
+    let mut infos = signed_data.signer_infos.0.iter();
+    match (infos.next(), infos.next()) {
+        (Some(info), None) => Ok(info),
+        _ => Err(SignatureError::Invalid(
+            "a signature must have exactly one signer".to_string(),
+        )),
+    }
+}
+
+fn attribute(attributes: &Option<Attributes>, oid: ObjectIdentifier) -> Option<&Any> {
+    attributes
+        .iter()
+        .flat_map(|attributes| attributes.iter())
+        .find(|attribute| attribute.oid == oid)
+        .and_then(|attribute| attribute.values.iter().next())
+}
+
+fn countersignature(info: &SignerInfo) -> Result<Option<SignerInfo>, SignatureError> {
+    Ok(attribute(&info.unsigned_attrs, COUNTERSIGNATURE)
+        .map(|value| value.decode_as())
+        .transpose()?)
+}
+
+fn find_certificate<'a>(
+    info: &SignerInfo,
+    certificates: &'a [Certificate],
+) -> Result<&'a Certificate, SignatureError> {
+    certificates
+        .iter()
+        .find(|cert| match &info.sid {
+            SignerIdentifier::IssuerAndSerialNumber(id) => {
+                cert.tbs_certificate.issuer == id.issuer
+                    && cert.tbs_certificate.serial_number == id.serial_number
+            }
+            SignerIdentifier::SubjectKeyIdentifier(id) => cert
+                .tbs_certificate
+                .get::<SubjectKeyIdentifier>()
+                .is_ok_and(|key| key.is_some_and(|(_, key)| key == *id)),
+        })
+        .ok_or(SignatureError::Invalid(
+            "the certificate of the signer is missing".to_string(),
+        ))
+}
+
+pub(crate) fn fingerprint(cert: &Certificate) -> Result<String, SignatureError> {
+    Ok(hex(&HashAlgorithm::Sha256.digest(&cert.to_der()?)))
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+// Describes a signer from its certificate and its attributes.
+fn describe(info: &SignerInfo, certificates: &[Certificate]) -> Result<Signer, SignatureError> {
+    let cert = find_certificate(info, certificates)?;
+    let tbs = &cert.tbs_certificate;
+    let kind = match attribute(&info.signed_attrs, COMMITMENT_TYPE) {
+        Some(value) => match value
+            .decode_as::<CommitmentTypeIndication>()?
+            .commitment_type_id
+        {
+            PROOF_OF_ORIGIN => SignatureKind::Author,
+            PROOF_OF_RECEIPT => SignatureKind::Repository,
+            _ => SignatureKind::Unknown,
+        },
+        None => SignatureKind::Unknown,
+    };
+    let signing_time = attribute(&info.signed_attrs, SIGNING_TIME)
+        .map(|value| Time::from_der(&value.to_der()?))
+        .transpose()?
+        .map(|time| time.to_system_time());
+    let service_index = attribute(&info.signed_attrs, SERVICE_INDEX)
+        .map(|value| value.decode_as::<Ia5StringRef>().map(|url| url.to_string()))
+        .transpose()?;
+    let owners = attribute(&info.signed_attrs, PACKAGE_OWNERS)
+        .map(|value| value.decode_as::<Vec<Utf8StringRef>>())
+        .transpose()?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|owner| owner.to_string())
+        .collect();
+    let timestamp = attribute(&info.unsigned_attrs, TIMESTAMP_TOKEN)
+        .map(|token| TimestampToken::decode(token)?.describe())
+        .transpose()?;
+    Ok(Signer {
+        kind,
+        subject: tbs.subject.to_string(),
+        issuer: tbs.issuer.to_string(),
+        serial_number: hex(tbs.serial_number.as_bytes()),
+        fingerprint: fingerprint(cert)?,
+        not_before: tbs.validity.not_before.to_system_time(),
+        not_after: tbs.validity.not_after.to_system_time(),
+        signing_time,
+        timestamp,
+        service_index,
+        owners,
+    })
+}
+
+// An RFC 3161 timestamp token, the SignedData of a TSTInfo.
+pub(crate) struct TimestampToken {
+    signed_data: SignedData,
+    content: Vec<u8>,
+    pub(crate) tst_info: TstInfo,
+}
+
+impl TimestampToken {
+    pub(crate) fn decode(token: &Any) -> Result<Self, SignatureError> {
+        let signed_data = decode_signed_data(&token.to_der()?)?;
+        if signed_data.encap_content_info.econtent_type != TST_INFO {
+            return Err(SignatureError::Invalid(
+                "the timestamp token has no TSTInfo".to_string(),
+            ));
+        }
+        let content = encapsulated_content(&signed_data)?;
+        let tst_info = TstInfo::from_der(&content)?;
+        Ok(TimestampToken {
+            signed_data,
+            content,
+            tst_info,
+        })
+    }
+
+    pub(crate) fn time(&self) -> SystemTime {
+        self.tst_info.gen_time.to_system_time()
+    }
+
+    fn describe(&self) -> Result<Timestamp, SignatureError> {
+        let certificates = certificates(&self.signed_data);
+        let cert = find_certificate(single_signer(&self.signed_data)?, &certificates)?;
+        Ok(Timestamp {
+            time: self.time(),
+            subject: cert.tbs_certificate.subject.to_string(),
+            fingerprint: fingerprint(cert)?,
+        })
+    }
+
+    // Verifies that the token is the one of a signature value, and its own signature.
+    pub(crate) fn verify(&self, signature: &[u8]) -> Result<(), SignatureError> {
+        let imprint = &self.tst_info.message_imprint;
+        let algorithm = HashAlgorithm::from_oid(&imprint.hash_algorithm.oid)?;
+        if imprint.hashed_message.as_bytes() != algorithm.digest(signature) {
+            return Err(SignatureError::Mismatch(
+                "the timestamp is not the one of the signature".to_string(),
+            ));
+        }
+        let certificates = certificates(&self.signed_data);
+        let info = single_signer(&self.signed_data)?;
+        let cert = find_certificate(info, &certificates)?;
+        check_signature(info, cert, &self.content, Some(TST_INFO))?;
+        check_validity(cert, self.time())
+    }
+}
+
+// The content of a timestamp token, as defined by RFC 3161.
+#[derive(Sequence)]
+pub(crate) struct TstInfo {
+    pub(crate) version: u8,
+    pub(crate) policy: ObjectIdentifier,
+    pub(crate) message_imprint: MessageImprint,
+    pub(crate) serial_number: Uint,
+    pub(crate) gen_time: GeneralizedTime,
+    #[asn1(optional = "true")]
+    pub(crate) accuracy: Option<Accuracy>,
+    #[asn1(default = "Default::default")]
+    pub(crate) ordering: bool,
+    #[asn1(optional = "true")]
+    pub(crate) nonce: Option<Uint>,
+    #[asn1(context_specific = "0", tag_mode = "EXPLICIT", optional = "true")]
+    pub(crate) tsa: Option<Any>,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    pub(crate) extensions: Option<Extensions>,
+}
+
+#[derive(Sequence)]
+pub(crate) struct Accuracy {
+    #[asn1(optional = "true")]
+    pub(crate) seconds: Option<Uint>,
+    #[asn1(context_specific = "0", tag_mode = "IMPLICIT", optional = "true")]
+    pub(crate) millis: Option<Uint>,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    pub(crate) micros: Option<Uint>,
+}
+
+#[derive(Sequence)]
+pub(crate) struct MessageImprint {
+    pub(crate) hash_algorithm: AlgorithmIdentifierOwned,
+    pub(crate) hashed_message: OctetString,
+}
+
+#[derive(Sequence)]
+pub(crate) struct CommitmentTypeIndication {
+    pub(crate) commitment_type_id: ObjectIdentifier,
+    #[asn1(optional = "true")]
+    pub(crate) qualifiers: Option<Any>,
+}
+
+#[derive(Sequence)]
+pub(crate) struct SigningCertificateV2 {
+    pub(crate) certs: Vec<EssCertIdV2>,
+    #[asn1(optional = "true")]
+    pub(crate) policies: Option<Any>,
+}
+
+#[derive(Sequence)]
+pub(crate) struct EssCertIdV2 {
+    // SHA-256 when absent
+    #[asn1(optional = "true")]
+    pub(crate) hash_algorithm: Option<AlgorithmIdentifierOwned>,
+    pub(crate) cert_hash: OctetString,
+    #[asn1(optional = "true")]
+    pub(crate) issuer_serial: Option<Any>,
+}
+
+// Verifies a signer with its signed attributes, and its timestamp.
+fn verify_signer(
+    info: &SignerInfo,
+    certificates: &[Certificate],
+    content: &[u8],
+    content_type: Option<ObjectIdentifier>,
+) -> Result<(), SignatureError> {
+    let cert = find_certificate(info, certificates)?;
+    check_signature(info, cert, content, content_type)?;
+    let time = match attribute(&info.unsigned_attrs, TIMESTAMP_TOKEN) {
+        Some(token) => {
+            let token = TimestampToken::decode(token)?;
+            token
+                .verify(info.signature.as_bytes())
+                .map_err(|e| context(e, "the timestamp"))?;
+            token.time()
+        }
+        None => SystemTime::now(),
+    };
+    check_validity(cert, time)
+}
+
+// Checks the message digest, the content type and the signing certificate attributes,
+// and the signature of the attributes.
+fn check_signature(
+    info: &SignerInfo,
+    cert: &Certificate,
+    content: &[u8],
+    content_type: Option<ObjectIdentifier>,
+) -> Result<(), SignatureError> {
+    let attributes = info.signed_attrs.as_ref().ok_or(SignatureError::Invalid(
+        "the signer has no signed attributes".to_string(),
+    ))?;
+    let algorithm = HashAlgorithm::from_oid(&info.digest_alg.oid)?;
+    let digest = attribute(&info.signed_attrs, MESSAGE_DIGEST)
+        .ok_or(SignatureError::Invalid(
+            "the signer has no message digest".to_string(),
+        ))?
+        .decode_as::<OctetString>()?;
+    if digest.as_bytes() != algorithm.digest(content) {
+        return Err(SignatureError::Mismatch(
+            "the message digest does not match the signed content".to_string(),
+        ));
+    }
+    if let Some(expected) = content_type {
+        let actual = attribute(&info.signed_attrs, CONTENT_TYPE)
+            .map(|value| value.decode_as::<ObjectIdentifier>())
+            .transpose()?;
+        if actual != Some(expected) {
+            return Err(SignatureError::Invalid(format!(
+                "the content type is not {expected}"
+            )));
+        }
+    }
+    if let Some(value) = attribute(&info.signed_attrs, SIGNING_CERTIFICATE_V2) {
+        let signing_certificate = value.decode_as::<SigningCertificateV2>()?;
+        let id = signing_certificate
+            .certs
+            .first()
+            .ok_or(SignatureError::Invalid(
+                "the signing certificate attribute is empty".to_string(),
+            ))?;
+        let algorithm = match &id.hash_algorithm {
+            Some(algorithm) => HashAlgorithm::from_oid(&algorithm.oid)?,
+            None => HashAlgorithm::Sha256,
+        };
+        if id.cert_hash.as_bytes() != algorithm.digest(&cert.to_der()?) {
+            return Err(SignatureError::Mismatch(
+                "the signing certificate attribute does not match the certificate".to_string(),
+            ));
+        }
+    }
+    let verification = verification_algorithm(info, cert, algorithm)?;
+    let key = cert
+        .tbs_certificate
+        .subject_public_key_info
+        .subject_public_key
+        .raw_bytes();
+    ring_signature::UnparsedPublicKey::new(verification, key)
+        .verify(&attributes.to_der()?, info.signature.as_bytes())
+        .map_err(|_| {
+            SignatureError::Mismatch("the signature does not match the certificate".to_string())
+        })
+}
+
+fn verification_algorithm(
+    info: &SignerInfo,
+    cert: &Certificate,
+    algorithm: HashAlgorithm,
+) -> Result<&'static dyn ring_signature::VerificationAlgorithm, SignatureError> {
+    let key = &cert.tbs_certificate.subject_public_key_info.algorithm;
+    let curve = key
+        .parameters
+        .as_ref()
+        .and_then(|parameters| parameters.decode_as::<ObjectIdentifier>().ok());
+    let signature = info.signature_algorithm.oid;
+    Ok(match (key.oid, curve, signature, algorithm) {
+        (RSA_ENCRYPTION, _, RSA_ENCRYPTION | SHA256_WITH_RSA, HashAlgorithm::Sha256) => {
+            &ring_signature::RSA_PKCS1_2048_8192_SHA256
+        }
+        (RSA_ENCRYPTION, _, RSA_ENCRYPTION | SHA384_WITH_RSA, HashAlgorithm::Sha384) => {
+            &ring_signature::RSA_PKCS1_2048_8192_SHA384
+        }
+        (RSA_ENCRYPTION, _, RSA_ENCRYPTION | SHA512_WITH_RSA, HashAlgorithm::Sha512) => {
+            &ring_signature::RSA_PKCS1_2048_8192_SHA512
+        }
+        (EC_PUBLIC_KEY, Some(P256), ECDSA_WITH_SHA256, HashAlgorithm::Sha256) => {
+            &ring_signature::ECDSA_P256_SHA256_ASN1
+        }
+        (EC_PUBLIC_KEY, Some(P256), ECDSA_WITH_SHA384, HashAlgorithm::Sha384) => {
+            &ring_signature::ECDSA_P256_SHA384_ASN1
+        }
+        (EC_PUBLIC_KEY, Some(P384), ECDSA_WITH_SHA256, HashAlgorithm::Sha256) => {
+            &ring_signature::ECDSA_P384_SHA256_ASN1
+        }
+        (EC_PUBLIC_KEY, Some(P384), ECDSA_WITH_SHA384, HashAlgorithm::Sha384) => {
+            &ring_signature::ECDSA_P384_SHA384_ASN1
+        }
+        _ => {
+            return Err(SignatureError::Unsupported(format!(
+                "the signature algorithm {signature} with {algorithm}"
+            )));
+        }
+    })
+}
+
+fn check_validity(cert: &Certificate, time: SystemTime) -> Result<(), SignatureError> {
+    let validity = &cert.tbs_certificate.validity;
+    if time < validity.not_before.to_system_time() || time > validity.not_after.to_system_time() {
+        return Err(SignatureError::Mismatch(format!(
+            "the certificate {} is not valid at the time of the signature",
+            cert.tbs_certificate.subject
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::{TempDir, fixture};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_author_signature() {
+        let signature = verify_package(fixture("author-signed.nupkg")).unwrap();
+        assert_eq!(signature.hash_algorithm, HashAlgorithm::Sha256);
+        let signer = &signature.signer;
+        assert_eq!(signer.kind, SignatureKind::Author);
+        assert_eq!(signer.subject, "O=Example,CN=Example Author");
+        assert_eq!(signer.issuer, "O=Example,CN=Example Root");
+        assert_eq!(signer.fingerprint.len(), 64);
+        // 2026-10-01T12:00:00Z
+        let signing_time = UNIX_EPOCH + Duration::from_secs(1_790_856_000);
+        assert_eq!(signer.signing_time, Some(signing_time));
+        let timestamp = signer.timestamp.as_ref().unwrap();
+        assert_eq!(timestamp.time, signing_time + Duration::from_secs(5));
+        assert_eq!(timestamp.subject, "O=Example,CN=Example Timestamps");
+        assert_eq!(signer.service_index, None);
+
+        let counter = signature.countersignature.as_ref().unwrap();
+        assert_eq!(counter.kind, SignatureKind::Repository);
+        assert_eq!(counter.subject, "O=Example,CN=Example Repository");
+        assert_eq!(
+            counter.service_index.as_deref(),
+            Some("https://nuget.example.com/v3/index.json")
+        );
+        assert_eq!(counter.owners, ["jane", "john"]);
+        assert!(counter.timestamp.is_some());
+        assert_ne!(counter.fingerprint, signer.fingerprint);
+
+        // The same signature from the reader
+        let read = PackageSignature::read(fixture("author-signed.nupkg")).unwrap();
+        assert_eq!(read, Some(signature));
+    }
+
+    #[test]
+    fn test_repository_signature() {
+        let signature = verify_package(fixture("repository-signed.nupkg")).unwrap();
+        assert_eq!(signature.hash_algorithm, HashAlgorithm::Sha512);
+        assert_eq!(signature.signer.kind, SignatureKind::Repository);
+        assert_eq!(signature.signer.timestamp, None);
+        assert_eq!(signature.signer.owners, ["jane", "john"]);
+        assert_eq!(signature.countersignature, None);
+    }
+
+    #[test]
+    fn test_tampered_package() {
+        let content = fs::read(fixture("author-signed.nupkg")).unwrap();
+        let bytes = NupkgReader::new(io::Cursor::new(content.as_slice()))
+            .unwrap()
+            .signature()
+            .unwrap()
+            .unwrap();
+        let signature = PackageSignature::from_der(&bytes).unwrap();
+        assert_eq!(signature.verify(&content), Ok(()));
+
+        // The stored content of a file of the package
+        let position = content.windows(3).position(|w| w == b"dll").unwrap();
+        let mut tampered = content.clone();
+        tampered[position..position + 3].copy_from_slice(b"DLL");
+        assert!(matches!(
+            signature.verify(&tampered),
+            Err(SignatureError::Mismatch(_))
+        ));
+
+        // The signature value of the author, the timestamp no longer matches it
+        let value = signature.signed_data.signer_infos.0.iter().next().unwrap();
+        let value = value.signature.as_bytes();
+        let position = bytes.windows(value.len()).position(|w| w == value).unwrap();
+        let mut tampered = bytes.clone();
+        tampered[position] ^= 0xFF;
+        let tampered = PackageSignature::from_der(&tampered).unwrap();
+        assert!(matches!(
+            tampered.verify(&content),
+            Err(SignatureError::Mismatch(_))
+        ));
+
+        // A crafted signature entry that starts beyond the end of the package
+        let archive = CentralDirectory::read(&content).unwrap();
+        let record = archive.offset + archive.size - archive.records.last().unwrap().length as u32;
+        let mut crafted = content.clone();
+        let at = record as usize + 42;
+        crafted[at..at + 4].copy_from_slice(&(content.len() as u32 + 1).to_le_bytes());
+        assert!(matches!(
+            unsigned_package(&crafted),
+            Err(SignatureError::Invalid(_))
+        ));
+
+        let unsigned = crate::nupkg::tests::test_package("Example", "1.0.0", &[]);
+        let dir = TempDir::new("unsigned");
+        let path = dir.join("example.nupkg");
+        fs::write(&path, unsigned).unwrap();
+        assert_eq!(verify_package(&path), Err(SignatureError::NotSigned));
+    }
+}
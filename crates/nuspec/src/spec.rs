@@ -320,6 +320,15 @@ pub enum License {
     File(String),
 }
 
+impl Display for License {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            License::Expression(expression) => write!(f, "{expression}"),
+            License::File(file) => write!(f, "file {file}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct Repository {
@@ -380,7 +389,9 @@ pub struct PackageType {
 pub enum KnownPackageType {
     Dependency,
     DotnetTool,
+    DotnetToolRidPackage,
     MSBuildSdk,
+    SymbolsPackage,
     Template,
     Custom(String),
 }
@@ -390,13 +401,29 @@ impl Display for KnownPackageType {
         match self {
             KnownPackageType::Dependency => write!(f, "Dependency"),
             KnownPackageType::DotnetTool => write!(f, "DotnetTool"),
+            KnownPackageType::DotnetToolRidPackage => write!(f, "DotnetToolRidPackage"),
             KnownPackageType::MSBuildSdk => write!(f, "MSBuildSdk"),
+            KnownPackageType::SymbolsPackage => write!(f, "SymbolsPackage"),
             KnownPackageType::Template => write!(f, "Template"),
             KnownPackageType::Custom(name) => write!(f, "{name}"),
         }
     }
 }
 
+impl From<&str> for KnownPackageType {
+    fn from(value: &str) -> Self {
+        match value {
+            "Dependency" => KnownPackageType::Dependency,
+            "DotnetTool" => KnownPackageType::DotnetTool,
+            "DotnetToolRidPackage" => KnownPackageType::DotnetToolRidPackage,
+            "MSBuildSdk" => KnownPackageType::MSBuildSdk,
+            "SymbolsPackage" => KnownPackageType::SymbolsPackage,
+            "Template" => KnownPackageType::Template,
+            _ => KnownPackageType::Custom(value.to_string()),
+        }
+    }
+}
+
 // It should be an enum type, but quick_xml + serde don't properly serialize/deserialize such a combination
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(deny_unknown_fields)]
@@ -557,7 +584,13 @@ pub struct ContentFile {
     /// The build action to assign to the content item for MSBuild, such as Content, None,
     /// Embedded Resource, Compile, etc.
     /// The default is Compile.
-    #[serde(rename = "@buildAction", alias = "build_action")]
+    #[serde(
+        rename = "@buildAction",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "optional_build_action",
+        alias = "build_action"
+    )]
     pub build_action: Option<BuildAction>,
 
     /// A Boolean indicating whether to copy content items to the build (or publish) output folder.
@@ -581,14 +614,56 @@ pub struct ContentFile {
     pub flatten: Option<bool>,
 }
 
+/// The MSBuild item type assigned to a content file.
+///
+/// The names are parsed case-insensitively, any unknown name is kept as a custom item type.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
-#[serde(deny_unknown_fields)]
 pub enum BuildAction {
+    #[default]
+    Compile,
     Content,
     None,
     EmbeddedResource,
-    #[default]
-    Compile,
+    Resource,
+    AdditionalFiles,
+    AndroidAsset,
+    AndroidResource,
+    BundleResource,
+    Custom(String),
+}
+
+impl Display for BuildAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildAction::Compile => write!(f, "Compile"),
+            BuildAction::Content => write!(f, "Content"),
+            BuildAction::None => write!(f, "None"),
+            BuildAction::EmbeddedResource => write!(f, "EmbeddedResource"),
+            BuildAction::Resource => write!(f, "Resource"),
+            BuildAction::AdditionalFiles => write!(f, "AdditionalFiles"),
+            BuildAction::AndroidAsset => write!(f, "AndroidAsset"),
+            BuildAction::AndroidResource => write!(f, "AndroidResource"),
+            BuildAction::BundleResource => write!(f, "BundleResource"),
+            BuildAction::Custom(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl From<&str> for BuildAction {
+    fn from(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "compile" => BuildAction::Compile,
+            "content" => BuildAction::Content,
+            "none" => BuildAction::None,
+            "embeddedresource" => BuildAction::EmbeddedResource,
+            "resource" => BuildAction::Resource,
+            "additionalfiles" => BuildAction::AdditionalFiles,
+            "androidasset" => BuildAction::AndroidAsset,
+            "androidresource" => BuildAction::AndroidResource,
+            "bundleresource" => BuildAction::BundleResource,
+            _ => BuildAction::Custom(value.to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -644,14 +719,32 @@ mod know_package_type {
         D: Deserializer<'de>,
     {
         let s: String = Deserialize::deserialize(deserializer)?;
-        match s.as_str() {
-            "Dependency" => Ok(KnownPackageType::Dependency),
-            "DotnetTool" => Ok(KnownPackageType::DotnetTool),
-            "MSBuildSdk" => Ok(KnownPackageType::MSBuildSdk),
-            "Template" => Ok(KnownPackageType::Template),
-            _ => Ok(KnownPackageType::Custom(s)),
+        Ok(KnownPackageType::from(s.as_str()))
+    }
+}
+
+mod optional_build_action {
+    use super::BuildAction;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Option<BuildAction>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if let Some(v) = value {
+            serializer.serialize_str(&v.to_string())
+        } else {
+            serializer.serialize_none()
         }
     }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<BuildAction>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        Ok(Some(BuildAction::from(s.as_str())))
+    }
 }
 
 mod comma_separated {
@@ -976,6 +1069,10 @@ mod tests {
                             name: KnownPackageType::MSBuildSdk,
                             version: None,
                         },
+                        PackageType {
+                            name: KnownPackageType::SymbolsPackage,
+                            version: None,
+                        },
                         PackageType {
                             name: KnownPackageType::Template,
                             version: None,
@@ -994,7 +1091,7 @@ mod tests {
         let serialized = quick_xml::se::to_string(&nuspec).unwrap();
         assert_eq!(
             serialized,
-            r#"<package><metadata><id>example-package</id><version>1.0.0</version><description>An example NuGet package</description><authors>Author One,Author Two</authors><packageTypes><packageType name="Dependency" version="1.0"/><packageType name="DotnetTool"/><packageType name="MSBuildSdk"/><packageType name="Template"/><packageType name="ContosoExtension"/></packageTypes></metadata></package>"#.to_string()
+            r#"<package><metadata><id>example-package</id><version>1.0.0</version><description>An example NuGet package</description><authors>Author One,Author Two</authors><packageTypes><packageType name="Dependency" version="1.0"/><packageType name="DotnetTool"/><packageType name="MSBuildSdk"/><packageType name="SymbolsPackage"/><packageType name="Template"/><packageType name="ContosoExtension"/></packageTypes></metadata></package>"#.to_string()
         );
 
         let deserialized: Package = quick_xml::de::from_str(&serialized).unwrap();
@@ -1284,4 +1381,71 @@ mod tests {
         let deserialized: Package = quick_xml::de::from_str(&serialized).unwrap();
         assert_eq!(deserialized, nuspec);
     }
+
+    #[test]
+    fn test_content_file_attributes_round_trip() {
+        let content_files = ContentFiles {
+            files: vec![
+                ContentFile {
+                    include: "cs/any/Bindings/*.cs".to_string(),
+                    exclude: Some(vec!["**/*.g.cs".to_string(), "obj/**".to_string()]),
+                    build_action: Some(BuildAction::Compile),
+                    copy_to_output: Some(false),
+                    flatten: Some(true),
+                },
+                ContentFile {
+                    include: "any/any/assets/**".to_string(),
+                    build_action: Some(BuildAction::AndroidAsset),
+                    copy_to_output: Some(true),
+                    ..Default::default()
+                },
+                ContentFile {
+                    include: "any/any/Resources/*.png".to_string(),
+                    build_action: Some(BuildAction::BundleResource),
+                    ..Default::default()
+                },
+                ContentFile {
+                    include: "any/any/schema.json".to_string(),
+                    build_action: Some(BuildAction::Custom("JsonSchema".to_string())),
+                    ..Default::default()
+                },
+                ContentFile {
+                    include: "any/any/readme.txt".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let serialized = quick_xml::se::to_string(&content_files).unwrap();
+        assert_eq!(
+            serialized,
+            r#"<ContentFiles><files include="cs/any/Bindings/*.cs" exclude="**/*.g.cs;obj/**" buildAction="Compile" copyToOutput="false" flatten="true"/><files include="any/any/assets/**" buildAction="AndroidAsset" copyToOutput="true"/><files include="any/any/Resources/*.png" buildAction="BundleResource"/><files include="any/any/schema.json" buildAction="JsonSchema"/><files include="any/any/readme.txt"/></ContentFiles>"#.to_string()
+        );
+
+        let deserialized: ContentFiles = quick_xml::de::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, content_files);
+    }
+
+    #[test]
+    fn test_build_action_case_insensitive() {
+        let serialized = r#"<ContentFiles><files include="a" buildAction="embeddedresource"/><files include="b" buildAction="NONE"/><files include="c" buildAction="androidResource"/></ContentFiles>"#;
+        let deserialized: ContentFiles = quick_xml::de::from_str(serialized).unwrap();
+        assert_eq!(
+            deserialized
+                .files
+                .into_iter()
+                .map(|f| f.build_action)
+                .collect::<Vec<_>>(),
+            vec![
+                Some(BuildAction::EmbeddedResource),
+                Some(BuildAction::None),
+                Some(BuildAction::AndroidResource),
+            ]
+        );
+        assert_eq!(BuildAction::from("Content").to_string(), "Content");
+        assert_eq!(
+            BuildAction::from("MyItemType"),
+            BuildAction::Custom("MyItemType".to_string())
+        );
+    }
 }
@@ -0,0 +1,47 @@
+//! The temporary directories and the fixture files of the tests.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, ops, path, process};
+
+/// A unique directory under the system temporary directory, removed with its content on drop,
+/// so a failed test does not leave it behind.
+pub(crate) struct TempDir(path::PathBuf);
+
+impl TempDir {
+    /// Creates an empty directory, the name tells the tests apart.
+    pub fn new(name: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("nuspec-{name}-{}-{count}", process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+}
+
+impl ops::Deref for TempDir {
+    type Target = path::Path;
+
+    fn deref(&self) -> &path::Path {
+        &self.0
+    }
+}
+
+impl AsRef<path::Path> for TempDir {
+    fn as_ref(&self) -> &path::Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Returns the path of a file of the `tests/fixtures` directory.
+#[cfg(feature = "signature")]
+pub(crate) fn fixture(name: &str) -> path::PathBuf {
+    path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
@@ -0,0 +1,409 @@
+use std::cmp::Ordering;
+use std::error;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// An error returned when a version or a version range can not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionError {
+    /// The text that failed to parse.
+    pub value: String,
+    /// The reason of the failure.
+    pub reason: &'static str,
+}
+
+impl Display for VersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid version `{}`: {}", self.value, self.reason)
+    }
+}
+
+impl error::Error for VersionError {}
+
+/// A NuGet package version: up to four numeric parts, optional pre-release labels and
+/// optional build metadata, such as `1.2.3`, `1.2.3.4` or `1.0.0-beta.2+sha.5114f85`.
+///
+/// Versions are compared the way NuGet does: the pre-release labels are compared
+/// case-insensitively, numeric labels are compared as numbers and the build metadata is ignored.
+///
+/// See [Package versioning](https://learn.microsoft.com/en-us/nuget/concepts/package-versioning).
+#[derive(Debug, Clone, Default)]
+pub struct NuGetVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub revision: u64,
+    /// The pre-release labels, such as `["beta", "2"]` for `1.0.0-beta.2`.
+    pub release: Vec<String>,
+    /// The build metadata, such as `sha.5114f85` for `1.0.0+sha.5114f85`.
+    pub metadata: Option<String>,
+}
+
+impl NuGetVersion {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        NuGetVersion {
+            major,
+            minor,
+            patch,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if the version has pre-release labels.
+    pub fn is_prerelease(&self) -> bool {
+        !self.release.is_empty()
+    }
+
+    /// Returns the normalized form of the version used by NuGet feeds and folders:
+    /// leading zeros are removed, the fourth part is omitted if it is zero and
+    /// the build metadata is dropped.
+    pub fn to_normalized_string(&self) -> String {
+        let mut s = format!("{}.{}.{}", self.major, self.minor, self.patch);
+        if self.revision != 0 {
+            s.push_str(&format!(".{}", self.revision));
+        }
+        if self.is_prerelease() {
+            s.push('-');
+            s.push_str(&self.release.join("."));
+        }
+        s
+    }
+}
+
+impl Display for NuGetVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_normalized_string())?;
+        if let Some(metadata) = &self.metadata {
+            write!(f, "+{metadata}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NuGetVersion {
+    type Err = VersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |reason| VersionError {
+            value: s.to_string(),
+            reason,
+        };
+        let value = s.trim();
+        let (value, metadata) = match value.split_once('+') {
+            Some((_, "")) => return Err(error("empty build metadata")),
+            Some((value, metadata)) => (value, Some(metadata.to_string())),
+            None => (value, None),
+        };
+        let (numbers, release) = match value.split_once('-') {
+            Some((numbers, release)) => {
+                let labels: Vec<String> = release.split('.').map(str::to_string).collect();
+                if labels.iter().any(|l| {
+                    l.is_empty() || !l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                }) {
+                    return Err(error("invalid pre-release label"));
+                }
+                (numbers, labels)
+            }
+            None => (value, Vec::new()),
+        };
+        let parts: Vec<&str> = numbers.split('.').collect();
+        if parts.len() > 4 {
+            return Err(error("too many numeric parts"));
+        }
+        let mut numbers = [0u64; 4];
+        for (i, part) in parts.iter().enumerate() {
+            if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                return Err(error("expected a number"));
+            }
+            numbers[i] = part.parse().map_err(|_| error("number is too large"))?;
+        }
+        Ok(NuGetVersion {
+            major: numbers[0],
+            minor: numbers[1],
+            patch: numbers[2],
+            revision: numbers[3],
+            release,
+            metadata,
+        })
+    }
+}
+
+impl PartialEq for NuGetVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NuGetVersion {}
+
+impl Hash for NuGetVersion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_normalized_string().to_ascii_lowercase().hash(state);
+    }
+}
+
+impl PartialOrd for NuGetVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NuGetVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch, self.revision)
+            .cmp(&(other.major, other.minor, other.patch, other.revision))
+            .then_with(|| match (self.is_prerelease(), other.is_prerelease()) {
+                (false, false) => Ordering::Equal,
+                (false, true) => Ordering::Greater,
+                (true, false) => Ordering::Less,
+                (true, true) => compare_labels(&self.release, &other.release),
+            })
+    }
+}
+
+fn compare_labels(a: &[String], b: &[String]) -> Ordering {
+    for (a, b) in a.iter().zip(b) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase()),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// A range of acceptable versions of a dependency in the interval notation,
+/// such as `1.0` (at least 1.0), `[1.0]` (exactly 1.0), `(,1.0]` or `[1.0,2.0)`.
+///
+/// See [Version ranges](https://learn.microsoft.com/en-us/nuget/concepts/package-versioning#version-ranges).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VersionRange {
+    pub min: Option<NuGetVersion>,
+    pub min_inclusive: bool,
+    pub max: Option<NuGetVersion>,
+    pub max_inclusive: bool,
+}
+
+impl VersionRange {
+    /// Returns true if the version satisfies the range.
+    pub fn contains(&self, version: &NuGetVersion) -> bool {
+        let above_min = match &self.min {
+            Some(min) if self.min_inclusive => version >= min,
+            Some(min) => version > min,
+            None => true,
+        };
+        let below_max = match &self.max {
+            Some(max) if self.max_inclusive => version <= max,
+            Some(max) => version < max,
+            None => true,
+        };
+        above_min && below_max
+    }
+
+    /// Returns true if every version accepted by `other` is accepted by this range too.
+    pub fn is_superset_of(&self, other: &VersionRange) -> bool {
+        let min_ok = match (&self.min, &other.min) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(a), Some(b)) => a < b || (a == b && (self.min_inclusive || !other.min_inclusive)),
+        };
+        let max_ok = match (&self.max, &other.max) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(a), Some(b)) => a > b || (a == b && (self.max_inclusive || !other.max_inclusive)),
+        };
+        min_ok && max_ok
+    }
+
+    /// Returns the highest version of the given ones that satisfies the range.
+    pub fn find_best_match<'a, I>(&self, versions: I) -> Option<&'a NuGetVersion>
+    where
+        I: IntoIterator<Item = &'a NuGetVersion>,
+    {
+        versions.into_iter().filter(|v| self.contains(v)).max()
+    }
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.min, &self.max) {
+            (Some(min), None) if self.min_inclusive => write!(f, "{min}"),
+            (Some(min), Some(max)) if min == max && self.min_inclusive && self.max_inclusive => {
+                write!(f, "[{min}]")
+            }
+            (min, max) => {
+                write!(f, "{}", if self.min_inclusive { '[' } else { '(' })?;
+                if let Some(min) = min {
+                    write!(f, "{min}")?;
+                }
+                write!(f, ", ")?;
+                if let Some(max) = max {
+                    write!(f, "{max}")?;
+                }
+                write!(f, "{}", if self.max_inclusive { ']' } else { ')' })
+            }
+        }
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = VersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |reason| VersionError {
+            value: s.to_string(),
+            reason,
+        };
+        let value = s.trim();
+        if value.is_empty() {
+            return Err(error("empty version range"));
+        }
+        let min_inclusive = match value.chars().next() {
+            Some('[') => true,
+            Some('(') => false,
+            _ => {
+                // A bare version means "this version or higher"
+                return Ok(VersionRange {
+                    min: Some(value.parse()?),
+                    min_inclusive: true,
+                    ..Default::default()
+                });
+            }
+        };
+        let max_inclusive = match value.chars().last() {
+            Some(']') => true,
+            Some(')') => false,
+            _ => return Err(error("missing closing bracket")),
+        };
+        let inner = &value[1..value.len() - 1];
+        let parse = |v: &str| -> Result<Option<NuGetVersion>, VersionError> {
+            let v = v.trim();
+            if v.is_empty() {
+                Ok(None)
+            } else {
+                v.parse().map(Some)
+            }
+        };
+        let range = match inner.split_once(',') {
+            Some((min, max)) => VersionRange {
+                min: parse(min)?,
+                min_inclusive,
+                max: parse(max)?,
+                max_inclusive,
+            },
+            None => {
+                if !min_inclusive || !max_inclusive {
+                    return Err(error("an exact version must use square brackets"));
+                }
+                let version = parse(inner)?.ok_or_else(|| error("empty version range"))?;
+                VersionRange {
+                    min: Some(version.clone()),
+                    min_inclusive,
+                    max: Some(version),
+                    max_inclusive,
+                }
+            }
+        };
+        if range.min.is_none() && range.max.is_none() {
+            return Err(error("at least one bound is required"));
+        }
+        if let (Some(min), Some(max)) = (&range.min, &range.max)
+            && (min > max || (min == max && !(min_inclusive && max_inclusive)))
+        {
+            return Err(error("the range is empty"));
+        }
+        Ok(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> NuGetVersion {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_version_parse_and_normalize() {
+        assert_eq!(v("1.0").to_normalized_string(), "1.0.0");
+        assert_eq!(v("01.02.03.0").to_normalized_string(), "1.2.3");
+        assert_eq!(v("1.2.3.4").to_normalized_string(), "1.2.3.4");
+        assert_eq!(
+            v("1.0.0-Beta.2+sha.5114f85").to_normalized_string(),
+            "1.0.0-Beta.2"
+        );
+        assert_eq!(v("1.0.0-beta+build").to_string(), "1.0.0-beta+build");
+        assert!("1.0.0.0.0".parse::<NuGetVersion>().is_err());
+        assert!("1.x".parse::<NuGetVersion>().is_err());
+        assert!("1.0-".parse::<NuGetVersion>().is_err());
+        assert!("".parse::<NuGetVersion>().is_err());
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.1") < v("1.0.0-alpha.beta"));
+        assert!(v("1.0.0-beta.2") < v("1.0.0-beta.11"));
+        assert!(v("1.0.0-rc.1") < v("1.0.0"));
+        assert!(v("1.0.0") < v("1.0.0.1"));
+        assert_eq!(v("1.0"), v("1.0.0.0"));
+        assert_eq!(v("1.0.0-BETA"), v("1.0.0-beta"));
+        assert_eq!(v("1.0.0+a"), v("1.0.0+b"));
+    }
+
+    #[test]
+    fn test_range_parse_and_display() {
+        for (input, expected) in [
+            ("1.0", "1.0.0"),
+            ("[1.0]", "[1.0.0]"),
+            ("(,1.0]", "(, 1.0.0]"),
+            ("(1.0,)", "(1.0.0, )"),
+            ("[1,2)", "[1.0.0, 2.0.0)"),
+            (" [1.0 , 2.0] ", "[1.0.0, 2.0.0]"),
+        ] {
+            let range: VersionRange = input.parse().unwrap();
+            assert_eq!(range.to_string(), expected, "{input}");
+            assert_eq!(range.to_string().parse::<VersionRange>().unwrap(), range);
+        }
+        for input in ["", "[1.0", "(1.0)", "(,)", "[2.0,1.0]", "(1.0,1.0]"] {
+            assert!(input.parse::<VersionRange>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_range_contains() {
+        let range: VersionRange = "[1.0,2.0)".parse().unwrap();
+        assert!(range.contains(&v("1.0")));
+        assert!(range.contains(&v("1.9.9")));
+        assert!(!range.contains(&v("2.0")));
+        assert!(range.contains(&v("2.0.0-beta")));
+        assert!(!range.contains(&v("0.9")));
+
+        let range: VersionRange = "1.0".parse().unwrap();
+        assert!(range.contains(&v("100.0")));
+        assert_eq!(
+            range.find_best_match(&[v("0.1"), v("1.5"), v("1.2")]),
+            Some(&v("1.5"))
+        );
+    }
+
+    #[test]
+    fn test_range_superset() {
+        let narrow: VersionRange = "[1,2)".parse().unwrap();
+        let wide: VersionRange = "[1,3)".parse().unwrap();
+        let open: VersionRange = "1.0".parse().unwrap();
+        assert!(wide.is_superset_of(&narrow));
+        assert!(!narrow.is_superset_of(&wide));
+        assert!(open.is_superset_of(&wide));
+        assert!(narrow.is_superset_of(&narrow));
+        let exclusive: VersionRange = "(1,2)".parse().unwrap();
+        assert!(narrow.is_superset_of(&exclusive));
+        assert!(!exclusive.is_superset_of(&narrow));
+    }
+}
@@ -0,0 +1,341 @@
+#!/usr/bin/env python3
+"""Generates the signed package fixtures of the `signature` tests.
+
+The packages are signed with self-made certificates, independently of the Rust code,
+in the format of the NuGet signed packages:
+
+* `author-signed.nupkg`: an RSA author signature with a timestamp and an ECDSA
+  repository countersignature, itself timestamped;
+* `repository-signed.nupkg`: an ECDSA P-384 repository primary signature of a
+  SHA-512 content hash, without a timestamp;
+* `author.pem` and `author.pfx`, protected by the `nuspec` password: the certificate
+  of the author with its chain and its PKCS#8 RSA key, to sign packages in the tests;
+* `timestamps.pem`: the certificate of the timestamp authority and its SEC1 EC key,
+  for the timestamp authority standing in for a real one in the tests.
+
+Requires the `cryptography` package: `python3 generate.py` in this folder.
+"""
+
+import base64
+import datetime
+import hashlib
+import io
+import zipfile
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec, padding, rsa
+from cryptography.hazmat.primitives.serialization import pkcs12
+from cryptography.x509.oid import ExtendedKeyUsageOID, NameOID
+
+DATA = "1.2.840.113549.1.7.1"
+SIGNED_DATA = "1.2.840.113549.1.7.2"
+TST_INFO = "1.2.840.113549.1.9.16.1.4"
+CONTENT_TYPE = "1.2.840.113549.1.9.3"
+MESSAGE_DIGEST = "1.2.840.113549.1.9.4"
+SIGNING_TIME = "1.2.840.113549.1.9.5"
+COUNTERSIGNATURE = "1.2.840.113549.1.9.6"
+TIMESTAMP_TOKEN = "1.2.840.113549.1.9.16.2.14"
+COMMITMENT_TYPE = "1.2.840.113549.1.9.16.2.16"
+SIGNING_CERTIFICATE_V2 = "1.2.840.113549.1.9.16.2.47"
+PROOF_OF_ORIGIN = "1.2.840.113549.1.9.16.6.1"
+PROOF_OF_RECEIPT = "1.2.840.113549.1.9.16.6.2"
+SERVICE_INDEX = "1.3.6.1.4.1.311.84.2.1.1.1"
+PACKAGE_OWNERS = "1.3.6.1.4.1.311.84.2.1.1.2"
+RSA_ENCRYPTION = "1.2.840.113549.1.1.1"
+ECDSA = {"sha256": "1.2.840.10045.4.3.2", "sha384": "1.2.840.10045.4.3.3"}
+DIGESTS = {
+    "sha256": ("2.16.840.1.101.3.4.2.1", hashes.SHA256()),
+    "sha384": ("2.16.840.1.101.3.4.2.2", hashes.SHA384()),
+    "sha512": ("2.16.840.1.101.3.4.2.3", hashes.SHA512()),
+}
+
+SIGNING_TIME_VALUE = datetime.datetime(2026, 10, 1, 12, 0, 0, tzinfo=datetime.timezone.utc)
+TIMESTAMP_VALUE = datetime.datetime(2026, 10, 1, 12, 0, 5, tzinfo=datetime.timezone.utc)
+ZIP_TIME = (2026, 10, 1, 12, 0, 0)
+
+
+def der_length(n):
+    if n < 0x80:
+        return bytes([n])
+    b = n.to_bytes((n.bit_length() + 7) // 8, "big")
+    return bytes([0x80 | len(b)]) + b
+
+
+def tlv(tag, *parts):
+    content = b"".join(parts)
+    return bytes([tag]) + der_length(len(content)) + content
+
+
+def seq(*parts):
+    return tlv(0x30, *parts)
+
+
+def set_of(*parts):
+    return tlv(0x31, *sorted(parts))
+
+
+def implicit(n, encoded):
+    # Replaces the tag of a constructed value by a context-specific one
+    return bytes([0xA0 | n]) + encoded[1:]
+
+
+def explicit(n, *parts):
+    return tlv(0xA0 | n, *parts)
+
+
+def oid(dotted):
+    arcs = [int(a) for a in dotted.split(".")]
+    out = b""
+    for arc in [40 * arcs[0] + arcs[1]] + arcs[2:]:
+        enc = [arc & 0x7F]
+        arc >>= 7
+        while arc:
+            enc.insert(0, 0x80 | (arc & 0x7F))
+            arc >>= 7
+        out += bytes(enc)
+    return tlv(0x06, out)
+
+
+def integer(n):
+    return tlv(0x02, n.to_bytes((n.bit_length() + 8) // 8, "big"))
+
+
+def octets(b):
+    return tlv(0x04, b)
+
+
+def utf8(s):
+    return tlv(0x0C, s.encode())
+
+
+def ia5(s):
+    return tlv(0x16, s.encode())
+
+
+def utc_time(t):
+    return tlv(0x17, t.strftime("%y%m%d%H%M%SZ").encode())
+
+
+def generalized_time(t):
+    return tlv(0x18, t.strftime("%Y%m%d%H%M%SZ").encode())
+
+
+def attribute(kind, *values):
+    return seq(oid(kind), set_of(*values))
+
+
+def digest(name, data):
+    return hashlib.new(name, data).digest()
+
+
+def certificate(subject, key, issuer=None, issuer_key=None, usage=None):
+    name = x509.Name([
+        x509.NameAttribute(NameOID.COMMON_NAME, subject),
+        x509.NameAttribute(NameOID.ORGANIZATION_NAME, "Example"),
+    ])
+    builder = (
+        x509.CertificateBuilder()
+        .subject_name(name)
+        .issuer_name(issuer.subject if issuer else name)
+        .public_key(key.public_key())
+        .serial_number(x509.random_serial_number())
+        .not_valid_before(datetime.datetime(2026, 1, 1, tzinfo=datetime.timezone.utc))
+        .not_valid_after(datetime.datetime(2126, 1, 1, tzinfo=datetime.timezone.utc))
+        .add_extension(x509.BasicConstraints(ca=issuer is None, path_length=None), critical=True)
+    )
+    if usage:
+        builder = builder.add_extension(x509.ExtendedKeyUsage([usage]), critical=True)
+    return builder.sign(issuer_key or key, hashes.SHA256())
+
+
+class Signer:
+    def __init__(self, cert, key, hash_name):
+        self.cert = cert
+        self.key = key
+        self.hash_name = hash_name
+
+    def der(self):
+        return self.cert.public_bytes(serialization.Encoding.DER)
+
+    def signing_certificate(self):
+        # The ESSCertIDv2 with the default SHA-256 algorithm
+        return attribute(SIGNING_CERTIFICATE_V2, seq(seq(seq(octets(digest("sha256", self.der()))))))
+
+    def signer_info(self, attributes, unsigned=None):
+        hash_oid, hash_algorithm = DIGESTS[self.hash_name]
+        signed = set_of(*attributes)
+        if isinstance(self.key, rsa.RSAPrivateKey):
+            algorithm = seq(oid(RSA_ENCRYPTION), tlv(0x05))
+            value = self.key.sign(signed, padding.PKCS1v15(), hash_algorithm)
+        else:
+            algorithm = seq(oid(ECDSA[self.hash_name]))
+            value = self.key.sign(signed, ec.ECDSA(hash_algorithm))
+        parts = [
+            integer(1),
+            seq(self.cert.issuer.public_bytes(), integer(self.cert.serial_number)),
+            seq(oid(hash_oid)),
+            implicit(0, signed),
+            algorithm,
+            octets(value),
+        ]
+        if unsigned:
+            parts.append(implicit(1, set_of(*unsigned(value))))
+        return seq(*parts), value
+
+
+def signed_data(content_type, content, signer_info, signer, certificates):
+    hash_oid = DIGESTS[signer.hash_name][0]
+    return seq(
+        oid(SIGNED_DATA),
+        explicit(
+            0,
+            seq(
+                integer(3 if content_type != DATA else 1),
+                set_of(seq(oid(hash_oid))),
+                seq(oid(content_type), explicit(0, octets(content))),
+                implicit(0, set_of(*[c.der() for c in certificates])),
+                set_of(signer_info),
+            ),
+        ),
+    )
+
+
+def timestamp(tsa, value, serial):
+    tst_info = seq(
+        integer(1),
+        oid("1.2.3.4.1"),
+        seq(seq(oid(DIGESTS["sha256"][0])), octets(digest("sha256", value))),
+        integer(serial),
+        generalized_time(TIMESTAMP_VALUE),
+        seq(integer(1)),
+        integer(0x1234 + serial),
+    )
+    info, _ = tsa.signer_info([
+        attribute(CONTENT_TYPE, oid(TST_INFO)),
+        attribute(MESSAGE_DIGEST, octets(digest(tsa.hash_name, tst_info))),
+        tsa.signing_certificate(),
+    ])
+    token = signed_data(TST_INFO, tst_info, info, tsa, [tsa])
+    return attribute(TIMESTAMP_TOKEN, token)
+
+
+def repository_attributes():
+    return [
+        attribute(SERVICE_INDEX, ia5("https://nuget.example.com/v3/index.json")),
+        attribute(PACKAGE_OWNERS, seq(utf8("jane"), utf8("john"))),
+    ]
+
+
+def package():
+    buffer = io.BytesIO()
+    with zipfile.ZipFile(buffer, "w") as z:
+        entries = [
+            ("Example.nuspec", '<?xml version="1.0" encoding="utf-8"?><package><metadata>'
+             "<id>Example</id><version>1.0.0</version><description>Test</description>"
+             "<authors>Jane Doe</authors></metadata></package>"),
+            ("lib/net8.0/Example.dll", "dll"),
+            ("_rels/.rels", ""),
+            ("[Content_Types].xml", ""),
+        ]
+        for name, content in entries:
+            z.writestr(zipfile.ZipInfo(name, ZIP_TIME), content)
+    return buffer.getvalue()
+
+
+def sign(name, primary, attributes, unsigned, content_hash, certificates):
+    unsigned_package = package()
+    hash_oid = DIGESTS[content_hash][0]
+    content = (
+        f"Version:1\r\n\r\n{hash_oid}-Hash:"
+        f"{base64.b64encode(digest(content_hash, unsigned_package)).decode()}\r\n\r\n"
+    ).encode()
+    info, _ = primary.signer_info(
+        [
+            attribute(CONTENT_TYPE, oid(DATA)),
+            attribute(SIGNING_TIME, utc_time(SIGNING_TIME_VALUE)),
+            attribute(MESSAGE_DIGEST, octets(digest(primary.hash_name, content))),
+            primary.signing_certificate(),
+        ]
+        + attributes,
+        unsigned,
+    )
+    signature = signed_data(DATA, content, info, primary, certificates)
+    buffer = io.BytesIO(unsigned_package)
+    # The appended signature entry is the last one, as NuGet requires
+    with zipfile.ZipFile(buffer, "a") as z:
+        z.writestr(zipfile.ZipInfo(".signature.p7s", ZIP_TIME), signature)
+    with open(name, "wb") as f:
+        f.write(buffer.getvalue())
+
+
+def write_pem(name, certificates, key, key_format):
+    with open(name, "wb") as f:
+        for cert in certificates:
+            f.write(cert.public_bytes(serialization.Encoding.PEM))
+        f.write(key.private_bytes(serialization.Encoding.PEM, key_format, serialization.NoEncryption()))
+
+
+def main():
+    ca_key = ec.generate_private_key(ec.SECP256R1())
+    ca = certificate("Example Root", ca_key)
+
+    def issue(subject, key, usage):
+        return certificate(subject, key, ca, ca_key, usage)
+
+    author_key = rsa.generate_private_key(public_exponent=65537, key_size=2048)
+    author = Signer(issue("Example Author", author_key, ExtendedKeyUsageOID.CODE_SIGNING), author_key, "sha256")
+    tsa_key = ec.generate_private_key(ec.SECP256R1())
+    tsa = Signer(issue("Example Timestamps", tsa_key, ExtendedKeyUsageOID.TIME_STAMPING), tsa_key, "sha256")
+    repository_key = ec.generate_private_key(ec.SECP256R1())
+    repository = Signer(
+        issue("Example Repository", repository_key, ExtendedKeyUsageOID.CODE_SIGNING), repository_key, "sha256"
+    )
+
+    write_pem("author.pem", [author.cert, ca], author_key, serialization.PrivateFormat.PKCS8)
+    with open("author.pfx", "wb") as f:
+        f.write(
+            pkcs12.serialize_key_and_certificates(
+                b"author", author_key, author.cert, [ca], serialization.BestAvailableEncryption(b"nuspec")
+            )
+        )
+    write_pem("timestamps.pem", [tsa.cert], tsa_key, serialization.PrivateFormat.TraditionalOpenSSL)
+
+    def countersignature(value):
+        info, _ = repository.signer_info(
+            [
+                attribute(SIGNING_TIME, utc_time(SIGNING_TIME_VALUE)),
+                attribute(MESSAGE_DIGEST, octets(digest(repository.hash_name, value))),
+                attribute(COMMITMENT_TYPE, seq(oid(PROOF_OF_RECEIPT))),
+                repository.signing_certificate(),
+            ]
+            + repository_attributes(),
+            lambda counter_value: [timestamp(tsa, counter_value, 2)],
+        )
+        return attribute(COUNTERSIGNATURE, info)
+
+    sign(
+        "author-signed.nupkg",
+        author,
+        [attribute(COMMITMENT_TYPE, seq(oid(PROOF_OF_ORIGIN)))],
+        lambda value: [timestamp(tsa, value, 1), countersignature(value)],
+        "sha256",
+        [author, repository],
+    )
+
+    primary_key = ec.generate_private_key(ec.SECP384R1())
+    primary = Signer(
+        issue("Example Repository Primary", primary_key, ExtendedKeyUsageOID.CODE_SIGNING), primary_key, "sha384"
+    )
+    sign(
+        "repository-signed.nupkg",
+        primary,
+        [attribute(COMMITMENT_TYPE, seq(oid(PROOF_OF_RECEIPT)))] + repository_attributes(),
+        None,
+        "sha512",
+        [primary],
+    )
+
+
+if __name__ == "__main__":
+    main()